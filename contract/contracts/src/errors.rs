@@ -0,0 +1,19 @@
+use soroban_sdk::contracterror;
+
+/// Failures of the staking entry points: setup (`AlreadyInitialized`,
+/// `NotInitialized`), then stake amounts and balances for staking,
+/// unstaking, compounding and slashing.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    InvalidAmount = 3,
+    InsufficientAmountForTier = 4,
+    UserNotFound = 5,
+    CompoundTokenMismatch = 6,
+    InsufficientBalance = 7,
+    SlashExceedsBalance = 8,
+    NoBalance = 9,
+}
@@ -1,3 +1,4 @@
+use crate::errors::Error;
 use crate::types::{Config, DataKey, Tier, UserInfo};
 use soroban_sdk::{Address, Env};
 
@@ -10,8 +11,11 @@ pub fn extend_instance(env: &Env) {
         .extend_ttl(TTL_INSTANCE, TTL_INSTANCE);
 }
 
-pub fn read_config(env: &Env) -> Config {
-    env.storage().instance().get(&DataKey::Config).unwrap()
+pub fn read_config(env: &Env) -> Result<Config, Error> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Config)
+        .ok_or(Error::NotInitialized)
 }
 
 pub fn write_config(env: &Env, config: &Config) {
@@ -1,6 +1,7 @@
 #![cfg(test)]
 
 use crate::contract::{StakingContract, StakingContractClient};
+use crate::errors::Error;
 use soroban_sdk::{
     testutils::{Address as _, Ledger},
     token, Address, Env,
@@ -79,3 +80,61 @@ fn test_staking_lifecycle() {
     // Has 998_900. Now has 998_900 + 400 = 999_300.
     assert_eq!(token.balance(&user1), 999_300);
 }
+
+#[test]
+fn test_error_codes() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+
+    let token = create_token_contract(&env, &admin);
+    let token_admin = token::StellarAssetClient::new(&env, &token.address);
+    token_admin.mint(&user1, &1_000_000);
+
+    let contract_id = env.register(StakingContract, ());
+    let client = StakingContractClient::new(&env, &contract_id);
+
+    assert_eq!(
+        client.try_set_tier(&1, &1000, &150),
+        Err(Ok(Error::NotInitialized))
+    );
+
+    client.initialize(&admin, &token.address, &token.address, &10);
+    assert_eq!(
+        client.try_initialize(&admin, &token.address, &token.address, &10),
+        Err(Ok(Error::AlreadyInitialized))
+    );
+
+    client.set_tier(&1, &1000, &150);
+    assert_eq!(
+        client.try_stake(&user1, &0, &0, &1),
+        Err(Ok(Error::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_stake(&user1, &500, &0, &1),
+        Err(Ok(Error::InsufficientAmountForTier))
+    );
+    assert_eq!(
+        client.try_emergency_withdraw(&user2),
+        Err(Ok(Error::UserNotFound))
+    );
+
+    client.stake(&user1, &2000, &0, &1);
+    assert_eq!(
+        client.try_unstake(&user1, &5000),
+        Err(Ok(Error::InsufficientBalance))
+    );
+    assert_eq!(
+        client.try_slash(&user1, &5000),
+        Err(Ok(Error::SlashExceedsBalance))
+    );
+
+    client.emergency_withdraw(&user1);
+    assert_eq!(
+        client.try_emergency_withdraw(&user1),
+        Err(Ok(Error::NoBalance))
+    );
+}
@@ -0,0 +1,18 @@
+use soroban_sdk::contracterror;
+
+/// Failures of the event factory.  Errors raised by a deployed ticket
+/// contract during `create_event` are not passed through; they surface as
+/// `EventInitFailed`.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    FactoryPaused = 3,
+    EventNotOwned = 4,
+    /// The deployed ticket contract rejected `initialize`.
+    EventInitFailed = 5,
+    /// Platform fee above 10000 bps.
+    InvalidFee = 6,
+}
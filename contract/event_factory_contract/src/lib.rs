@@ -3,8 +3,10 @@
 #[cfg(test)]
 mod test;
 
+pub mod errors;
 mod storage_types;
-use storage_types::DataKey;
+use errors::Error;
+use storage_types::{DataKey, PlatformFee};
 
 use soroban_sdk::{
     contract, contractimpl, symbol_short, vec, Address, BytesN, Env, IntoVal, String, Vec,
@@ -13,11 +15,15 @@ use soroban_sdk::{
 #[contract]
 pub struct EventFactoryContract;
 
+const TTL_INSTANCE: u32 = 17280 * 30; // 30 days
+const TTL_PERSISTENT: u32 = 17280 * 90; // 90 days
+const BPS_DENOMINATOR: u32 = 10_000;
+
 #[contractimpl]
 impl EventFactoryContract {
-    pub fn initialize(e: Env, admin: Address, event_wasm_hash: BytesN<32>) {
+    pub fn initialize(e: Env, admin: Address, event_wasm_hash: BytesN<32>) -> Result<(), Error> {
         if e.storage().instance().has(&DataKey::Admin) {
-            panic!("already initialized");
+            return Err(Error::AlreadyInitialized);
         }
 
         e.storage().instance().set(&DataKey::Admin, &admin);
@@ -25,26 +31,49 @@ impl EventFactoryContract {
             .instance()
             .set(&DataKey::EventWasmHash, &event_wasm_hash);
         e.storage().instance().set(&DataKey::Paused, &false);
+        extend_instance(&e);
+        Ok(())
     }
 
-    pub fn pause(e: Env) {
-        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
+    pub fn pause(e: Env) -> Result<(), Error> {
+        read_admin(&e)?.require_auth();
+        extend_instance(&e);
         e.storage().instance().set(&DataKey::Paused, &true);
+        Ok(())
     }
 
-    pub fn unpause(e: Env) {
-        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
+    pub fn unpause(e: Env) -> Result<(), Error> {
+        read_admin(&e)?.require_auth();
+        extend_instance(&e);
         e.storage().instance().set(&DataKey::Paused, &false);
+        Ok(())
     }
 
-    pub fn update_wasm_hash(e: Env, new_wasm_hash: BytesN<32>) {
-        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
+    pub fn update_wasm_hash(e: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        read_admin(&e)?.require_auth();
+        extend_instance(&e);
         e.storage()
             .instance()
             .set(&DataKey::EventWasmHash, &new_wasm_hash);
+        Ok(())
+    }
+
+    /// Admin-only: the fee taken from events created from now on.  Events
+    /// already deployed keep the fee they were created with.
+    pub fn set_platform_fee(e: Env, recipient: Address, fee_bps: u32) -> Result<(), Error> {
+        read_admin(&e)?.require_auth();
+        extend_instance(&e);
+        if fee_bps > BPS_DENOMINATOR {
+            return Err(Error::InvalidFee);
+        }
+        e.storage()
+            .instance()
+            .set(&DataKey::PlatformFee, &PlatformFee { recipient, fee_bps });
+        Ok(())
+    }
+
+    pub fn get_platform_fee(e: Env) -> Option<PlatformFee> {
+        e.storage().instance().get(&DataKey::PlatformFee)
     }
 
     pub fn create_event(
@@ -55,15 +84,24 @@ impl EventFactoryContract {
         uri: String,
         start_time: u64,
         refund_cutoff_time: u64,
-    ) -> Address {
+    ) -> Result<Address, Error> {
         organizer.require_auth();
+        extend_instance(&e);
 
-        let paused: bool = e.storage().instance().get(&DataKey::Paused).unwrap();
+        let paused: bool = e
+            .storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .ok_or(Error::NotInitialized)?;
         if paused {
-            panic!("factory is paused");
+            return Err(Error::FactoryPaused);
         }
 
-        let wasm_hash: BytesN<32> = e.storage().instance().get(&DataKey::EventWasmHash).unwrap();
+        let wasm_hash: BytesN<32> = e
+            .storage()
+            .instance()
+            .get(&DataKey::EventWasmHash)
+            .ok_or(Error::NotInitialized)?;
 
         let counter: u32 = e
             .storage()
@@ -87,7 +125,9 @@ impl EventFactoryContract {
 
         // Max length for symbol_short is 9, initialize is 10.
         // Use soroban_sdk::Symbol::new(&e, "initialize")
-        e.invoke_contract::<()>(
+        // `try_` form so a rejected initialize surfaces as a factory error
+        // instead of aborting with the ticket contract's code.
+        let init = e.try_invoke_contract::<(), soroban_sdk::Error>(
             &event_contract_id,
             &soroban_sdk::Symbol::new(&e, "initialize"),
             vec![
@@ -100,16 +140,31 @@ impl EventFactoryContract {
                 refund_cutoff_time.into_val(&e),
             ],
         );
+        if !matches!(init, Ok(Ok(()))) {
+            return Err(Error::EventInitFailed);
+        }
 
-        let key = DataKey::OrganizerEvents(organizer.clone());
-        let mut events: Vec<Address> = e
-            .storage()
-            .persistent()
-            .get(&key)
-            .unwrap_or_else(|| Vec::new(&e));
+        // The factory is the fee authority, so only it can change the fee
+        // later; the organizer co-signs this first setting.
+        if let Some(fee) = Self::get_platform_fee(e.clone()) {
+            let set_fee = e.try_invoke_contract::<(), soroban_sdk::Error>(
+                &event_contract_id,
+                &soroban_sdk::Symbol::new(&e, "set_platform_fee"),
+                vec![
+                    &e,
+                    e.current_contract_address().to_val(),
+                    fee.recipient.to_val(),
+                    fee.fee_bps.into_val(&e),
+                ],
+            );
+            if !matches!(set_fee, Ok(Ok(()))) {
+                return Err(Error::EventInitFailed);
+            }
+        }
 
+        let mut events = read_organizer_events(&e, &organizer);
         events.push_back(event_contract_id.clone());
-        e.storage().persistent().set(&key, &events);
+        write_organizer_events(&e, &organizer, &events);
 
         // Emit an event (using soroban events)
         #[allow(deprecated)]
@@ -118,23 +173,37 @@ impl EventFactoryContract {
             event_contract_id.clone(),
         );
 
-        event_contract_id
+        Ok(event_contract_id)
     }
 
     pub fn get_events_by_organizer(e: Env, organizer: Address) -> Vec<Address> {
-        let key = DataKey::OrganizerEvents(organizer);
-        e.storage().persistent().get(&key).unwrap_or(Vec::new(&e))
+        read_organizer_events(&e, &organizer)
     }
 
-    pub fn transfer_event_ownership(e: Env, event: Address, from: Address, to: Address) {
+    /// Extend the TTL of the factory instance and of each of `keys` that
+    /// exists.  Anyone may call this; returns how many of `keys` were found.
+    pub fn bump(e: Env, keys: Vec<DataKey>) -> u32 {
+        extend_instance(&e);
+        let mut found = 0;
+        for key in keys.iter() {
+            if e.storage().persistent().has(&key) {
+                extend_persistent(&e, &key);
+                found += 1;
+            }
+        }
+        found
+    }
+
+    pub fn transfer_event_ownership(
+        e: Env,
+        event: Address,
+        from: Address,
+        to: Address,
+    ) -> Result<(), Error> {
         from.require_auth();
+        extend_instance(&e);
 
-        let from_key = DataKey::OrganizerEvents(from.clone());
-        let from_events: Vec<Address> = e
-            .storage()
-            .persistent()
-            .get(&from_key)
-            .unwrap_or(Vec::new(&e));
+        let from_events = read_organizer_events(&e, &from);
 
         let mut new_from_events = Vec::new(&e);
         let mut found = false;
@@ -147,22 +216,54 @@ impl EventFactoryContract {
         }
 
         if !found {
-            panic!("event not owned by 'from' address");
+            return Err(Error::EventNotOwned);
         }
 
-        e.storage().persistent().set(&from_key, &new_from_events);
+        write_organizer_events(&e, &from, &new_from_events);
 
-        let to_key = DataKey::OrganizerEvents(to.clone());
-        let mut to_events: Vec<Address> = e
-            .storage()
-            .persistent()
-            .get(&to_key)
-            .unwrap_or(Vec::new(&e));
+        let mut to_events = read_organizer_events(&e, &to);
         to_events.push_back(event.clone());
-        e.storage().persistent().set(&to_key, &to_events);
+        write_organizer_events(&e, &to, &to_events);
 
         #[allow(deprecated)]
         e.events()
             .publish((symbol_short!("transfer"), event), (from, to));
+        Ok(())
     }
 }
+
+fn read_admin(e: &Env) -> Result<Address, Error> {
+    e.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(Error::NotInitialized)
+}
+
+fn extend_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(TTL_INSTANCE, TTL_INSTANCE);
+}
+
+fn extend_persistent(e: &Env, key: &DataKey) {
+    e.storage()
+        .persistent()
+        .extend_ttl(key, TTL_PERSISTENT, TTL_PERSISTENT);
+}
+
+fn read_organizer_events(e: &Env, organizer: &Address) -> Vec<Address> {
+    let key = DataKey::OrganizerEvents(organizer.clone());
+    match e.storage().persistent().get(&key) {
+        Some(events) => {
+            extend_persistent(e, &key);
+            events
+        }
+        None => Vec::new(e),
+    }
+}
+
+fn write_organizer_events(e: &Env, organizer: &Address, events: &Vec<Address>) {
+    let key = DataKey::OrganizerEvents(organizer.clone());
+    e.storage().persistent().set(&key, events);
+    extend_persistent(e, &key);
+}
@@ -1,5 +1,13 @@
 use soroban_sdk::{contracttype, Address};
 
+/// Fee the platform keeps from the net proceeds of every event it deploys.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct PlatformFee {
+    pub recipient: Address,
+    pub fee_bps: u32,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
@@ -7,4 +15,5 @@ pub enum DataKey {
     EventWasmHash,            // BytesN<32> of the event contract WASM
     Paused,                   // bool indicating if new events can be created
     OrganizerEvents(Address), // Mapping from an organizer Address to Vec<Address> of event contracts
+    PlatformFee,              // PlatformFee applied to newly created events
 }
@@ -1,14 +1,37 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{testutils::Address as _, Bytes, Env, String};
-
+use soroban_sdk::{
+    contracttype,
+    testutils::{
+        storage::{Instance as _, Persistent as _},
+        Address as _, Ledger,
+    },
+    Bytes, Env, String, Symbol,
+};
+
+// Release build of `ticket_contract`.  Rebuild it whenever the ticket
+// contract changes, from `contract/ticket_contract`:
+//   cargo build --target wasm32v1-none --release
+//   cp target/wasm32v1-none/release/ticket_contract.wasm \
+//      ../event_factory_contract/src/mock/
 const TICKET_WASM: &[u8] = include_bytes!("./mock/ticket_contract.wasm");
 
+/// The ticket contract's `PlatformFee`, which also names the fee authority.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct TicketPlatformFee {
+    authority: Address,
+    recipient: Address,
+    fee_bps: u32,
+}
+
 // Helper function to setup the environment and factory
 fn setup_test() -> (Env, Address, Address, BytesN<32>) {
     let e = Env::default();
     e.mock_all_auths();
+    // Parsing the full ticket contract wasm exceeds the default test budget
+    e.cost_estimate().budget().reset_unlimited();
 
     let admin = Address::generate(&e);
     let organizer = Address::generate(&e);
@@ -34,7 +57,7 @@ fn test_initialize() {
     factory.initialize(&admin, &wasm_hash);
     // double init should fail
     let res = factory.try_initialize(&admin, &wasm_hash);
-    assert!(res.is_err());
+    assert_eq!(res, Err(Ok(Error::AlreadyInitialized)));
 }
 
 #[test]
@@ -66,7 +89,6 @@ fn test_create_event() {
 }
 
 #[test]
-#[should_panic(expected = "factory is paused")]
 fn test_pause_unpause() {
     let (e, admin, organizer, wasm_hash) = setup_test();
 
@@ -80,10 +102,25 @@ fn test_pause_unpause() {
     let symbol = String::from_str(&e, "TST");
     let uri = String::from_str(&e, "https://example.com");
 
-    // This should panic
+    let res = factory.try_create_event(&organizer, &name, &symbol, &uri, &1000, &500);
+    assert_eq!(res, Err(Ok(Error::FactoryPaused)));
+
+    factory.unpause();
     factory.create_event(&organizer, &name, &symbol, &uri, &1000, &500);
 }
 
+#[test]
+fn test_transfer_not_owned() {
+    let (e, admin, organizer, wasm_hash) = setup_test();
+    let factory_id = e.register(EventFactoryContract, ());
+    let factory = EventFactoryContractClient::new(&e, &factory_id);
+    factory.initialize(&admin, &wasm_hash);
+
+    let stranger = Address::generate(&e);
+    let res = factory.try_transfer_event_ownership(&factory_id, &organizer, &stranger);
+    assert_eq!(res, Err(Ok(Error::EventNotOwned)));
+}
+
 #[test]
 fn test_update_wasm_hash() {
     let (e, admin, _organizer, wasm_hash) = setup_test();
@@ -124,3 +161,89 @@ fn test_transfer_ownership() {
     assert_eq!(to_events_after.len(), 1);
     assert_eq!(to_events_after.get(0).unwrap(), event_id);
 }
+
+#[test]
+fn test_bump_keeps_organizer_events_alive() {
+    const DAY: u32 = 17280;
+    let (e, admin, organizer, wasm_hash) = setup_test();
+    let factory_id = e.register(EventFactoryContract, ());
+    let factory = EventFactoryContractClient::new(&e, &factory_id);
+    factory.initialize(&admin, &wasm_hash);
+
+    let name = String::from_str(&e, "Test Event");
+    let symbol = String::from_str(&e, "TST");
+    let uri = String::from_str(&e, "https://example.com");
+    let event_id = factory.create_event(&organizer, &name, &symbol, &uri, &1000, &500);
+
+    let key = DataKey::OrganizerEvents(organizer.clone());
+    e.as_contract(&factory_id, || {
+        assert_eq!(e.storage().persistent().get_ttl(&key), TTL_PERSISTENT);
+        assert_eq!(e.storage().instance().get_ttl(), TTL_INSTANCE);
+    });
+
+    // Bumped every 25 days, the list outlives the 90-day TTL it was
+    // written with
+    let keys = soroban_sdk::vec![&e, key.clone(), DataKey::OrganizerEvents(admin.clone())];
+    for _ in 0..4 {
+        e.ledger().with_mut(|li| li.sequence_number += 25 * DAY);
+        e.as_contract(&factory_id, || {
+            assert_eq!(
+                e.storage().persistent().get_ttl(&key),
+                TTL_PERSISTENT - 25 * DAY
+            );
+            assert_eq!(e.storage().instance().get_ttl(), TTL_INSTANCE - 25 * DAY);
+        });
+        assert_eq!(factory.bump(&keys), 1);
+    }
+    assert_eq!(
+        factory.get_events_by_organizer(&organizer),
+        soroban_sdk::vec![&e, event_id]
+    );
+}
+
+#[test]
+fn test_platform_fee_applies_to_new_events() {
+    let (e, admin, organizer, wasm_hash) = setup_test();
+    let factory_id = e.register(EventFactoryContract, ());
+    let factory = EventFactoryContractClient::new(&e, &factory_id);
+    factory.initialize(&admin, &wasm_hash);
+    assert_eq!(factory.get_platform_fee(), None);
+
+    let platform = Address::generate(&e);
+    assert_eq!(
+        factory.try_set_platform_fee(&platform, &10_001),
+        Err(Ok(Error::InvalidFee))
+    );
+    factory.set_platform_fee(&platform, &250);
+    assert_eq!(
+        factory.get_platform_fee(),
+        Some(PlatformFee {
+            recipient: platform.clone(),
+            fee_bps: 250,
+        })
+    );
+
+    let name = String::from_str(&e, "Test Event");
+    let symbol = String::from_str(&e, "TST");
+    let uri = String::from_str(&e, "https://example.com");
+    let event_id = factory.create_event(&organizer, &name, &symbol, &uri, &1000, &500);
+    assert_eq!(
+        factory.get_events_by_organizer(&organizer),
+        soroban_sdk::vec![&e, event_id.clone()]
+    );
+
+    // The factory stays the fee authority of the deployed ticket contract
+    let ticket_fee: Option<TicketPlatformFee> = e.invoke_contract(
+        &event_id,
+        &Symbol::new(&e, "get_platform_fee"),
+        soroban_sdk::vec![&e],
+    );
+    assert_eq!(
+        ticket_fee,
+        Some(TicketPlatformFee {
+            authority: factory_id,
+            recipient: platform,
+            fee_bps: 250,
+        })
+    );
+}
@@ -0,0 +1,35 @@
+use soroban_sdk::contracterror;
+
+/// Failures of the identity registry, grouped by range: registry state
+/// (1-9), DIDs and their claims (10-19), delegations (20-29) and attendance
+/// recorded by event contracts (30-39).
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    ContractPaused = 3,
+    Unauthorized = 4,
+
+    // DIDs and claims
+    DidAlreadyExists = 10,
+    DidNotFound = 11,
+    MaxClaimsReached = 12,
+    ClaimNotFound = 13,
+    ClaimAlreadyVerified = 14,
+    ClaimRevoked = 15,
+    ClaimAlreadyRevoked = 16,
+
+    // Delegations
+    DelegationExists = 20,
+    MaxDelegationsReached = 21,
+    DelegationNotFound = 22,
+    DelegationRevoked = 23,
+    DelegationExpired = 24,
+    PermissionNotGranted = 25,
+
+    // Attendance
+    IssuerNotApproved = 30,
+    AttendanceAlreadyRecorded = 31,
+}
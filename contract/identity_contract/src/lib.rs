@@ -3,11 +3,13 @@
 #[cfg(test)]
 mod test;
 
+pub mod errors;
 mod storage_types;
-use storage_types::{DataKey, DIDDocument, Claim, Credential, Delegation, Revocation};
+use errors::Error;
+use storage_types::{DataKey, DIDDocument, Claim, Delegation, Revocation};
 
 use soroban_sdk::{
-    contract, contractimpl, vec, Address, Bytes, BytesN, Env, String, Symbol, Vec, crypto,
+    contract, contractimpl, xdr::ToXdr, Address, Bytes, BytesN, Env, String, Symbol, Vec,
 };
 
 #[contract]
@@ -24,27 +26,28 @@ const VERIFIED_CREDENTIAL_SCORE: u32 = 30;
 #[contractimpl]
 impl IdentityRegistryContract {
     /// Initialize the identity registry contract
-    pub fn initialize(e: Env, admin: Address) {
+    pub fn initialize(e: Env, admin: Address) -> Result<(), Error> {
         if e.storage().instance().has(&DataKey::Admin) {
-            panic!("already initialized");
+            return Err(Error::AlreadyInitialized);
         }
         
         e.storage().instance().set(&DataKey::Admin, &admin);
         e.storage().instance().set(&DataKey::Paused, &false);
         e.storage().instance().set(&DataKey::TotalDIDs, &0u32);
         extend_instance(&e);
+        Ok(())
     }
 
     /// Create a new DID for a user
-    pub fn create_did(e: Env, user: Address, public_key: BytesN<32>) -> String {
+    pub fn create_did(e: Env, user: Address, public_key: BytesN<32>) -> Result<String, Error> {
         user.require_auth();
         
-        check_paused(&e);
+        check_paused(&e)?;
         
         let did_string = generate_did(&e, &user);
         
         if e.storage().persistent().has(&DataKey::DID(did_string.clone())) {
-            panic!("DID already exists for this address");
+            return Err(Error::DidAlreadyExists);
         }
         
         let did_document = DIDDocument {
@@ -66,7 +69,7 @@ impl IdentityRegistryContract {
         e.storage().instance().set(&DataKey::TotalDIDs, &(total_dids + 1));
         
         extend_persistent(&e, &DataKey::DID(did_string.clone()));
-        extend_persistent(&e, &DataKey::AddressToDID(user));
+        extend_persistent(&e, &DataKey::AddressToDID(user.clone()));
         
         // Emit event
         #[allow(deprecated)]
@@ -75,29 +78,30 @@ impl IdentityRegistryContract {
             did_string.clone(),
         );
         
-        did_string
+        Ok(did_string)
     }
 
     /// Add a claim to a DID (Twitter, GitHub, email, etc.)
     pub fn add_claim(
         e: Env, 
+        caller: Address,
         did: String, 
         claim_type: String, 
         claim_value: String, 
         proof: Bytes
-    ) -> u32 {
-        let mut did_doc = get_did_document(&e, &did);
-        let caller = e.invoker();
+    ) -> Result<u32, Error> {
+        caller.require_auth();
+        let mut did_doc = get_did_document(&e, &did)?;
         
         // Only controller or delegate can add claims
         if caller != did_doc.controller {
-            check_delegation(&e, &did, &caller, &String::from_str(&e, "add_claim"));
+            check_delegation(&e, &did, &caller, &String::from_str(&e, "add_claim"))?;
         }
         
-        check_paused(&e);
+        check_paused(&e)?;
         
         if did_doc.claims.len() >= MAX_CLAIMS_PER_DID {
-            panic!("Maximum claims limit reached");
+            return Err(Error::MaxClaimsReached);
         }
         
         let claim_id = e.storage().instance().get(&DataKey::NextClaimId).unwrap_or(1u32);
@@ -118,7 +122,7 @@ impl IdentityRegistryContract {
         e.storage().persistent().set(&DataKey::DID(did.clone()), &did_doc);
         e.storage().instance().set(&DataKey::NextClaimId, &(claim_id + 1));
         
-        extend_persistent(&e, &DataKey::DID(did));
+        extend_persistent(&e, &DataKey::DID(did.clone()));
         
         // Emit event
         #[allow(deprecated)]
@@ -127,15 +131,14 @@ impl IdentityRegistryContract {
             claim_id,
         );
         
-        claim_id
+        Ok(claim_id)
     }
 
     /// Verify a claim with off-chain oracle integration
-    pub fn verify_claim(e: Env, did: String, claim_id: u32, oracle_signature: Bytes) {
-        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
+    pub fn verify_claim(e: Env, did: String, claim_id: u32, oracle_signature: Bytes) -> Result<(), Error> {
+        read_admin(&e)?.require_auth();
         
-        let mut did_doc = get_did_document(&e, &did);
+        let mut did_doc = get_did_document(&e, &did)?;
         let mut claim = None;
         let mut claim_index = 0u32;
         
@@ -148,47 +151,49 @@ impl IdentityRegistryContract {
             }
         }
         
-        let mut claim_obj = claim.unwrap_or_else(|| panic!("Claim not found"));
+        let mut claim_obj = claim.ok_or(Error::ClaimNotFound)?;
         if claim_obj.verified {
-            panic!("Claim already verified");
+            return Err(Error::ClaimAlreadyVerified);
         }
         if claim_obj.revoked {
-            panic!("Claim has been revoked");
+            return Err(Error::ClaimRevoked);
         }
         
         // Verify oracle signature (simplified - in practice would verify against oracle public key)
         verify_oracle_signature(&e, &did, claim_id, &oracle_signature);
         
         claim_obj.verified = true;
+        let claim_type = claim_obj.claim_type.clone();
         did_doc.claims.set(claim_index, claim_obj);
         did_doc.reputation_score += VERIFIED_CREDENTIAL_SCORE;
         did_doc.updated = e.ledger().timestamp();
         
         e.storage().persistent().set(&DataKey::DID(did.clone()), &did_doc);
-        extend_persistent(&e, &DataKey::DID(did));
+        extend_persistent(&e, &DataKey::DID(did.clone()));
         
         // Emit event
         #[allow(deprecated)]
         e.events().publish(
-            (Symbol::new(&e, "claim_verified"), did.clone()),
-            (claim_id, claim_obj.claim_type),
+            (Symbol::new(&e, "claim_verified"), did),
+            (claim_id, claim_type),
         );
+        Ok(())
     }
 
     /// Revoke a compromised credential
-    pub fn revoke_claim(e: Env, did: String, claim_id: u32, reason: String) {
-        let did_doc = get_did_document(&e, &did);
-        let caller = e.invoker();
+    pub fn revoke_claim(e: Env, caller: Address, did: String, claim_id: u32, reason: String) -> Result<(), Error> {
+        caller.require_auth();
+        let did_doc = get_did_document(&e, &did)?;
         
         // Only controller, delegate, or admin can revoke
         if caller != did_doc.controller {
-            let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+            let admin = read_admin(&e)?;
             if caller != admin {
-                check_delegation(&e, &did, &caller, &String::from_str(&e, "revoke_claim"));
+                check_delegation(&e, &did, &caller, &String::from_str(&e, "revoke_claim"))?;
             }
         }
         
-        let mut did_doc = get_did_document(&e, &did);
+        let mut did_doc = get_did_document(&e, &did)?;
         let mut claim = None;
         let mut claim_index = 0u32;
         
@@ -201,9 +206,9 @@ impl IdentityRegistryContract {
             }
         }
         
-        let mut claim_obj = claim.unwrap_or_else(|| panic!("Claim not found"));
+        let mut claim_obj = claim.ok_or(Error::ClaimNotFound)?;
         if claim_obj.revoked {
-            panic!("Claim already revoked");
+            return Err(Error::ClaimAlreadyRevoked);
         }
         
         claim_obj.revoked = true;
@@ -230,24 +235,24 @@ impl IdentityRegistryContract {
             (Symbol::new(&e, "claim_revoked"), did_doc.id),
             claim_id,
         );
+        Ok(())
     }
 
     /// Add reputation score for event attendance
-    pub fn add_event_attendance(e: Env, did: String, event_id: String, score: u32) {
-        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
+    pub fn add_event_attendance(e: Env, did: String, event_id: String, score: u32) -> Result<(), Error> {
+        read_admin(&e)?.require_auth();
         
-        let mut did_doc = get_did_document(&e, &did);
+        let mut did_doc = get_did_document(&e, &did)?;
         
         // Add attendance score
         did_doc.reputation_score += score.min(EVENT_ATTENDANCE_SCORE);
         did_doc.updated = e.ledger().timestamp();
         
         e.storage().persistent().set(&DataKey::DID(did.clone()), &did_doc);
-        extend_persistent(&e, &DataKey::DID(did));
+        extend_persistent(&e, &DataKey::DID(did.clone()));
         
         // Store attendance record
-        let attendance_key = DataKey::EventAttendance(did.clone(), event_id);
+        let attendance_key = DataKey::EventAttendance(did, event_id.clone());
         e.storage().persistent().set(&attendance_key, &e.ledger().timestamp());
         extend_persistent(&e, &attendance_key);
         
@@ -257,6 +262,54 @@ impl IdentityRegistryContract {
             (Symbol::new(&e, "attendance_recorded"), did_doc.id),
             (event_id, score),
         );
+        Ok(())
+    }
+
+    /// Approve or revoke an event contract that records attendance itself
+    pub fn set_attendance_issuer(e: Env, issuer: Address, approved: bool) -> Result<(), Error> {
+        read_admin(&e)?.require_auth();
+        
+        if approved {
+            e.storage().persistent().set(&DataKey::AttendanceIssuer(issuer.clone()), &true);
+            extend_persistent(&e, &DataKey::AttendanceIssuer(issuer));
+        } else {
+            e.storage().persistent().remove(&DataKey::AttendanceIssuer(issuer));
+        }
+        Ok(())
+    }
+
+    /// Record attendance when a holder claims an attendance badge from an
+    /// approved event contract.  Counts toward the reputation score once per
+    /// DID and event, however many badges the DID claims there.
+    pub fn record_attendance(e: Env, event: Address, did: String) -> Result<(), Error> {
+        event.require_auth();
+        
+        if !e.storage().persistent().has(&DataKey::AttendanceIssuer(event.clone())) {
+            return Err(Error::IssuerNotApproved);
+        }
+        
+        let attendance_key = DataKey::BadgeAttendance(did.clone(), event.clone());
+        if e.storage().persistent().has(&attendance_key) {
+            return Err(Error::AttendanceAlreadyRecorded);
+        }
+        
+        let mut did_doc = get_did_document(&e, &did)?;
+        did_doc.reputation_score += EVENT_ATTENDANCE_SCORE;
+        did_doc.updated = e.ledger().timestamp();
+        
+        e.storage().persistent().set(&DataKey::DID(did.clone()), &did_doc);
+        extend_persistent(&e, &DataKey::DID(did.clone()));
+        
+        e.storage().persistent().set(&attendance_key, &e.ledger().timestamp());
+        extend_persistent(&e, &attendance_key);
+        
+        // Emit event
+        #[allow(deprecated)]
+        e.events().publish(
+            (Symbol::new(&e, "badge_attendance"), did_doc.id),
+            event,
+        );
+        Ok(())
     }
 
     /// Delegate identity management rights
@@ -266,20 +319,20 @@ impl IdentityRegistryContract {
         delegate: Address, 
         permissions: Vec<String>,
         expiry: u64
-    ) {
-        let did_doc = get_did_document(&e, &did);
+    ) -> Result<(), Error> {
+        let did_doc = get_did_document(&e, &did)?;
         did_doc.controller.require_auth();
         
-        check_paused(&e);
+        check_paused(&e)?;
         
         if e.storage().persistent().has(&DataKey::Delegation(did.clone(), delegate.clone())) {
-            panic!("Delegation already exists");
+            return Err(Error::DelegationExists);
         }
         
         // Check delegation limit
         let existing_delegations = get_delegations(&e, &did);
         if existing_delegations.len() >= MAX_DELEGATIONS_PER_DID {
-            panic!("Maximum delegations limit reached");
+            return Err(Error::MaxDelegationsReached);
         }
         
         let delegation = Delegation {
@@ -291,7 +344,7 @@ impl IdentityRegistryContract {
         };
         
         e.storage().persistent().set(&DataKey::Delegation(did.clone(), delegate.clone()), &delegation);
-        extend_persistent(&e, &DataKey::Delegation(did.clone(), delegate));
+        extend_persistent(&e, &DataKey::Delegation(did, delegate.clone()));
         
         // Emit event
         #[allow(deprecated)]
@@ -299,27 +352,26 @@ impl IdentityRegistryContract {
             (Symbol::new(&e, "delegation_added"), did_doc.id),
             delegate,
         );
+        Ok(())
     }
 
     /// Revoke a delegation
-    pub fn revoke_delegation(e: Env, did: String, delegate: Address) {
-        let did_doc = get_did_document(&e, &did);
-        let caller = e.invoker();
+    pub fn revoke_delegation(e: Env, caller: Address, did: String, delegate: Address) -> Result<(), Error> {
+        caller.require_auth();
+        let did_doc = get_did_document(&e, &did)?;
         
         // Only controller or the delegate themselves can revoke
         if caller != did_doc.controller && caller != delegate {
-            panic!("Unauthorized to revoke delegation");
-        }
-        
-        if !e.storage().persistent().has(&DataKey::Delegation(did.clone(), delegate.clone())) {
-            panic!("Delegation not found");
+            return Err(Error::Unauthorized);
         }
         
-        let mut delegation: Delegation = e.storage().persistent().get(&DataKey::Delegation(did.clone(), delegate.clone())).unwrap();
+        let mut delegation: Delegation = e.storage().persistent()
+            .get(&DataKey::Delegation(did.clone(), delegate.clone()))
+            .ok_or(Error::DelegationNotFound)?;
         delegation.revoked = true;
         
         e.storage().persistent().set(&DataKey::Delegation(did.clone(), delegate.clone()), &delegation);
-        extend_persistent(&e, &DataKey::Delegation(did, delegate));
+        extend_persistent(&e, &DataKey::Delegation(did, delegate.clone()));
         
         // Emit event
         #[allow(deprecated)]
@@ -327,11 +379,12 @@ impl IdentityRegistryContract {
             (Symbol::new(&e, "delegation_revoked"), did_doc.id),
             delegate,
         );
+        Ok(())
     }
 
     /// Deactivate a DID
-    pub fn deactivate_did(e: Env, did: String) {
-        let did_doc = get_did_document(&e, &did);
+    pub fn deactivate_did(e: Env, did: String) -> Result<(), Error> {
+        let did_doc = get_did_document(&e, &did)?;
         did_doc.controller.require_auth();
         
         let mut updated_doc = did_doc;
@@ -347,10 +400,11 @@ impl IdentityRegistryContract {
             (Symbol::new(&e, "did_deactivated"), updated_doc.id),
             (),
         );
+        Ok(())
     }
 
     /// Resolve a DID to get the DID document
-    pub fn resolve_did(e: Env, did: String) -> DIDDocument {
+    pub fn resolve_did(e: Env, did: String) -> Result<DIDDocument, Error> {
         get_did_document(&e, &did)
     }
 
@@ -360,25 +414,25 @@ impl IdentityRegistryContract {
     }
 
     /// Get reputation score
-    pub fn get_reputation_score(e: Env, did: String) -> u32 {
-        let did_doc = get_did_document(&e, &did);
-        did_doc.reputation_score
+    pub fn get_reputation_score(e: Env, did: String) -> Result<u32, Error> {
+        let did_doc = get_did_document(&e, &did)?;
+        Ok(did_doc.reputation_score)
     }
 
     /// Check if a claim is verified
-    pub fn is_claim_verified(e: Env, did: String, claim_id: u32) -> bool {
-        let did_doc = get_did_document(&e, &did);
+    pub fn is_claim_verified(e: Env, did: String, claim_id: u32) -> Result<bool, Error> {
+        let did_doc = get_did_document(&e, &did)?;
         for claim in did_doc.claims.iter() {
             if claim.id == claim_id {
-                return claim.verified && !claim.revoked;
+                return Ok(claim.verified && !claim.revoked);
             }
         }
-        false
+        Ok(false)
     }
 
     /// Get verified claims of a specific type
-    pub fn get_verified_claims_by_type(e: Env, did: String, claim_type: String) -> Vec<Claim> {
-        let did_doc = get_did_document(&e, &did);
+    pub fn get_verified_claims_by_type(e: Env, did: String, claim_type: String) -> Result<Vec<Claim>, Error> {
+        let did_doc = get_did_document(&e, &did)?;
         let mut verified_claims = Vec::new(&e);
         
         for claim in did_doc.claims.iter() {
@@ -387,20 +441,20 @@ impl IdentityRegistryContract {
             }
         }
         
-        verified_claims
+        Ok(verified_claims)
     }
 
     /// Admin functions
-    pub fn pause(e: Env) {
-        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
+    pub fn pause(e: Env) -> Result<(), Error> {
+        read_admin(&e)?.require_auth();
         e.storage().instance().set(&DataKey::Paused, &true);
+        Ok(())
     }
 
-    pub fn unpause(e: Env) {
-        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
+    pub fn unpause(e: Env) -> Result<(), Error> {
+        read_admin(&e)?.require_auth();
         e.storage().instance().set(&DataKey::Paused, &false);
+        Ok(())
     }
 
     pub fn get_total_dids(e: Env) -> u32 {
@@ -417,66 +471,69 @@ fn extend_persistent(e: &Env, key: &DataKey) {
     e.storage().persistent().extend_ttl(key, TTL_PERSISTENT, TTL_PERSISTENT);
 }
 
-fn check_paused(e: &Env) {
-    let paused: bool = e.storage().instance().get(&DataKey::Paused).unwrap();
+fn read_admin(e: &Env) -> Result<Address, Error> {
+    e.storage().instance().get(&DataKey::Admin).ok_or(Error::NotInitialized)
+}
+
+fn check_paused(e: &Env) -> Result<(), Error> {
+    let paused: bool = e.storage().instance().get(&DataKey::Paused).ok_or(Error::NotInitialized)?;
     if paused {
-        panic!("contract is paused");
+        return Err(Error::ContractPaused);
     }
+    Ok(())
 }
 
 fn generate_did(e: &Env, address: &Address) -> String {
-    // Generate DID in format: did:stellar:<address_hash>
-    let address_bytes = address.to_bytes();
-    let hash = crypto::sha256(e, &address_bytes);
-    let hash_str = hex_encode(&hash);
-    String::from_str(e, &format!("did:stellar:{}", hash_str))
-}
-
-fn hex_encode(bytes: &BytesN<32>) -> String {
-    // Simplified hex encoding - in practice use proper hex encoding
-    let mut result = String::from_str(&bytes.env(), "0x");
-    // This is a placeholder - real implementation would convert bytes to hex string
-    result
+    // Generate DID in format: did:stellar:<hex sha256 of the address XDR>
+    const PREFIX: &[u8] = b"did:stellar:";
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let hash = e.crypto().sha256(&address.clone().to_xdr(e)).to_array();
+    
+    let mut did = [0u8; PREFIX.len() + 64];
+    did[..PREFIX.len()].copy_from_slice(PREFIX);
+    for (i, byte) in hash.iter().enumerate() {
+        did[PREFIX.len() + 2 * i] = HEX[(byte >> 4) as usize];
+        did[PREFIX.len() + 2 * i + 1] = HEX[(byte & 0x0f) as usize];
+    }
+    String::from_bytes(e, &did)
 }
 
-fn get_did_document(e: &Env, did: &String) -> DIDDocument {
+fn get_did_document(e: &Env, did: &String) -> Result<DIDDocument, Error> {
     e.storage().persistent().get(&DataKey::DID(did.clone()))
-        .unwrap_or_else(|| panic!("DID not found"))
+        .ok_or(Error::DidNotFound)
 }
 
-fn get_delegations(e: &Env, did: &String) -> Vec<Delegation> {
-    let mut delegations = Vec::new(&e.env());
+fn get_delegations(e: &Env, _did: &String) -> Vec<Delegation> {
+    let delegations = Vec::new(e);
     // In practice, you'd iterate through storage to find all delegations for a DID
     // This is a simplified approach
     delegations
 }
 
-fn check_delegation(e: &Env, did: &String, delegate: &Address, permission: &String) -> bool {
-    if !e.storage().persistent().has(&DataKey::Delegation(did.clone(), delegate.clone())) {
-        panic!("No delegation found");
-    }
-    
-    let delegation: Delegation = e.storage().persistent().get(&DataKey::Delegation(did.clone(), delegate.clone())).unwrap();
+fn check_delegation(e: &Env, did: &String, delegate: &Address, permission: &String) -> Result<(), Error> {
+    let delegation: Delegation = e.storage().persistent()
+        .get(&DataKey::Delegation(did.clone(), delegate.clone()))
+        .ok_or(Error::DelegationNotFound)?;
     
     if delegation.revoked {
-        panic!("Delegation has been revoked");
+        return Err(Error::DelegationRevoked);
     }
     
     if delegation.expiry < e.ledger().timestamp() {
-        panic!("Delegation has expired");
+        return Err(Error::DelegationExpired);
     }
     
     // Check if permission is granted
     for perm in delegation.permissions.iter() {
         if &perm == permission {
-            return true;
+            return Ok(());
         }
     }
     
-    panic!("Permission not granted in delegation");
+    Err(Error::PermissionNotGranted)
 }
 
-fn verify_oracle_signature(e: &Env, did: &String, claim_id: u32, signature: &Bytes) -> bool {
+fn verify_oracle_signature(_e: &Env, _did: &String, _claim_id: u32, _signature: &Bytes) -> bool {
     // Simplified oracle signature verification
     // In practice, this would verify the signature against the oracle's public key
     // and check that it contains the correct DID and claim_id
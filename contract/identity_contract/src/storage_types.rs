@@ -1,5 +1,7 @@
 use soroban_sdk::{contracttype, Address, Bytes, String, Vec, BytesN};
 
+// Variant names are part of the storage keys, so `DID` keeps its casing
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
@@ -12,6 +14,8 @@ pub enum DataKey {
     Delegation(String, Address),              // Delegation - delegations for a DID
     Revocation(String, u32),                  // Revocation - revoked claims
     EventAttendance(String, String),          // u64 - event attendance timestamps
+    AttendanceIssuer(Address),                // bool - event contracts allowed to record attendance
+    BadgeAttendance(String, Address),         // u64 - attendance recorded by an event contract
 }
 
 #[derive(Clone)]
@@ -1,12 +1,17 @@
 #![cfg(test)]
 
+extern crate std;
+
 use super::*;
-use soroban_sdk::{testutils::Address as _, Address, Bytes, BytesN, Env, String, Vec};
+use crate::errors::Error;
+use soroban_sdk::{testutils::Address as _, vec, Address, Bytes, BytesN, Env, String, Vec};
+use std::string::ToString;
 
 #[test]
 fn test_initialize() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, IdentityRegistryContract);
+    env.mock_all_auths();
+    let contract_id = env.register(IdentityRegistryContract, ());
     let client = IdentityRegistryContractClient::new(&env, &contract_id);
     
     let admin = Address::generate(&env);
@@ -15,13 +20,14 @@ fn test_initialize() {
     
     // Should panic on re-initialization
     let result = client.try_initialize(&admin);
-    assert!(result.is_err());
+    assert_eq!(result, Err(Ok(Error::AlreadyInitialized)));
 }
 
 #[test]
 fn test_create_did() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, IdentityRegistryContract);
+    env.mock_all_auths();
+    let contract_id = env.register(IdentityRegistryContract, ());
     let client = IdentityRegistryContractClient::new(&env, &contract_id);
     
     let admin = Address::generate(&env);
@@ -37,13 +43,14 @@ fn test_create_did() {
     
     // Should fail if same user tries again
     let result = client.try_create_did(&user, &public_key);
-    assert!(result.is_err());
+    assert_eq!(result, Err(Ok(Error::DidAlreadyExists)));
 }
 
 #[test]
 fn test_add_claim() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, IdentityRegistryContract);
+    env.mock_all_auths();
+    let contract_id = env.register(IdentityRegistryContract, ());
     let client = IdentityRegistryContractClient::new(&env, &contract_id);
     
     let admin = Address::generate(&env);
@@ -57,14 +64,15 @@ fn test_add_claim() {
     let claim_value = String::from_str(&env, "@user123");
     let proof = Bytes::from_slice(&env, &[1, 2, 3, 4]);
     
-    let claim_id = client.add_claim(&did, &claim_type, &claim_value, &proof);
+    let claim_id = client.add_claim(&user, &did, &claim_type, &claim_value, &proof);
     assert_eq!(claim_id, 1);
 }
 
 #[test]
 fn test_verify_claim() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, IdentityRegistryContract);
+    env.mock_all_auths();
+    let contract_id = env.register(IdentityRegistryContract, ());
     let client = IdentityRegistryContractClient::new(&env, &contract_id);
     
     let admin = Address::generate(&env);
@@ -78,7 +86,7 @@ fn test_verify_claim() {
     let claim_value = String::from_str(&env, "user123");
     let proof = Bytes::from_slice(&env, &[1, 2, 3, 4]);
     
-    let claim_id = client.add_claim(&did, &claim_type, &claim_value, &proof);
+    let claim_id = client.add_claim(&user, &did, &claim_type, &claim_value, &proof);
     
     // Admin verifies the claim
     let oracle_signature = Bytes::from_slice(&env, &[5, 6, 7, 8]);
@@ -92,7 +100,8 @@ fn test_verify_claim() {
 #[test]
 fn test_revoke_claim() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, IdentityRegistryContract);
+    env.mock_all_auths();
+    let contract_id = env.register(IdentityRegistryContract, ());
     let client = IdentityRegistryContractClient::new(&env, &contract_id);
     
     let admin = Address::generate(&env);
@@ -106,11 +115,11 @@ fn test_revoke_claim() {
     let claim_value = String::from_str(&env, "user@example.com");
     let proof = Bytes::from_slice(&env, &[1, 2, 3, 4]);
     
-    let claim_id = client.add_claim(&did, &claim_type, &claim_value, &proof);
+    let claim_id = client.add_claim(&user, &did, &claim_type, &claim_value, &proof);
     
     // User revokes their own claim
     let reason = String::from_str(&env, "no longer valid");
-    client.revoke_claim(&did, &claim_id, &reason);
+    client.revoke_claim(&user, &did, &claim_id, &reason);
     
     // Check that claim is revoked
     let is_verified = client.is_claim_verified(&did, &claim_id);
@@ -120,7 +129,8 @@ fn test_revoke_claim() {
 #[test]
 fn test_delegation() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, IdentityRegistryContract);
+    env.mock_all_auths();
+    let contract_id = env.register(IdentityRegistryContract, ());
     let client = IdentityRegistryContractClient::new(&env, &contract_id);
     
     let admin = Address::generate(&env);
@@ -138,36 +148,30 @@ fn test_delegation() {
     client.add_delegation(&did, &delegate, &permissions, &expiry);
     
     // Delegate can now add claims
-    env.as_contract(&contract_id, || {
-        env.invoker().set(delegate.clone());
-        
-        let claim_type = String::from_str(&env, "discord");
-        let claim_value = String::from_str(&env, "user123#4567");
-        let proof = Bytes::from_slice(&env, &[1, 2, 3, 4]);
-        
-        let claim_id = client.add_claim(&did, &claim_type, &claim_value, &proof);
-        assert_eq!(claim_id, 1);
-    });
+    let claim_type = String::from_str(&env, "discord");
+    let claim_value = String::from_str(&env, "user123#4567");
+    let proof = Bytes::from_slice(&env, &[1, 2, 3, 4]);
+    
+    let claim_id = client.add_claim(&delegate, &did, &claim_type, &claim_value, &proof);
+    assert_eq!(claim_id, 1);
     
     // Revoke delegation
-    client.revoke_delegation(&did, &delegate);
+    client.revoke_delegation(&user, &did, &delegate);
     
     // Should fail now
-    env.as_contract(&contract_id, || {
-        env.invoker().set(delegate.clone());
-        let claim_type = String::from_str(&env, "telegram");
-        let claim_value = String::from_str(&env, "@user123");
-        let proof = Bytes::from_slice(&env, &[1, 2, 3, 4]);
-        
-        let result = client.try_add_claim(&did, &claim_type, &claim_value, &proof);
-        assert!(result.is_err());
-    });
+    let claim_type = String::from_str(&env, "telegram");
+    let claim_value = String::from_str(&env, "@user123");
+    let proof = Bytes::from_slice(&env, &[1, 2, 3, 4]);
+    
+    let result = client.try_add_claim(&delegate, &did, &claim_type, &claim_value, &proof);
+    assert_eq!(result, Err(Ok(Error::DelegationRevoked)));
 }
 
 #[test]
 fn test_reputation_scoring() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, IdentityRegistryContract);
+    env.mock_all_auths();
+    let contract_id = env.register(IdentityRegistryContract, ());
     let client = IdentityRegistryContractClient::new(&env, &contract_id);
     
     let admin = Address::generate(&env);
@@ -185,7 +189,7 @@ fn test_reputation_scoring() {
     let claim_type = String::from_str(&env, "github");
     let claim_value = String::from_str(&env, "verified_user");
     let proof = Bytes::from_slice(&env, &[1, 2, 3, 4]);
-    let claim_id = client.add_claim(&did, &claim_type, &claim_value, &proof);
+    let claim_id = client.add_claim(&user, &did, &claim_type, &claim_value, &proof);
     
     let oracle_signature = Bytes::from_slice(&env, &[5, 6, 7, 8]);
     client.verify_claim(&did, &claim_id, &oracle_signature);
@@ -204,7 +208,8 @@ fn test_reputation_scoring() {
 #[test]
 fn test_did_resolution() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, IdentityRegistryContract);
+    env.mock_all_auths();
+    let contract_id = env.register(IdentityRegistryContract, ());
     let client = IdentityRegistryContractClient::new(&env, &contract_id);
     
     let admin = Address::generate(&env);
@@ -232,7 +237,8 @@ fn test_did_resolution() {
 #[test]
 fn test_pause_unpause() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, IdentityRegistryContract);
+    env.mock_all_auths();
+    let contract_id = env.register(IdentityRegistryContract, ());
     let client = IdentityRegistryContractClient::new(&env, &contract_id);
     
     let admin = Address::generate(&env);
@@ -246,7 +252,7 @@ fn test_pause_unpause() {
     
     // Should fail to create DID when paused
     let result = client.try_create_did(&user, &public_key);
-    assert!(result.is_err());
+    assert_eq!(result, Err(Ok(Error::ContractPaused)));
     
     // Unpause contract
     client.unpause();
@@ -259,7 +265,8 @@ fn test_pause_unpause() {
 #[test]
 fn test_deactivate_did() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, IdentityRegistryContract);
+    env.mock_all_auths();
+    let contract_id = env.register(IdentityRegistryContract, ());
     let client = IdentityRegistryContractClient::new(&env, &contract_id);
     
     let admin = Address::generate(&env);
@@ -280,7 +287,8 @@ fn test_deactivate_did() {
 #[test]
 fn test_get_verified_claims_by_type() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, IdentityRegistryContract);
+    env.mock_all_auths();
+    let contract_id = env.register(IdentityRegistryContract, ());
     let client = IdentityRegistryContractClient::new(&env, &contract_id);
     
     let admin = Address::generate(&env);
@@ -292,13 +300,15 @@ fn test_get_verified_claims_by_type() {
     
     // Add multiple claims
     let github_claim = client.add_claim(
+        &user,
         &did,
         &String::from_str(&env, "github"),
         &String::from_str(&env, "user1"),
         &Bytes::from_slice(&env, &[1]),
     );
     
-    let twitter_claim = client.add_claim(
+    let _twitter_claim = client.add_claim(
+        &user,
         &did,
         &String::from_str(&env, "twitter"),
         &String::from_str(&env, "@user1"),
@@ -306,6 +316,7 @@ fn test_get_verified_claims_by_type() {
     );
     
     let email_claim = client.add_claim(
+        &user,
         &did,
         &String::from_str(&env, "github"), // Another github claim
         &String::from_str(&env, "user2"),
@@ -328,6 +339,65 @@ fn test_get_verified_claims_by_type() {
         found_claims.push_back(claim.claim_value);
     }
     
-    assert!(found_claims.contains(&String::from_str(&env, "user1")));
-    assert!(found_claims.contains(&String::from_str(&env, "user2")));
-}
\ No newline at end of file
+    assert!(found_claims.contains(String::from_str(&env, "user1")));
+    assert!(found_claims.contains(String::from_str(&env, "user2")));
+}
+#[test]
+fn test_error_codes() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(IdentityRegistryContract, ());
+    let client = IdentityRegistryContractClient::new(&env, &contract_id);
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let public_key = BytesN::from_array(&env, &[0; 32]);
+    
+    // Nothing works before initialize
+    assert_eq!(client.try_pause(), Err(Ok(Error::NotInitialized)));
+    
+    client.initialize(&admin);
+    let did = client.create_did(&user, &public_key);
+    
+    let unknown = String::from_str(&env, "did:stellar:unknown");
+    assert!(matches!(client.try_resolve_did(&unknown), Err(Ok(Error::DidNotFound))));
+    assert_eq!(client.try_verify_claim(&did, &42, &Bytes::new(&env)), Err(Ok(Error::ClaimNotFound)));
+    
+    let stranger = Address::generate(&env);
+    assert_eq!(client.try_revoke_delegation(&stranger, &did, &stranger), Err(Ok(Error::DelegationNotFound)));
+}
+
+#[test]
+fn test_badge_attendance_counts_once_per_event() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(IdentityRegistryContract, ());
+    let client = IdentityRegistryContractClient::new(&env, &contract_id);
+    
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let event = Address::generate(&env);
+    let public_key = BytesN::from_array(&env, &[0; 32]);
+    
+    client.initialize(&admin);
+    let did = client.create_did(&user, &public_key);
+    
+    // Only approved event contracts may record attendance
+    let result = client.try_record_attendance(&event, &did);
+    assert_eq!(result, Err(Ok(Error::IssuerNotApproved)));
+    
+    client.set_attendance_issuer(&event, &true);
+    client.record_attendance(&event, &did);
+    assert_eq!(client.get_reputation_score(&did), 150); // 100 + 50 (EVENT_ATTENDANCE_SCORE)
+    
+    // A second badge from the same event does not count again
+    let result = client.try_record_attendance(&event, &did);
+    assert_eq!(result, Err(Ok(Error::AttendanceAlreadyRecorded)));
+    assert_eq!(client.get_reputation_score(&did), 150);
+    
+    client.set_attendance_issuer(&event, &false);
+    let other_user = Address::generate(&env);
+    let other_did = client.create_did(&other_user, &public_key);
+    let result = client.try_record_attendance(&event, &other_did);
+    assert_eq!(result, Err(Ok(Error::IssuerNotApproved)));
+}
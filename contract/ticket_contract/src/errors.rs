@@ -0,0 +1,193 @@
+//! errors.rs — Error codes returned by the ticket contract
+//!
+//! A contract error enum holds at most 50 cases, so the ticket contract
+//! splits its codes across several enums, each owning its own range.
+//! Every condition has exactly one code.  Those several features hit —
+//! unknown tier or ticket, a refunded ticket, a missing DID, an unsettled
+//! auction — live in `Error`; a ticket not yet checked in lives in
+//! `TicketError` with the other check-in codes.  Entry points declared with
+//! a different enum raise those with `panic_with_error!`, so clients see the
+//! same code whichever function they called; the generated clients report
+//! it as `InvokeError::Contract(code)`.  Every other code an entry point can
+//! fail with belongs to the enum it returns.
+use soroban_sdk::contracterror;
+
+/// Tiers, pricing, sales and tickets (codes 1-99).
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    // Tier inputs
+    TierAlreadyExists = 1,
+    NegativeBasePrice = 2,
+    ZeroMaxSupply = 3,
+    CurveTooSteep = 4,
+    UnorderedDiscountPhases = 5,
+    DiscountTooLarge = 6,
+    InvalidAuctionPriceRange = 7,
+    InvalidAuctionSchedule = 8,
+    InvalidSaleWindow = 9,
+    SupplyBelowMinted = 10,
+    AuctionEndsAfterRefundCutoff = 11,
+    AuctionTierSold = 12,
+
+    // Lifecycle
+    AlreadyInitialized = 30,
+
+    // Sales
+    TierNotFound = 40,
+    TierInactive = 41,
+    TierSoldOut = 42,
+    ExceedsMaxSupply = 43,
+    TierAllocatedByLottery = 44,
+    AuctionNotStarted = 45,
+    AuctionEnded = 46,
+    FiatAuctionUnsupported = 47,
+    SaleNotStarted = 48,
+    SaleEnded = 49,
+
+    // Oracle and payment
+    OraclePriceUnavailable = 50,
+    InvalidSlippage = 51,
+    PaymentAssetNotAccepted = 52,
+    PaymentRateUnavailable = 53,
+    MaxAmountExceeded = 54,
+    PriceOverflow = 55,
+
+    // Experiments
+    ExperimentRunning = 60,
+    InvalidVariants = 61,
+    ExperimentNotFound = 62,
+    ExperimentOnAuction = 63,
+
+    // Promo codes
+    InvalidPromoCode = 70,
+    PromoCodeExists = 71,
+    PromoCodeNotFound = 72,
+    PromoCodeExpired = 73,
+    PromoCodeExhausted = 74,
+    PromoCodeNotValidForTier = 75,
+
+    // Identity
+    DidRequired = 80,
+
+    // Sessions
+    SessionFull = 85,
+
+    // Tickets
+    TicketNotFound = 90,
+    NotTicketOwner = 91,
+    RefundWindowClosed = 92,
+    TicketInvalidated = 93,
+    AuctionNotSettled = 94,
+    NoRebateDue = 95,
+    Soulbound = 96,
+    TicketIdInUse = 97,
+    WrongPaymentToken = 98,
+}
+
+/// Lottery allocation (codes 100-199).
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum LotteryError {
+    LotteryExists = 101,
+    InvalidLottery = 102,
+    LotteryNotFound = 103,
+    RegistrationClosed = 104,
+    RegistrationOpen = 105,
+    AlreadyDrawn = 106,
+    AlreadyEntered = 107,
+    LotteryFull = 108,
+    LotteryCancelled = 109,
+    InvalidDrawBatch = 110,
+    DrawDeadlinePassed = 111,
+    NotEntered = 112,
+    RefundUnavailable = 113,
+    AlreadyRefunded = 114,
+}
+
+/// Check-in, attendance and ticket metadata (codes 200-299).
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum TicketError {
+    CheckInClosed = 200,
+    AlreadyCheckedIn = 201,
+    AttendeeAlreadyAssigned = 202,
+    EventStarted = 203,
+    InvalidEventEndTime = 204,
+    UriTemplateTooLong = 205,
+    NotCheckedIn = 206,
+}
+
+/// Sessions and session passes (codes 300-399).
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum SessionError {
+    SessionExists = 300,
+    SessionNotFound = 301,
+    InvalidSessionWindow = 302,
+    CapacityBelowSold = 303,
+    TierHasTickets = 304,
+    SessionNotGranted = 305,
+    SessionEnded = 306,
+    AlreadyAdmitted = 307,
+}
+
+/// Add-on products (codes 400-499).
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum AddOnError {
+    AddOnExists = 400,
+    AddOnNotFound = 401,
+    NegativePrice = 402,
+    AddOnInactive = 403,
+    OutOfStock = 404,
+    StockBelowSold = 405,
+    TierNotEligible = 406,
+    InvalidQuantity = 407,
+    NotTicketHolder = 408,
+    NotAddOnBuyer = 409,
+    NothingToRedeem = 410,
+}
+
+/// Revenue split and withdrawals (codes 500-599).
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum PayoutError {
+    InvalidShares = 500,
+    DuplicatePayee = 501,
+    RevenueRecorded = 502,
+    InvalidFee = 503,
+    NotPlatform = 504,
+    WithdrawLocked = 505,
+    NothingToWithdraw = 506,
+    NotPayee = 507,
+}
+
+/// Attendance badges (codes 600-699).
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum BadgeError {
+    EventNotEnded = 600,
+    AlreadyClaimed = 601,
+}
+
+/// Pricing config validation (codes 700-799).
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum PricingError {
+    InvalidPriceBounds = 700,
+    InvalidReferencePrice = 701,
+    ZeroOracleAge = 702,
+    InvalidDeviationBand = 703,
+    InvalidMinSources = 704,
+    InvalidChangeClamp = 705,
+    LastUpdateTimeMismatch = 706,
+}
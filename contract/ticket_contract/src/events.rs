@@ -0,0 +1,361 @@
+//! events.rs — Events published by the ticket contract
+//!
+//! Schema version 1.  The topics of every event are its name, the schema
+//! version symbol `v1`, then the fields marked `#[topic]` in declaration
+//! order.  All other fields form the data map, keyed by field name.
+//!
+//! Indexers should subscribe to `(name, version)` pairs.  An event's shape is
+//! never changed in place: adding, removing or retyping a field ships the
+//! event under the next version symbol, and `v1` consumers keep working until
+//! the old version is retired.
+//!
+//! Amounts are in payment token units unless stated otherwise.
+use soroban_sdk::{contractevent, Address, BytesN, Symbol, Vec};
+
+use crate::storage_types::{AddOn, Payee, PlatformFee, PricingConfig, Session, Tier};
+
+// ---------------------------------------------------------------------------
+// Tickets
+// ---------------------------------------------------------------------------
+
+/// A ticket was sold through `purchase` or `purchase_for`.  `buyer` paid
+/// for it and `recipient` received it.  `price` is what the buyer paid,
+/// after promo codes and fiat conversion.
+#[contractevent(topics = ["ticket_purchased", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TicketPurchased {
+    #[topic]
+    pub buyer: Address,
+    #[topic]
+    pub tier: Symbol,
+    pub recipient: Address,
+    pub token_id: u32,
+    pub price: i128,
+    pub payment_token: Address,
+}
+
+/// The organizer minted free tickets with `batch_mint`.
+#[contractevent(topics = ["tickets_minted", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TicketsMinted {
+    #[topic]
+    pub to: Address,
+    #[topic]
+    pub tier: Symbol,
+    pub token_ids: Vec<u32>,
+}
+
+/// A ticket was refunded and burned.  `owner` held the ticket; `amount`
+/// went to whoever paid for it.
+#[contractevent(topics = ["ticket_refunded", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TicketRefunded {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub tier: Symbol,
+    pub token_id: u32,
+    pub amount: i128,
+    pub payment_token: Address,
+}
+
+/// The difference between the price paid and the auction clearing price
+/// was paid back to whoever paid for `owner`'s ticket.
+#[contractevent(topics = ["rebate_claimed", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RebateClaimed {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub tier: Symbol,
+    pub token_id: u32,
+    pub amount: i128,
+    pub payment_token: Address,
+}
+
+/// A lottery draw batch picked winners; `token_ids` were minted to that
+/// batch's winners at `price`.  Batches that only refund losers publish
+/// `lottery_refunded` events instead.
+#[contractevent(topics = ["lottery_drawn", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LotteryDrawn {
+    #[topic]
+    pub tier: Symbol,
+    pub token_ids: Vec<u32>,
+    pub price: i128,
+    pub entrants: u32,
+}
+
+/// The organizer cancelled a lottery; entrants not yet picked can reclaim
+/// their deposits.
+#[contractevent(topics = ["lottery_cancelled", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LotteryCancelled {
+    #[topic]
+    pub tier: Symbol,
+}
+
+/// A losing (or stranded) lottery deposit was paid back to `entrant`.
+#[contractevent(topics = ["lottery_refunded", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LotteryRefunded {
+    #[topic]
+    pub entrant: Address,
+    #[topic]
+    pub tier: Symbol,
+    pub amount: i128,
+}
+
+/// The payer moved `token_id` from `previous` to the named attendee.
+#[contractevent(topics = ["attendee_assigned", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttendeeAssigned {
+    #[topic]
+    pub attendee: Address,
+    #[topic]
+    pub tier: Symbol,
+    pub token_id: u32,
+    pub previous: Address,
+}
+
+/// The holder of `token_id` was admitted at the door.
+#[contractevent(topics = ["ticket_checked_in", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TicketCheckedIn {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub tier: Symbol,
+    pub token_id: u32,
+    pub timestamp: u64,
+}
+
+/// The holder of `token_id` was admitted to `session`.
+#[contractevent(topics = ["session_checked_in", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionCheckedIn {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub session: Symbol,
+    pub token_id: u32,
+    pub timestamp: u64,
+}
+
+/// The holder of `token_id` claimed its attendance badge.
+/// `reputation_recorded` is whether the identity registry counted it.
+#[contractevent(topics = ["badge_claimed", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BadgeClaimed {
+    #[topic]
+    pub holder: Address,
+    #[topic]
+    pub tier: Symbol,
+    pub token_id: u32,
+    pub reputation_recorded: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Tiers
+// ---------------------------------------------------------------------------
+
+#[contractevent(topics = ["tier_added", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TierAdded {
+    #[topic]
+    pub tier_symbol: Symbol,
+    pub tier: Tier,
+}
+
+/// Any admin change to an existing tier; `tier` is the new state.
+#[contractevent(topics = ["tier_updated", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TierUpdated {
+    #[topic]
+    pub tier_symbol: Symbol,
+    pub tier: Tier,
+}
+
+/// `sessions` is the full set the tier grants from now on.
+#[contractevent(topics = ["tier_sessions_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TierSessionsSet {
+    #[topic]
+    pub tier_symbol: Symbol,
+    pub sessions: Vec<Symbol>,
+}
+
+// ---------------------------------------------------------------------------
+// Sessions
+// ---------------------------------------------------------------------------
+
+#[contractevent(topics = ["session_added", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionAdded {
+    #[topic]
+    pub session_id: Symbol,
+    pub session: Session,
+}
+
+/// Any admin change to an existing session; `session` is the new state.
+#[contractevent(topics = ["session_updated", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionUpdated {
+    #[topic]
+    pub session_id: Symbol,
+    pub session: Session,
+}
+
+// ---------------------------------------------------------------------------
+// Add-ons
+// ---------------------------------------------------------------------------
+
+#[contractevent(topics = ["addon_added", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AddOnAdded {
+    #[topic]
+    pub addon_id: Symbol,
+    pub addon: AddOn,
+}
+
+/// Any admin change to an existing add-on; `addon` is the new state.
+#[contractevent(topics = ["addon_updated", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AddOnUpdated {
+    #[topic]
+    pub addon_id: Symbol,
+    pub addon: AddOn,
+}
+
+/// `amount` is the total paid, in the add-on's payment token.
+#[contractevent(topics = ["addon_purchased", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AddOnPurchased {
+    #[topic]
+    pub buyer: Address,
+    #[topic]
+    pub addon_id: Symbol,
+    pub token_id: u32,
+    pub quantity: u32,
+    pub amount: i128,
+}
+
+/// Unredeemed units were refunded along with their ticket.
+#[contractevent(topics = ["addon_refunded", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AddOnRefunded {
+    #[topic]
+    pub buyer: Address,
+    #[topic]
+    pub addon_id: Symbol,
+    pub token_id: u32,
+    pub quantity: u32,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["addon_redeemed", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AddOnRedeemed {
+    #[topic]
+    pub addon_id: Symbol,
+    pub token_id: u32,
+    pub quantity: u32,
+}
+
+// ---------------------------------------------------------------------------
+// Payouts
+// ---------------------------------------------------------------------------
+
+#[contractevent(topics = ["payees_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayeesSet {
+    pub payees: Vec<Payee>,
+}
+
+#[contractevent(topics = ["platform_fee_set", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlatformFeeSet {
+    pub fee: PlatformFee,
+}
+
+/// `recipient` withdrew `amount` of `payment_token`; `platform` tells a
+/// platform fee withdrawal from a payee share.
+#[contractevent(topics = ["withdrawn", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Withdrawn {
+    #[topic]
+    pub recipient: Address,
+    #[topic]
+    pub payment_token: Address,
+    pub amount: i128,
+    pub platform: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Pricing
+// ---------------------------------------------------------------------------
+
+/// The pricing config was replaced or partially updated.
+#[contractevent(topics = ["pricing_changed", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PricingChanged {
+    pub config: PricingConfig,
+}
+
+#[contractevent(topics = ["frozen", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Frozen {
+    pub frozen: bool,
+}
+
+/// No usable oracle price; `path` is `dex`, `last_good` or `neutral` and
+/// `value` the price or multiplier that was used instead.
+#[contractevent(topics = ["oracle_fallback", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleFallback {
+    #[topic]
+    pub path: Symbol,
+    pub value: i128,
+}
+
+/// A TWAP observation was recorded.
+#[contractevent(topics = ["price_observed", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceObserved {
+    pub price: i128,
+    pub multiplier: i128,
+}
+
+/// The cached multiplier was refreshed at `timestamp`.
+#[contractevent(topics = ["repriced", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Repriced {
+    pub previous: i128,
+    pub multiplier: i128,
+    pub timestamp: u64,
+}
+
+// ---------------------------------------------------------------------------
+// Promo codes
+// ---------------------------------------------------------------------------
+
+#[contractevent(topics = ["promo_registered", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PromoRegistered {
+    #[topic]
+    pub code_hash: BytesN<32>,
+    pub max_redemptions: u32,
+    pub expires_at: u64,
+}
+
+/// `discount` is in the tier's price unit.
+#[contractevent(topics = ["promo_redeemed", "v1"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PromoRedeemed {
+    #[topic]
+    pub code_hash: BytesN<32>,
+    #[topic]
+    pub buyer: Address,
+    pub tier: Symbol,
+    pub discount: i128,
+}
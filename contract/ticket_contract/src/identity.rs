@@ -0,0 +1,18 @@
+//! identity.rs — Client for the Gatherraa identity registry
+//!
+//! Only the DID lookup used for per-DID limits and the attendance hook used
+//! by badges are mirrored here; the registry itself lives in
+//! `identity_contract`.
+use soroban_sdk::{contractclient, Address, Env, String};
+
+/// Trait mirroring the identity registry's public lookup.
+/// `contractclient` generates `IdentityRegistryClient`.
+#[contractclient(name = "IdentityRegistryClient")]
+pub trait IdentityRegistryInterface {
+    /// Returns the DID controlled by `address`, if it has registered one.
+    fn get_did_by_address(env: Env, address: Address) -> Option<String>;
+
+    /// Counts attendance at `event` toward the reputation of `did`.  Needs
+    /// `event`'s auth and fails unless the registry approved it.
+    fn record_attendance(env: Env, event: Address, did: String);
+}
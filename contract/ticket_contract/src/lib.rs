@@ -1,23 +1,86 @@
+//! Soulbound event tickets with dynamic pricing.
+//!
+//! # Upgrades
+//!
+//! Redeploying is the only upgrade path.  The contract has no upgrade entry
+//! point and no storage migration, and the stored `Tier`, `Ticket`,
+//! `PricingConfig` and `EventInfo` records of earlier releases do not decode
+//! with the current definitions (see `storage_types`).  Deploy a new event
+//! through the factory; tickets sold by an old contract stay there.
 #![no_std]
 
 #[cfg(test)]
 mod test;
 
-use soroban_sdk::{contract, contractimpl, token, Address, Env, String, Symbol};
+use soroban_sdk::{
+    contract, contractimpl, panic_with_error, symbol_short, token, xdr::ToXdr, Address, Bytes,
+    BytesN, Env, Map, String, Symbol, Vec,
+};
 use stellar_access::ownable::{self as ownable, Ownable};
-use stellar_tokens::non_fungible::{Base, NonFungibleToken};
+use stellar_tokens::non_fungible::{
+    burnable, emit_transfer,
+    enumerable::{Enumerable, NonFungibleEnumerable},
+    sequential, Base, ContractOverrides, NonFungibleToken,
+};
+
+pub mod errors;
+use errors::{
+    AddOnError, BadgeError, Error, LotteryError, PayoutError, PricingError, SessionError,
+    TicketError,
+};
+
+pub mod events;
+use events::{
+    AddOnAdded, AddOnPurchased, AddOnRedeemed, AddOnRefunded, AddOnUpdated, AttendeeAssigned,
+    BadgeClaimed, Frozen, LotteryCancelled, LotteryDrawn, LotteryRefunded, OracleFallback,
+    PayeesSet, PlatformFeeSet, PriceObserved, PricingChanged, PromoRedeemed, PromoRegistered,
+    RebateClaimed, Repriced, SessionAdded, SessionCheckedIn, SessionUpdated, TicketCheckedIn,
+    TicketPurchased, TicketRefunded, TicketsMinted, TierAdded, TierSessionsSet, TierUpdated,
+    Withdrawn,
+};
+
+pub mod payout;
+
+pub mod metadata;
+use metadata::{push_i128, push_json_string, push_u128, MAX_URI_TEMPLATE_LEN};
+
+mod storage;
 
 mod storage_types;
-use storage_types::{DataKey, EventInfo, PricingConfig, PricingStrategy, Ticket, Tier};
-
-mod oracle;
-use oracle::{fetch_price_with_fallback, oracle_price_to_multiplier, DEFAULT_STALENESS_SECONDS};
+use storage_types::{
+    AddOn, AttendanceBadge, DataKey, EventInfo, Experiment, ExperimentVariant, LastGoodMultiplier,
+    Lottery, LotteryEntry, OracleKind, OracleSource, Payee, PaymentAsset, PlatformFee,
+    PriceObservation, PricePoint, PricingConfig, PricingStrategy, PromoCode, PromoDiscount,
+    Revenue, Session, StalePricePolicy, Ticket, TicketAddOn, TicketStatus, Tier, VariantStats,
+};
+
+pub mod identity;
+use identity::IdentityRegistryClient;
+
+pub mod oracle;
+use oracle::{
+    clamp_change, fetch_aggregated_price, fetch_oracle_result, fetch_price_with_fallback,
+    oracle_price_to_multiplier, time_weighted_average, MultiplierReading, OracleResult,
+    DEFAULT_STALENESS_SECONDS,
+};
+
+pub mod pricing;
+use pricing::{
+    bonding_curve_price, dutch_auction_price, fiat_to_token_amount, mul_div, surge_start,
+    time_decay_price, MAX_CURVE_STEEPNESS,
+};
 
 // Dynamic pricing constants
 const PRICE_INCREASE_BPS: i128 = 500; // 5% increase per tier threshold
-const EARLY_BIRD_DISCOUNT_BPS: i128 = 1000; // 10% discount max
 const ORACLE_PRECISION: i128 = 10000; // Assuming oracle returns multiplier in bps (e.g. 10000 = 1x)
 
+// Largest page returned by the ticket listing views
+const MAX_PAGE_SIZE: u32 = 100;
+
+// Lottery bounds: entries per lottery and winners minted per draw call
+const MAX_LOTTERY_ENTRANTS: u32 = 10_000;
+const MAX_DRAW_BATCH: u32 = 50;
+
 #[contract]
 pub struct SoulboundTicketContract;
 
@@ -31,24 +94,28 @@ impl SoulboundTicketContract {
         uri: String,
         start_time: u64,
         refund_cutoff_time: u64,
-    ) {
+    ) -> Result<(), Error> {
         if e.storage().instance().has(&DataKey::Admin) {
-            panic!("already initialized");
+            return Err(Error::AlreadyInitialized);
         }
 
         // Init Event Info
         let event_info = EventInfo {
             start_time,
             refund_cutoff_time,
+            end_time: 0,
         };
         e.storage().instance().set(&DataKey::EventInfo, &event_info);
         e.storage().instance().set(&DataKey::Admin, &admin);
+        storage::extend_instance(e);
 
-        // Init Token Counter
-        e.storage().instance().set(&DataKey::TokenIdCounter, &0u32);
+        // Token ids come from `Base::sequential_mint`; reserve 0 so the
+        // first ticket is #1.
+        sequential::increment_token_id(e, 1);
 
         // Init default PricingConfig (placeholder addresses, standard bounds)
         let default_config = PricingConfig {
+            oracle_kind: OracleKind::Dia,
             oracle_address: admin.clone(), // Update via set_pricing_config after deployment
             dex_pool_address: admin.clone(), // Update via set_pricing_config after deployment
             price_floor: 0,
@@ -59,6 +126,12 @@ impl SoulboundTicketContract {
             oracle_pair: String::from_str(e, "XLM/USD"),
             oracle_reference_price: oracle::DIA_ORACLE_DECIMALS, // $1.00 baseline (1.0 * 10^8)
             max_oracle_age_seconds: DEFAULT_STALENESS_SECONDS,
+            oracle_sources: Vec::new(e),
+            max_deviation_bps: 500,
+            min_sources: 1,
+            stale_policy: StalePricePolicy::Neutral,
+            twap_window: 0,
+            max_change_bps: 0,
         };
         e.storage()
             .instance()
@@ -67,35 +140,163 @@ impl SoulboundTicketContract {
         // Init Token Metadata via OpenZeppelin Base
         Base::set_metadata(e, uri, name, symbol);
         ownable::set_owner(e, &admin);
+        Ok(())
     }
 
-    // Set Pricing Config
-    pub fn set_pricing_config(e: &Env, config: PricingConfig) {
-        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
-        e.storage().instance().set(&DataKey::PricingConfig, &config);
+    /// Admin-only: replace the pricing config.  `last_update_time` is
+    /// managed by the contract and must match the stored value, so start
+    /// from `get_pricing_config`.
+    pub fn set_pricing_config(e: &Env, config: PricingConfig) -> Result<(), PricingError> {
+        Self::require_admin(e);
+        let current: PricingConfig = e.storage().instance().get(&DataKey::PricingConfig).unwrap();
+        if config.last_update_time != current.last_update_time {
+            return Err(PricingError::LastUpdateTimeMismatch);
+        }
+        Self::write_pricing_config(e, &config)
+    }
+
+    pub fn get_pricing_config(e: &Env) -> PricingConfig {
+        e.storage().instance().get(&DataKey::PricingConfig).unwrap()
     }
 
     /// Admin-only: manually update the oracle reference price used to compute
     /// multipliers.  Call this once after deployment pointing at a real oracle,
     /// or whenever you want to re-baseline the reference price.
-    pub fn update_oracle_reference(e: &Env, new_reference_price: i128) {
+    pub fn update_oracle_reference(e: &Env, new_reference_price: i128) -> Result<(), PricingError> {
+        let mut config = Self::read_pricing_config_as_admin(e);
+        config.oracle_reference_price = new_reference_price;
+        Self::write_pricing_config(e, &config)
+    }
+
+    /// Admin-only: set the floor and ceiling applied to every tier price.
+    pub fn set_price_bounds(
+        e: &Env,
+        price_floor: i128,
+        price_ceiling: i128,
+    ) -> Result<(), PricingError> {
+        let mut config = Self::read_pricing_config_as_admin(e);
+        config.price_floor = price_floor;
+        config.price_ceiling = price_ceiling;
+        Self::write_pricing_config(e, &config)
+    }
+
+    /// Admin-only: set how long cached multipliers and quotes stay valid.
+    pub fn set_update_frequency(e: &Env, update_frequency: u64) -> Result<(), PricingError> {
+        let mut config = Self::read_pricing_config_as_admin(e);
+        config.update_frequency = update_frequency;
+        Self::write_pricing_config(e, &config)
+    }
+
+    /// Admin-only: point the primary oracle and the DEX fallback elsewhere.
+    pub fn set_oracle(
+        e: &Env,
+        oracle_kind: OracleKind,
+        oracle_address: Address,
+        dex_pool_address: Address,
+        oracle_pair: String,
+    ) -> Result<(), PricingError> {
+        let mut config = Self::read_pricing_config_as_admin(e);
+        config.oracle_kind = oracle_kind;
+        config.oracle_address = oracle_address;
+        config.dex_pool_address = dex_pool_address;
+        config.oracle_pair = oracle_pair;
+        Self::write_pricing_config(e, &config)
+    }
+
+    /// Admin-only: set how old an oracle price may be before it is ignored.
+    pub fn set_max_oracle_age(e: &Env, max_oracle_age_seconds: u64) -> Result<(), PricingError> {
+        let mut config = Self::read_pricing_config_as_admin(e);
+        config.max_oracle_age_seconds = max_oracle_age_seconds;
+        Self::write_pricing_config(e, &config)
+    }
+
+    /// Admin-only: replace the aggregated oracle sources and their quorum.
+    pub fn set_oracle_sources(
+        e: &Env,
+        oracle_sources: Vec<OracleSource>,
+        max_deviation_bps: u32,
+        min_sources: u32,
+    ) -> Result<(), PricingError> {
+        let mut config = Self::read_pricing_config_as_admin(e);
+        config.oracle_sources = oracle_sources;
+        config.max_deviation_bps = max_deviation_bps;
+        config.min_sources = min_sources;
+        Self::write_pricing_config(e, &config)
+    }
+
+    /// Admin-only: choose what happens when no oracle price is usable.
+    pub fn set_stale_policy(e: &Env, stale_policy: StalePricePolicy) -> Result<(), PricingError> {
+        let mut config = Self::read_pricing_config_as_admin(e);
+        config.stale_policy = stale_policy;
+        Self::write_pricing_config(e, &config)
+    }
+
+    /// Admin-only: configure TWAP smoothing (`twap_window` 0 disables it).
+    pub fn set_twap(e: &Env, twap_window: u32, max_change_bps: u32) -> Result<(), PricingError> {
+        let mut config = Self::read_pricing_config_as_admin(e);
+        config.twap_window = twap_window;
+        config.max_change_bps = max_change_bps;
+        Self::write_pricing_config(e, &config)
+    }
+
+    /// Require the admin's authorization.  Every admin entry point goes
+    /// through here, which also extends the instance TTL for it.
+    fn require_admin(e: &Env) {
+        storage::extend_instance(e);
         let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
         admin.require_auth();
-        let mut config: PricingConfig =
-            e.storage().instance().get(&DataKey::PricingConfig).unwrap();
-        config.oracle_reference_price = new_reference_price;
-        e.storage().instance().set(&DataKey::PricingConfig, &config);
+    }
+
+    fn read_pricing_config_as_admin(e: &Env) -> PricingConfig {
+        Self::require_admin(e);
+        e.storage().instance().get(&DataKey::PricingConfig).unwrap()
+    }
+
+    fn write_pricing_config(e: &Env, config: &PricingConfig) -> Result<(), PricingError> {
+        Self::validate_pricing_config(config)?;
+        e.storage().instance().set(&DataKey::PricingConfig, config);
+        // Multipliers computed under the old config no longer apply
+        e.storage().instance().remove(&DataKey::CachedMultiplier);
+        PricingChanged {
+            config: config.clone(),
+        }
+        .publish(e);
+        Ok(())
+    }
+
+    fn validate_pricing_config(config: &PricingConfig) -> Result<(), PricingError> {
+        if config.price_floor < 0 || config.price_floor > config.price_ceiling {
+            return Err(PricingError::InvalidPriceBounds);
+        }
+        if config.oracle_reference_price <= 0 {
+            return Err(PricingError::InvalidReferencePrice);
+        }
+        if config.max_oracle_age_seconds == 0 {
+            return Err(PricingError::ZeroOracleAge);
+        }
+        if config.max_deviation_bps > 10000 {
+            return Err(PricingError::InvalidDeviationBand);
+        }
+        if config.min_sources == 0
+            || (!config.oracle_sources.is_empty()
+                && config.min_sources > config.oracle_sources.len())
+        {
+            return Err(PricingError::InvalidMinSources);
+        }
+        if config.max_change_bps > 10000 {
+            return Err(PricingError::InvalidChangeClamp);
+        }
+        Ok(())
     }
 
     // Emergency freeze toggle
     pub fn emergency_freeze(e: &Env, freeze: bool) {
-        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
+        Self::require_admin(e);
         let mut config: PricingConfig =
             e.storage().instance().get(&DataKey::PricingConfig).unwrap();
         config.is_frozen = freeze;
         e.storage().instance().set(&DataKey::PricingConfig, &config);
+        Frozen { frozen: freeze }.publish(e);
     }
 
     // Add a new ticket tier
@@ -106,14 +307,20 @@ impl SoulboundTicketContract {
         base_price: i128,
         max_supply: u32,
         strategy: PricingStrategy,
-    ) {
-        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
+    ) -> Result<(), Error> {
+        Self::require_admin(e);
 
         let key = DataKey::Tier(tier_symbol.clone());
         if e.storage().persistent().has(&key) {
-            panic!("Tier already exists");
+            return Err(Error::TierAlreadyExists);
         }
+        if base_price < 0 {
+            return Err(Error::NegativeBasePrice);
+        }
+        if max_supply == 0 {
+            return Err(Error::ZeroMaxSupply);
+        }
+        Self::validate_strategy(e, &strategy)?;
 
         let tier = Tier {
             name,
@@ -123,312 +330,2830 @@ impl SoulboundTicketContract {
             minted: 0,
             active: true,
             strategy,
+            currency: None,
+            sale_start: 0,
+            sale_end: 0,
         };
 
-        e.storage().persistent().set(&key, &tier);
-    }
+        storage::write(e, &key, &tier);
 
-    /// Fetch the current external price multiplier using the real DIA oracle.
-    ///
-    /// Strategy:
-    ///  1. Call `DiaOraclePriceClient::try_get_value(pair)` on the configured oracle.
-    ///  2. Verify that the returned timestamp is within `max_oracle_age_seconds`.
-    ///  3. If the oracle is stale or the cross-contract call fails, fall back to
-    ///     `DexPriceRouterClient::try_get_spot_price(pair)` on the DEX address.
-    ///  4. If both fail, return `ORACLE_PRECISION` (neutral — no adjustment).
-    ///
-    /// The raw price (8 decimals, $1.00 == 100_000_000) is converted into a
-    /// `ORACLE_PRECISION`-scaled multiplier using the stored `oracle_reference_price`.
-    fn fetch_oracle_multiplier(e: &Env, config: &PricingConfig) -> i128 {
-        match fetch_price_with_fallback(
-            e,
-            &config.oracle_address,
-            &config.dex_pool_address,
-            config.oracle_pair.clone(),
-            config.max_oracle_age_seconds,
-        ) {
-            Some(result) => oracle_price_to_multiplier(
-                result.price,
-                config.oracle_reference_price,
-                ORACLE_PRECISION,
-            ),
-            // Both oracle and DEX unavailable: apply neutral multiplier (no adjustment)
-            None => ORACLE_PRECISION,
-        }
+        let mut index = Self::list_tiers(e);
+        index.push_back(tier_symbol.clone());
+        storage::write(e, &DataKey::TierIndex, &index);
+
+        TierAdded { tier_symbol, tier }.publish(e);
+        Ok(())
     }
 
-    // Dynamic pricing query
-    pub fn get_ticket_price(e: &Env, tier_symbol: Symbol) -> i128 {
-        let config: PricingConfig = e.storage().instance().get(&DataKey::PricingConfig).unwrap();
-        let key = DataKey::Tier(tier_symbol);
-        let tier: Tier = e.storage().persistent().get(&key).unwrap();
+    /// Admin-only: replace a tier's name, base price and strategy.
+    ///
+    /// Supply, sales and the sale window are left untouched; tickets already
+    /// sold keep the price they were bought at.  Once a tier has sold, its
+    /// strategy can no longer change to, from or within `DutchAuction`:
+    /// clearing rebates are settled against the auction buyers paid in.
+    pub fn update_tier(
+        e: &Env,
+        tier_symbol: Symbol,
+        name: String,
+        base_price: i128,
+        strategy: PricingStrategy,
+    ) -> Result<(), Error> {
+        Self::require_admin(e);
 
-        if config.is_frozen {
-            return tier.current_price;
+        let mut tier = Self::read_tier(e, &tier_symbol)?;
+        if base_price < 0 {
+            return Err(Error::NegativeBasePrice);
         }
-
-        // Base price
-        let mut price = tier.base_price;
-
-        // Apply strategy variations
-        match tier.strategy {
-            PricingStrategy::Standard => {
-                // Demand based: base_price * (1 + (minted / (max_supply / 5)) * 5%)
-                let thresholds_passed = tier.minted / (tier.max_supply.max(1) / 5).max(1);
-                let increase = price * PRICE_INCREASE_BPS * (thresholds_passed as i128) / 10000;
-                price += increase;
-            }
-            PricingStrategy::TimeDecay => {
-                let event_info: EventInfo =
-                    e.storage().instance().get(&DataKey::EventInfo).unwrap();
-                let now = e.ledger().timestamp();
-                // If purchased way before event, apply 10% discount
-                // Assume linear scale from start to event_start_time
-                let start = event_info.start_time.saturating_sub(604800); // 1 week before
-                if now < start {
-                    price -= price * EARLY_BIRD_DISCOUNT_BPS / 10000;
-                }
-            }
-            PricingStrategy::AbTestA => {
-                // High demand sensitivity (10% increase per threshold)
-                let thresholds_passed = tier.minted / (tier.max_supply.max(1) / 5).max(1);
-                let increase =
-                    price * (PRICE_INCREASE_BPS * 2) * (thresholds_passed as i128) / 10000;
-                price += increase;
+        Self::validate_strategy(e, &strategy)?;
+        let auction_involved = matches!(tier.strategy, PricingStrategy::DutchAuction(_))
+            || matches!(strategy, PricingStrategy::DutchAuction(_));
+        if tier.minted > 0 && auction_involved && strategy != tier.strategy {
+            return Err(Error::AuctionTierSold);
+        }
+        if let PricingStrategy::DutchAuction(_) = strategy {
+            if tier.currency.is_some() {
+                return Err(Error::FiatAuctionUnsupported);
             }
-            PricingStrategy::AbTestB => {
-                // Floor starts higher (+20%)
-                price += price * 2000 / 10000;
+            if Self::active_experiment(e, &tier_symbol).is_some() {
+                return Err(Error::ExperimentOnAuction);
             }
         }
 
-        // Apply external Oracle factors using the real DIA oracle integration
-        let oracle_multiplier = Self::fetch_oracle_multiplier(e, &config);
-        price = price * oracle_multiplier / ORACLE_PRECISION;
+        tier.name = name;
+        tier.base_price = base_price;
+        tier.strategy = strategy;
+        if tier.minted == 0 {
+            tier.current_price = base_price;
+        }
+        Self::write_tier_update(e, tier_symbol, tier);
+        Ok(())
+    }
 
-        // Apply bounds
-        price = price.max(config.price_floor).min(config.price_ceiling);
+    /// Admin-only: open or close a tier for purchases.
+    pub fn set_tier_active(e: &Env, tier_symbol: Symbol, active: bool) -> Result<(), Error> {
+        Self::require_admin(e);
 
-        // We only return the price here. It is updated during `purchase`.
-        price
+        let mut tier = Self::read_tier(e, &tier_symbol)?;
+        tier.active = active;
+        Self::write_tier_update(e, tier_symbol, tier);
+        Ok(())
     }
 
-    // Batch Minting for Organizer
-    pub fn batch_mint(e: &Env, to: Address, tier_symbol: Symbol, amount: u32) {
-        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
-
-        let key = DataKey::Tier(tier_symbol.clone());
-        let mut tier: Tier = e
-            .storage()
-            .persistent()
-            .get(&key)
-            .unwrap_or_else(|| panic!("Tier not found"));
+    /// Admin-only: raise or lower a tier's supply, never below what has
+    /// already been minted.  An auction tier's supply is fixed once it has
+    /// sold, since it decides when the auction settles.
+    pub fn set_max_supply(e: &Env, tier_symbol: Symbol, max_supply: u32) -> Result<(), Error> {
+        Self::require_admin(e);
 
-        if tier.minted + amount > tier.max_supply {
-            panic!("Exceeds tier max supply");
+        let mut tier = Self::read_tier(e, &tier_symbol)?;
+        if max_supply == 0 {
+            return Err(Error::ZeroMaxSupply);
+        }
+        if max_supply < tier.minted {
+            return Err(Error::SupplyBelowMinted);
         }
+        if tier.minted > 0 {
+            if let PricingStrategy::DutchAuction(_) = tier.strategy {
+                return Err(Error::AuctionTierSold);
+            }
+        }
+        tier.max_supply = max_supply;
+        Self::write_tier_update(e, tier_symbol, tier);
+        Ok(())
+    }
 
-        for _ in 0..amount {
-            // custom sequential increment
-            let mut counter: u32 = e
-                .storage()
-                .instance()
-                .get(&DataKey::TokenIdCounter)
-                .unwrap();
-            counter += 1;
-            let token_id = counter;
-            e.storage()
-                .instance()
-                .set(&DataKey::TokenIdCounter, &counter);
+    /// Admin-only: restrict purchases to `[sale_start, sale_end]`.  Either
+    /// bound may be 0 to leave that side open.
+    pub fn set_sale_window(
+        e: &Env,
+        tier_symbol: Symbol,
+        sale_start: u64,
+        sale_end: u64,
+    ) -> Result<(), Error> {
+        Self::require_admin(e);
+
+        let mut tier = Self::read_tier(e, &tier_symbol)?;
+        if sale_end != 0 && sale_start >= sale_end {
+            return Err(Error::InvalidSaleWindow);
+        }
+        tier.sale_start = sale_start;
+        tier.sale_end = sale_end;
+        Self::write_tier_update(e, tier_symbol, tier);
+        Ok(())
+    }
 
-            Base::sequential_mint(e, &to);
+    /// Store an admin change to an existing tier and announce it.
+    fn write_tier_update(e: &Env, tier_symbol: Symbol, tier: Tier) {
+        storage::write(e, &DataKey::Tier(tier_symbol.clone()), &tier);
+        TierUpdated { tier_symbol, tier }.publish(e);
+    }
 
-            let ticket = Ticket {
-                tier_symbol: tier_symbol.clone(),
-                purchase_time: e.ledger().timestamp(),
-                price_paid: 0, // Admin mints are free
-                is_valid: true,
-            };
-            e.storage()
-                .persistent()
-                .set(&DataKey::Ticket(token_id), &ticket);
-        }
+    pub fn get_tier(e: &Env, tier_symbol: Symbol) -> Result<Tier, Error> {
+        Self::read_tier(e, &tier_symbol)
+    }
 
-        tier.minted += amount;
-        e.storage().persistent().set(&key, &tier);
+    /// Symbols of every tier, in the order they were added.
+    pub fn list_tiers(e: &Env) -> Vec<Symbol> {
+        storage::read(e, &DataKey::TierIndex).unwrap_or(Vec::new(e))
     }
 
-    // Purchase a ticket
-    pub fn purchase(e: &Env, buyer: Address, payment_token: Address, tier_symbol: Symbol) {
-        buyer.require_auth();
+    fn validate_strategy(e: &Env, strategy: &PricingStrategy) -> Result<(), Error> {
+        match strategy {
+            PricingStrategy::BondingCurve(curve) if curve.steepness > MAX_CURVE_STEEPNESS => {
+                return Err(Error::CurveTooSteep);
+            }
+            PricingStrategy::TimeDecay(schedule) => {
+                let mut previous = 0u64;
+                for phase in schedule.phases.iter() {
+                    if phase.until <= previous {
+                        return Err(Error::UnorderedDiscountPhases);
+                    }
+                    if phase.discount_bps > 10000 {
+                        return Err(Error::DiscountTooLarge);
+                    }
+                    previous = phase.until;
+                }
+            }
+            PricingStrategy::DutchAuction(auction) => {
+                if auction.floor_price < 0 || auction.floor_price > auction.start_price {
+                    return Err(Error::InvalidAuctionPriceRange);
+                }
+                if auction.start_time >= auction.end_time {
+                    return Err(Error::InvalidAuctionSchedule);
+                }
+                // Rebates are partial refunds: the auction must settle while
+                // refunds are still open, before proceeds can be withdrawn
+                if auction.end_time > Self::get_event_info(e).refund_cutoff_time {
+                    return Err(Error::AuctionEndsAfterRefundCutoff);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
 
-        let key = DataKey::Tier(tier_symbol.clone());
-        let mut tier: Tier = e
-            .storage()
-            .persistent()
-            .get(&key)
-            .unwrap_or_else(|| panic!("Tier not found"));
+    /// Admin-only: run a pricing experiment on a tier.
+    ///
+    /// Buyers are split between `variants` by hashing their address, so the
+    /// same buyer always sees the same variant.  Weights are in bps and must
+    /// add up to 10000.  Only one experiment can run per tier at a time.
+    pub fn start_experiment(
+        e: &Env,
+        tier_symbol: Symbol,
+        variants: Vec<ExperimentVariant>,
+    ) -> Result<(), Error> {
+        Self::require_admin(e);
 
-        if !tier.active {
-            panic!("Tier is not active");
+        let tier = Self::read_tier(e, &tier_symbol)?;
+        if Self::active_experiment(e, &tier_symbol).is_some() {
+            return Err(Error::ExperimentRunning);
         }
-        if tier.minted >= tier.max_supply {
-            panic!("Tier sold out");
+        // An auction promises one clearing price to every buyer
+        if let PricingStrategy::DutchAuction(_) = tier.strategy {
+            return Err(Error::ExperimentOnAuction);
         }
 
-        let price = Self::get_ticket_price(e, tier_symbol.clone());
-
-        // Process payment
-        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
-        let token_client = token::Client::new(e, &payment_token);
-        token_client.transfer(&buyer, &admin, &price);
+        if variants.len() < 2 {
+            return Err(Error::InvalidVariants);
+        }
+        let mut total_weight = 0u32;
+        for variant in variants.iter() {
+            Self::validate_strategy(e, &variant.strategy)?;
+            if let PricingStrategy::DutchAuction(_) = variant.strategy {
+                return Err(Error::ExperimentOnAuction);
+            }
+            total_weight = total_weight
+                .checked_add(variant.weight_bps)
+                .ok_or(Error::InvalidVariants)?;
+        }
+        if total_weight != 10000 {
+            return Err(Error::InvalidVariants);
+        }
 
-        // Mint Token
-        let mut counter: u32 = e
+        let id: u32 = e
             .storage()
             .instance()
-            .get(&DataKey::TokenIdCounter)
-            .unwrap();
-        counter += 1;
-        let token_id = counter;
-        e.storage()
-            .instance()
-            .set(&DataKey::TokenIdCounter, &counter);
-
-        Base::sequential_mint(e, &buyer);
-
-        let ticket = Ticket {
-            tier_symbol: tier_symbol.clone(),
-            purchase_time: e.ledger().timestamp(),
-            price_paid: price,
-            is_valid: true,
+            .get(&DataKey::ExperimentCounter)
+            .unwrap_or(0)
+            + 1;
+        e.storage().instance().set(&DataKey::ExperimentCounter, &id);
+
+        let experiment = Experiment {
+            id,
+            variants,
+            active: true,
         };
-        e.storage()
-            .persistent()
-            .set(&DataKey::Ticket(token_id), &ticket);
+        storage::write(e, &DataKey::Experiment(tier_symbol), &experiment);
+        Ok(())
+    }
 
-        tier.minted += 1;
-        tier.current_price = price; // Update the current recorded price for this tier
-        e.storage().persistent().set(&key, &tier);
+    /// Admin-only: stop assigning buyers to variants.  Purchases go back to
+    /// the tier's own strategy; the experiment and its stats stay readable.
+    pub fn end_experiment(e: &Env, tier_symbol: Symbol) -> Result<(), Error> {
+        Self::require_admin(e);
 
-        // Update pricing config last update time
-        let mut config: PricingConfig =
-            e.storage().instance().get(&DataKey::PricingConfig).unwrap();
-        config.last_update_time = e.ledger().timestamp();
-        e.storage().instance().set(&DataKey::PricingConfig, &config);
+        let key = DataKey::Experiment(tier_symbol);
+        let mut experiment: Experiment = storage::read(e, &key).ok_or(Error::ExperimentNotFound)?;
+        experiment.active = false;
+        storage::write(e, &key, &experiment);
+        Ok(())
     }
 
-    // Refund a ticket
-    pub fn refund(e: &Env, owner: Address, payment_token: Address, token_id: u32) {
-        owner.require_auth();
+    pub fn get_experiment(e: &Env, tier_symbol: Symbol) -> Option<Experiment> {
+        storage::read(e, &DataKey::Experiment(tier_symbol))
+    }
 
-        let current_owner = Self::owner_of(e, token_id);
-        if owner != current_owner {
-            panic!("Not the ticket owner");
-        }
+    /// Variant index `buyer` is assigned to in the tier's running experiment.
+    pub fn get_variant(e: &Env, tier_symbol: Symbol, buyer: Address) -> Option<u32> {
+        Self::active_variant(e, &tier_symbol, &buyer).map(|(_, index)| index)
+    }
 
-        let event_info: EventInfo = e.storage().instance().get(&DataKey::EventInfo).unwrap();
-        if e.ledger().timestamp() > event_info.refund_cutoff_time {
-            panic!("Refund window closed");
-        }
+    /// Per-variant exposure, conversion and revenue counters of the tier's
+    /// latest experiment.
+    pub fn get_variant_stats(
+        e: &Env,
+        tier_symbol: Symbol,
+        variant: u32,
+    ) -> Result<VariantStats, Error> {
+        let experiment = Self::get_experiment(e, tier_symbol).ok_or(Error::ExperimentNotFound)?;
+        Ok(Self::read_variant_stats(e, experiment.id, variant))
+    }
 
-        let mut ticket: Ticket = e
-            .storage()
-            .persistent()
-            .get(&DataKey::Ticket(token_id))
-            .unwrap();
-        if !ticket.is_valid {
-            panic!("Ticket already invalidated");
+    /// Quote `buyer` their price and count them as exposed to their variant.
+    ///
+    /// Frontends call this when showing the checkout; each buyer is counted
+    /// once per experiment, so conversion = conversions / exposures.
+    pub fn quote_price(e: &Env, buyer: Address, tier_symbol: Symbol) -> Result<i128, Error> {
+        storage::extend_instance(e);
+        buyer.require_auth();
+        if let Some((experiment, index)) = Self::active_variant(e, &tier_symbol, &buyer) {
+            Self::record_exposure(e, &experiment, index, &buyer);
         }
+        Self::get_ticket_price_for(e, tier_symbol, buyer)
+    }
 
-        // Process refund
-        let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
-        let token_client = token::Client::new(e, &payment_token);
-        token_client.transfer(&admin, &owner, &ticket.price_paid);
+    /// Deterministic variant assignment: sha256(buyer || tier || experiment
+    /// id) reduced to a bucket in `[0, 10000)` and mapped onto the
+    /// cumulative variant weights.
+    fn active_variant(e: &Env, tier_symbol: &Symbol, buyer: &Address) -> Option<(Experiment, u32)> {
+        let experiment = Self::active_experiment(e, tier_symbol)?;
+
+        let mut preimage = buyer.clone().to_xdr(e);
+        preimage.append(&tier_symbol.clone().to_xdr(e));
+        preimage.append(&Bytes::from_array(e, &experiment.id.to_be_bytes()));
+        let digest = e.crypto().sha256(&preimage).to_array();
+        let bucket = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) % 10000;
+
+        let mut cumulative = 0u32;
+        for (index, variant) in experiment.variants.iter().enumerate() {
+            cumulative += variant.weight_bps;
+            if bucket < cumulative {
+                return Some((experiment, index as u32));
+            }
+        }
+        None
+    }
 
-        // Invalidate and Burn
-        ticket.is_valid = false;
-        e.storage()
-            .persistent()
-            .set(&DataKey::Ticket(token_id), &ticket);
-        Base::burn(e, &owner, token_id);
+    fn active_experiment(e: &Env, tier_symbol: &Symbol) -> Option<Experiment> {
+        storage::read::<Experiment>(e, &DataKey::Experiment(tier_symbol.clone()))
+            .filter(|experiment| experiment.active)
     }
 
-    // Ticket Validation
-    pub fn validate_ticket(e: &Env, token_id: u32) -> bool {
-        let key = DataKey::Ticket(token_id);
-        if !e.storage().persistent().has(&key) {
-            return false;
+    fn record_exposure(e: &Env, experiment: &Experiment, variant: u32, buyer: &Address) {
+        let exposed_key = DataKey::ExperimentExposure(experiment.id, buyer.clone());
+        if e.storage().persistent().has(&exposed_key) {
+            return;
         }
-        let ticket: Ticket = e.storage().persistent().get(&key).unwrap();
-        ticket.is_valid
+        storage::write(e, &exposed_key, &true);
+
+        let mut stats = Self::read_variant_stats(e, experiment.id, variant);
+        stats.exposures += 1;
+        storage::write(e, &DataKey::VariantStats(experiment.id, variant), &stats);
     }
 
-    // View functions logic
-    pub fn get_ticket(e: &Env, token_id: u32) -> Ticket {
-        e.storage()
-            .persistent()
-            .get(&DataKey::Ticket(token_id))
-            .unwrap()
+    fn read_variant_stats(e: &Env, experiment_id: u32, variant: u32) -> VariantStats {
+        storage::read(e, &DataKey::VariantStats(experiment_id, variant)).unwrap_or(VariantStats {
+            exposures: 0,
+            conversions: 0,
+            revenue: 0,
+        })
     }
-}
 
-// Implement SEP-0054 via OpenZeppelin Interface
-#[contractimpl]
-impl NonFungibleToken for SoulboundTicketContract {
-    type ContractType = Base;
+    /// Read the current external price multiplier without storing or
+    /// publishing anything.
+    ///
+    /// Strategy:
+    ///  1. With `twap_window` set, use the smoothed multiplier maintained by
+    ///     `record_price_observation` while its newest observation is
+    ///     within `max_oracle_age_seconds`.  Spot prices are never used
+    ///     directly in this mode.
+    ///  2. Otherwise read a live price (see `read_live_price`) and convert
+    ///     it with the stored `oracle_reference_price`.
+    ///  3. If no price is available, apply `stale_policy`: neutral
+    ///     `ORACLE_PRECISION`, the last known good multiplier, or
+    ///     `OraclePriceUnavailable`.
+    fn read_oracle_multiplier(e: &Env, config: &PricingConfig) -> Result<MultiplierReading, Error> {
+        if config.twap_window > 0 {
+            if let Some(multiplier) = Self::fresh_twap_multiplier(e, config) {
+                return Ok(MultiplierReading {
+                    multiplier,
+                    live: false,
+                    fallback: None,
+                });
+            }
+        } else if let Some(result) = Self::read_live_price(e, config) {
+            return Ok(MultiplierReading {
+                multiplier: oracle_price_to_multiplier(
+                    result.price,
+                    config.oracle_reference_price,
+                    ORACLE_PRECISION,
+                ),
+                live: true,
+                fallback: (!result.from_primary).then(|| (symbol_short!("dex"), result.price)),
+            });
+        }
 
-    fn balance(e: &Env, owner: Address) -> u32 {
-        Self::ContractType::balance(e, &owner)
+        // No usable price
+        let neutral = MultiplierReading {
+            multiplier: ORACLE_PRECISION,
+            live: false,
+            fallback: Some((symbol_short!("neutral"), ORACLE_PRECISION)),
+        };
+        match config.stale_policy {
+            StalePricePolicy::Neutral => Ok(neutral),
+            StalePricePolicy::LastKnownGood => match Self::get_last_good_multiplier(e.clone()) {
+                Some(last_good) => Ok(MultiplierReading {
+                    multiplier: last_good.multiplier,
+                    live: false,
+                    fallback: Some((symbol_short!("last_good"), last_good.multiplier)),
+                }),
+                // Nothing recorded yet: neutral is the only safe option
+                None => Ok(neutral),
+            },
+            StalePricePolicy::HaltSales => Err(Error::OraclePriceUnavailable),
+        }
     }
 
-    fn owner_of(e: &Env, token_id: u32) -> Address {
-        Self::ContractType::owner_of(e, token_id)
+    /// Read the multiplier for a refresh: every live multiplier is persisted
+    /// as the last known good one, and an `oracle_fallback` event is
+    /// published whenever a fallback path is taken.
+    fn fetch_oracle_multiplier(e: &Env, config: &PricingConfig) -> Result<i128, Error> {
+        let reading = Self::read_oracle_multiplier(e, config)?;
+        if reading.live {
+            let last_good = LastGoodMultiplier {
+                multiplier: reading.multiplier,
+                timestamp: e.ledger().timestamp(),
+            };
+            e.storage()
+                .instance()
+                .set(&DataKey::LastGoodMultiplier, &last_good);
+        }
+        if let Some((path, value)) = reading.fallback {
+            Self::publish_oracle_fallback(e, path, value);
+        }
+        Ok(reading.multiplier)
     }
 
-    // Soulbound restrictions overrides
-    fn transfer(_e: &Env, _from: Address, _to: Address, _token_id: u32) {
-        panic!("Soulbound: Tickets cannot be transferred");
+    /// Current raw price (8 decimals, $1.00 == 100_000_000), read without
+    /// side effects.
+    ///
+    /// With `oracle_sources` configured, query all of them, take the median,
+    /// discard sources outside `max_deviation_bps` of it and require
+    /// `min_sources` agreeing sources (see `fetch_aggregated_price`).
+    /// Otherwise query `oracle_address` through the `oracle_kind` interface,
+    /// check it is within `max_oracle_age_seconds`, and fall back to
+    /// `DexPriceRouterClient::try_get_spot_price(pair)`.
+    fn read_live_price(e: &Env, config: &PricingConfig) -> Option<OracleResult> {
+        if config.oracle_sources.is_empty() {
+            fetch_price_with_fallback(
+                e,
+                &config.oracle_kind,
+                &config.oracle_address,
+                &config.dex_pool_address,
+                config.oracle_pair.clone(),
+                config.max_oracle_age_seconds,
+            )
+        } else {
+            fetch_aggregated_price(
+                e,
+                &config.oracle_sources,
+                config.oracle_pair.clone(),
+                config.max_oracle_age_seconds,
+                config.max_deviation_bps,
+                config.min_sources,
+            )
+            .map(|price| OracleResult {
+                price,
+                timestamp: e.ledger().timestamp(),
+                from_primary: true,
+            })
+        }
     }
 
-    fn transfer_from(_e: &Env, _spender: Address, _from: Address, _to: Address, _token_id: u32) {
-        panic!("Soulbound: Tickets cannot be transferred");
+    /// `read_live_price`, publishing an `oracle_fallback` event when the DEX
+    /// answered instead of the oracle.
+    fn fetch_live_price(e: &Env, config: &PricingConfig) -> Option<i128> {
+        let result = Self::read_live_price(e, config)?;
+        if !result.from_primary {
+            Self::publish_oracle_fallback(e, symbol_short!("dex"), result.price);
+        }
+        Some(result.price)
     }
 
-    fn approve(
-        _e: &Env,
-        _approver: Address,
-        _approved: Address,
-        _token_id: u32,
-        _live_until_ledger: u32,
-    ) {
-        panic!("Soulbound: Approval disabled for non-transferable tokens");
+    fn fresh_twap_multiplier(e: &Env, config: &PricingConfig) -> Option<i128> {
+        let observations = Self::get_price_observations(e.clone());
+        let newest = observations.last()?;
+        if e.ledger().timestamp() > newest.timestamp + config.max_oracle_age_seconds {
+            return None;
+        }
+        e.storage().instance().get(&DataKey::TwapMultiplier)
     }
 
-    fn approve_for_all(_e: &Env, _owner: Address, _operator: Address, _live_until_ledger: u32) {
-        panic!("Soulbound: Approval disabled for non-transferable tokens");
+    /// Permissionless keeper call: append a live price to the observation
+    /// ring buffer and refresh the smoothed multiplier.  Does nothing (and
+    /// returns `false`) when smoothing is disabled, `update_frequency` has
+    /// not elapsed since the last observation, or no live price is available.
+    pub fn record_price_observation(e: &Env) -> bool {
+        storage::extend_instance(e);
+        let config: PricingConfig = e.storage().instance().get(&DataKey::PricingConfig).unwrap();
+        Self::observe_price(e, &config)
     }
 
-    fn get_approved(_e: &Env, _token_id: u32) -> Option<Address> {
-        None
-    }
+    fn observe_price(e: &Env, config: &PricingConfig) -> bool {
+        if config.twap_window == 0 {
+            return false;
+        }
 
-    fn is_approved_for_all(_e: &Env, _owner: Address, _operator: Address) -> bool {
-        false
+        let now = e.ledger().timestamp();
+        let mut observations = Self::get_price_observations(e.clone());
+        if let Some(newest) = observations.last() {
+            if now < newest.timestamp + config.update_frequency {
+                return false;
+            }
+        }
+        let price = match Self::fetch_live_price(e, config) {
+            Some(price) => price,
+            None => return false,
+        };
+
+        // Keep the newest `twap_window` observations, oldest first
+        observations.push_back(PriceObservation {
+            price,
+            timestamp: now,
+        });
+        while observations.len() > config.twap_window {
+            observations.pop_front();
+        }
+        e.storage()
+            .instance()
+            .set(&DataKey::PriceObservations, &observations);
+
+        let twap = time_weighted_average(&observations, now).unwrap();
+        let target =
+            oracle_price_to_multiplier(twap, config.oracle_reference_price, ORACLE_PRECISION);
+        let multiplier = match e
+            .storage()
+            .instance()
+            .get::<_, i128>(&DataKey::TwapMultiplier)
+        {
+            Some(previous) => clamp_change(previous, target, config.max_change_bps),
+            None => target,
+        };
+        e.storage()
+            .instance()
+            .set(&DataKey::TwapMultiplier, &multiplier);
+        e.storage().instance().set(
+            &DataKey::LastGoodMultiplier,
+            &LastGoodMultiplier {
+                multiplier,
+                timestamp: now,
+            },
+        );
+
+        PriceObserved { price, multiplier }.publish(e);
+        true
     }
 
-    // Metadata
-    fn name(e: &Env) -> String {
-        Self::ContractType::name(e)
+    /// Multiplier applied to ticket prices right now.
+    ///
+    /// The cached multiplier is served while it is younger than
+    /// `update_frequency` (and always within the ledger it was computed in),
+    /// so views stay cheap and deterministic.  Once it has expired, views
+    /// compute what the next refresh would produce without storing it.
+    fn current_multiplier(e: &Env, config: &PricingConfig) -> Result<i128, Error> {
+        if !Self::refresh_due(e, config) {
+            if let Some(multiplier) = e.storage().instance().get(&DataKey::CachedMultiplier) {
+                return Ok(multiplier);
+            }
+        }
+        Self::read_oracle_multiplier(e, config).map(|reading| reading.multiplier)
     }
 
-    fn symbol(e: &Env) -> String {
-        Self::ContractType::symbol(e)
+    fn refresh_due(e: &Env, config: &PricingConfig) -> bool {
+        !e.storage().instance().has(&DataKey::CachedMultiplier)
+            || e.ledger().timestamp() >= config.last_update_time + config.update_frequency.max(1)
+    }
+
+    /// Recompute the cached multiplier, stamp `last_update_time` and publish
+    /// a `repriced` event with the previous and new multiplier.
+    fn refresh_multiplier(e: &Env, config: &mut PricingConfig) -> Result<i128, Error> {
+        let multiplier = Self::fetch_oracle_multiplier(e, config)?;
+        let previous: i128 = e
+            .storage()
+            .instance()
+            .get(&DataKey::CachedMultiplier)
+            .unwrap_or(ORACLE_PRECISION);
+
+        e.storage()
+            .instance()
+            .set(&DataKey::CachedMultiplier, &multiplier);
+        config.last_update_time = e.ledger().timestamp();
+        e.storage().instance().set(&DataKey::PricingConfig, config);
+
+        Repriced {
+            previous,
+            multiplier,
+            timestamp: config.last_update_time,
+        }
+        .publish(e);
+        Ok(multiplier)
+    }
+
+    /// Permissionless keeper call: record a TWAP observation and refresh the
+    /// cached multiplier once `update_frequency` has elapsed.  Returns
+    /// whether the multiplier was refreshed, or `OraclePriceUnavailable`
+    /// when the `HaltSales` policy has no price to refresh from.
+    pub fn poke_price(e: &Env) -> Result<bool, Error> {
+        storage::extend_instance(e);
+        let mut config: PricingConfig =
+            e.storage().instance().get(&DataKey::PricingConfig).unwrap();
+        Self::observe_price(e, &config);
+        if !Self::refresh_due(e, &config) {
+            return Ok(false);
+        }
+        Self::refresh_multiplier(e, &mut config)?;
+        Ok(true)
+    }
+
+    /// Cached multiplier and the time it was refreshed, if any.
+    pub fn get_cached_multiplier(e: &Env) -> Option<(i128, u64)> {
+        let multiplier: i128 = e.storage().instance().get(&DataKey::CachedMultiplier)?;
+        let config: PricingConfig = e.storage().instance().get(&DataKey::PricingConfig).unwrap();
+        Some((multiplier, config.last_update_time))
+    }
+
+    /// Observations currently in the TWAP ring buffer, oldest first.
+    pub fn get_price_observations(e: Env) -> Vec<PriceObservation> {
+        e.storage()
+            .instance()
+            .get(&DataKey::PriceObservations)
+            .unwrap_or(Vec::new(&e))
+    }
+
+    fn publish_oracle_fallback(e: &Env, path: Symbol, value: i128) {
+        OracleFallback { path, value }.publish(e);
+    }
+
+    /// Last multiplier computed from a live oracle or DEX price.
+    pub fn get_last_good_multiplier(e: Env) -> Option<LastGoodMultiplier> {
+        e.storage().instance().get(&DataKey::LastGoodMultiplier)
+    }
+
+    // Dynamic pricing query
+    pub fn get_ticket_price(e: &Env, tier_symbol: Symbol) -> Result<i128, Error> {
+        let tier = Self::read_tier(e, &tier_symbol)?;
+        Self::price_with_strategy(e, &tier, &tier.strategy, e.ledger().timestamp())
+    }
+
+    /// Price of `tier` as seen by `buyer`, who may be assigned to an
+    /// experiment variant with its own strategy.
+    pub fn get_ticket_price_for(
+        e: &Env,
+        tier_symbol: Symbol,
+        buyer: Address,
+    ) -> Result<i128, Error> {
+        let tier = Self::read_tier(e, &tier_symbol)?;
+        let strategy = match Self::active_variant(e, &tier_symbol, &buyer) {
+            Some((experiment, index)) => experiment.variants.get(index).unwrap().strategy,
+            None => tier.strategy.clone(),
+        };
+        Self::price_with_strategy(e, &tier, &strategy, e.ledger().timestamp())
+    }
+
+    fn read_tier(e: &Env, tier_symbol: &Symbol) -> Result<Tier, Error> {
+        storage::read(e, &DataKey::Tier(tier_symbol.clone())).ok_or(Error::TierNotFound)
+    }
+
+    /// `read_tier` for entry points declared with a feature error enum:
+    /// aborts with the shared `Error::TierNotFound` code.
+    fn require_tier(e: &Env, tier_symbol: &Symbol) -> Tier {
+        Self::read_tier(e, tier_symbol).unwrap_or_else(|err| panic_with_error!(e, err))
+    }
+
+    /// Admin-only: configure how `token` is priced in `currency`.
+    pub fn set_payment_asset(
+        e: &Env,
+        token: Address,
+        currency: Symbol,
+        asset: PaymentAsset,
+    ) -> Result<(), Error> {
+        Self::require_admin(e);
+        if asset.max_slippage_bps > 10000 {
+            return Err(Error::InvalidSlippage);
+        }
+        storage::write(
+            e,
+            &DataKey::PaymentAsset(token.clone(), currency.clone()),
+            &asset,
+        );
+        e.storage()
+            .persistent()
+            .remove(&DataKey::PaymentRate(token, currency));
+        Ok(())
+    }
+
+    pub fn get_payment_asset(e: &Env, token: Address, currency: Symbol) -> Option<PaymentAsset> {
+        storage::read(e, &DataKey::PaymentAsset(token, currency))
+    }
+
+    /// Admin-only: quote a tier in fiat cents (`Some(currency)`) or in
+    /// payment token units (`None`).
+    pub fn set_tier_currency(
+        e: &Env,
+        tier_symbol: Symbol,
+        currency: Option<Symbol>,
+    ) -> Result<(), Error> {
+        Self::require_admin(e);
+
+        let mut tier = Self::read_tier(e, &tier_symbol)?;
+        if currency.is_some() {
+            if let PricingStrategy::DutchAuction(_) = tier.strategy {
+                // Clearing rebates compare prices paid in token units
+                return Err(Error::FiatAuctionUnsupported);
+            }
+        }
+        tier.currency = currency;
+        Self::write_tier_update(e, tier_symbol, tier);
+        Ok(())
+    }
+
+    /// Amount of `payment_token` `buyer` would pay for `tier` right now,
+    /// before promo codes.
+    pub fn get_payment_quote(
+        e: &Env,
+        tier_symbol: Symbol,
+        buyer: Address,
+        payment_token: Address,
+    ) -> Result<i128, Error> {
+        let tier = Self::read_tier(e, &tier_symbol)?;
+        let price = Self::get_ticket_price_for(e, tier_symbol, buyer)?;
+        match tier.currency {
+            Some(currency) => {
+                let asset = Self::read_payment_asset(e, &payment_token, &currency)?;
+                let rate = match Self::quoted_rate(e, &payment_token, &currency) {
+                    Some(quoted) => quoted.price,
+                    None => Self::fetch_payment_rate(e, &asset)?,
+                };
+                Self::convert_fiat(e, price, rate, &asset)
+            }
+            None => Ok(price),
+        }
+    }
+
+    fn read_payment_asset(
+        e: &Env,
+        token: &Address,
+        currency: &Symbol,
+    ) -> Result<PaymentAsset, Error> {
+        storage::read(e, &DataKey::PaymentAsset(token.clone(), currency.clone()))
+            .ok_or(Error::PaymentAssetNotAccepted)
+    }
+
+    fn fetch_payment_rate(e: &Env, asset: &PaymentAsset) -> Result<i128, Error> {
+        let config: PricingConfig = e.storage().instance().get(&DataKey::PricingConfig).unwrap();
+        fetch_oracle_result(
+            e,
+            &asset.oracle_kind,
+            &asset.oracle_address,
+            asset.oracle_pair.clone(),
+            config.max_oracle_age_seconds,
+        )
+        .map(|result| result.price)
+        .ok_or(Error::PaymentRateUnavailable)
+    }
+
+    /// Rate quoted to buyers, valid for `update_frequency` like the cached
+    /// multiplier.
+    fn quoted_rate(e: &Env, token: &Address, currency: &Symbol) -> Option<PriceObservation> {
+        let config: PricingConfig = e.storage().instance().get(&DataKey::PricingConfig).unwrap();
+        let quoted: PriceObservation =
+            storage::read(e, &DataKey::PaymentRate(token.clone(), currency.clone()))?;
+        if e.ledger().timestamp() >= quoted.timestamp + config.update_frequency.max(1) {
+            return None;
+        }
+        Some(quoted)
+    }
+
+    /// Token amount for `fiat_amount` at `rate`, clamped to the configured
+    /// price bounds, which are in token units.
+    fn convert_fiat(
+        e: &Env,
+        fiat_amount: i128,
+        rate: i128,
+        asset: &PaymentAsset,
+    ) -> Result<i128, Error> {
+        let config: PricingConfig = e.storage().instance().get(&DataKey::PricingConfig).unwrap();
+        let amount = fiat_to_token_amount(fiat_amount, rate, asset.token_decimals)
+            .ok_or(Error::PaymentRateUnavailable)?;
+        Ok(amount.max(config.price_floor).min(config.price_ceiling))
+    }
+
+    /// Token amount charged for `fiat_amount` at purchase time.
+    ///
+    /// While a quoted rate is valid it is charged as-is, provided the live
+    /// rate has not moved more than `max_slippage_bps` away from it.  When
+    /// there is no valid quote, or the live rate has drifted out of the band,
+    /// the live rate becomes the new quote and is charged; buyers bound what
+    /// that may cost them with `purchase`'s `max_amount`.
+    fn charge_in_token(
+        e: &Env,
+        token: &Address,
+        currency: &Symbol,
+        fiat_amount: i128,
+    ) -> Result<i128, Error> {
+        let asset = Self::read_payment_asset(e, token, currency)?;
+        let live = Self::fetch_payment_rate(e, &asset)?;
+
+        let within_band = |quoted: &PriceObservation| {
+            let band = quoted.price * asset.max_slippage_bps as i128 / 10000;
+            (live - quoted.price).abs() <= band
+        };
+        let rate = match Self::quoted_rate(e, token, currency) {
+            Some(quoted) if within_band(&quoted) => quoted.price,
+            _ => {
+                let quoted = PriceObservation {
+                    price: live,
+                    timestamp: e.ledger().timestamp(),
+                };
+                storage::write(
+                    e,
+                    &DataKey::PaymentRate(token.clone(), currency.clone()),
+                    &quoted,
+                );
+                live
+            }
+        };
+        Self::convert_fiat(e, fiat_amount, rate, &asset)
+    }
+
+    /// Price changes of a tier over time, for display.
+    ///
+    /// For `TimeDecay` tiers this lists one point per early-bird phase, the
+    /// full price after the last phase and the surge price, each computed
+    /// with the current demand and oracle multiplier.  Other strategies
+    /// return a single point with the current price.
+    pub fn get_price_timeline(e: &Env, tier_symbol: Symbol) -> Result<Vec<PricePoint>, Error> {
+        let tier = Self::read_tier(e, &tier_symbol)?;
+
+        let mut starts = Vec::new(e);
+        starts.push_back(0u64);
+        if let PricingStrategy::TimeDecay(schedule) = &tier.strategy {
+            for phase in schedule.phases.iter() {
+                starts.push_back(phase.until);
+            }
+            let event_info: EventInfo = e.storage().instance().get(&DataKey::EventInfo).unwrap();
+            if let Some(from) = surge_start(schedule, event_info.start_time) {
+                starts.push_back(from);
+            }
+        }
+
+        let mut timeline = Vec::new(e);
+        for from in starts.iter() {
+            let price = Self::price_with_strategy(e, &tier, &tier.strategy, from)?;
+            timeline.push_back(PricePoint { from, price });
+        }
+        Ok(timeline)
+    }
+
+    /// Apply `strategy`, the oracle multiplier and the configured bounds to
+    /// `tier` as of `now`.  Frozen pricing short-circuits to the last
+    /// recorded price.
+    fn price_with_strategy(
+        e: &Env,
+        tier: &Tier,
+        strategy: &PricingStrategy,
+        now: u64,
+    ) -> Result<i128, Error> {
+        let config: PricingConfig = e.storage().instance().get(&DataKey::PricingConfig).unwrap();
+
+        if config.is_frozen {
+            return Ok(tier.current_price);
+        }
+
+        // Base price
+        let mut price = tier.base_price;
+
+        // Apply strategy variations
+        match strategy {
+            PricingStrategy::Standard => {
+                // Demand based: base_price * (1 + (minted / (max_supply / 5)) * 5%)
+                let thresholds_passed = tier.minted / (tier.max_supply.max(1) / 5).max(1);
+                let increase =
+                    Self::bps_of(price, PRICE_INCREASE_BPS * (thresholds_passed as i128))?;
+                price = price.checked_add(increase).ok_or(Error::PriceOverflow)?;
+            }
+            PricingStrategy::TimeDecay(schedule) => {
+                let event_info: EventInfo =
+                    e.storage().instance().get(&DataKey::EventInfo).unwrap();
+                price = time_decay_price(price, schedule, event_info.start_time, now);
+            }
+            PricingStrategy::AbTestA => {
+                // High demand sensitivity (10% increase per threshold)
+                let thresholds_passed = tier.minted / (tier.max_supply.max(1) / 5).max(1);
+                let increase =
+                    Self::bps_of(price, PRICE_INCREASE_BPS * 2 * (thresholds_passed as i128))?;
+                price = price.checked_add(increase).ok_or(Error::PriceOverflow)?;
+            }
+            PricingStrategy::AbTestB => {
+                // Floor starts higher (+20%)
+                let increase = Self::bps_of(price, 2000)?;
+                price = price.checked_add(increase).ok_or(Error::PriceOverflow)?;
+            }
+            PricingStrategy::BondingCurve(curve) => {
+                price = bonding_curve_price(price, tier.minted, tier.max_supply, curve);
+            }
+            PricingStrategy::DutchAuction(auction) => {
+                // The auction schedule replaces the base price entirely
+                price = dutch_auction_price(auction, now);
+            }
+        }
+
+        // Apply external Oracle factors using the real DIA oracle integration.
+        // Fiat tiers use the oracle for conversion at purchase time instead,
+        // and are bounded once converted to token units (see `convert_fiat`).
+        if tier.currency.is_none() {
+            let oracle_multiplier = Self::current_multiplier(e, &config)?;
+            price = price
+                .checked_mul(oracle_multiplier)
+                .ok_or(Error::PriceOverflow)?
+                / ORACLE_PRECISION;
+
+            // Apply bounds
+            price = price.max(config.price_floor).min(config.price_ceiling);
+        }
+
+        // We only return the price here. It is updated during `purchase`.
+        Ok(price)
+    }
+
+    /// `bps` basis points of `price`, or `PriceOverflow` if the product
+    /// does not fit in an `i128`.
+    fn bps_of(price: i128, bps: i128) -> Result<i128, Error> {
+        price
+            .checked_mul(bps)
+            .map(|product| product / 10000)
+            .ok_or(Error::PriceOverflow)
+    }
+
+    // Batch Minting for Organizer
+    pub fn batch_mint(e: &Env, to: Address, tier_symbol: Symbol, amount: u32) -> Result<(), Error> {
+        Self::require_admin(e);
+
+        let key = DataKey::Tier(tier_symbol.clone());
+        let mut tier = Self::read_tier(e, &tier_symbol)?;
+
+        if tier.minted + amount > tier.max_supply {
+            return Err(Error::ExceedsMaxSupply);
+        }
+        if amount > Self::session_seats_left(e, &tier_symbol) {
+            return Err(Error::SessionFull);
+        }
+
+        let mut token_ids = Vec::new(e);
+        for _ in 0..amount {
+            // Admin mints are free
+            token_ids.push_back(Self::mint_ticket(e, &to, &to, &tier_symbol, 0, None, None));
+        }
+
+        tier.minted += amount;
+        storage::write(e, &key, &tier);
+
+        TicketsMinted {
+            to,
+            tier: tier_symbol,
+            token_ids,
+        }
+        .publish(e);
+        Ok(())
+    }
+
+    /// Buy a ticket.  With `max_amount` the purchase fails instead of
+    /// charging more than that many payment token units, e.g. when a fiat
+    /// tier's rate moved since `get_payment_quote`.
+    pub fn purchase(
+        e: &Env,
+        buyer: Address,
+        payment_token: Address,
+        tier_symbol: Symbol,
+        promo_code: Option<String>,
+        max_amount: Option<i128>,
+    ) -> Result<u32, Error> {
+        Self::purchase_for(
+            e,
+            buyer.clone(),
+            buyer,
+            payment_token,
+            tier_symbol,
+            promo_code,
+            max_amount,
+        )
+    }
+
+    /// Buy a ticket paid by `payer` and minted to `recipient`, e.g. a
+    /// company booking for an employee.  Pricing, promo codes and
+    /// experiments apply to `payer`, and refunds go back to `payer`.
+    pub fn purchase_for(
+        e: &Env,
+        payer: Address,
+        recipient: Address,
+        payment_token: Address,
+        tier_symbol: Symbol,
+        promo_code: Option<String>,
+        max_amount: Option<i128>,
+    ) -> Result<u32, Error> {
+        storage::extend_instance(e);
+        payer.require_auth();
+
+        let key = DataKey::Tier(tier_symbol.clone());
+        let mut tier = Self::read_tier(e, &tier_symbol)?;
+
+        if !tier.active {
+            return Err(Error::TierInactive);
+        }
+        let now = e.ledger().timestamp();
+        if now < tier.sale_start {
+            return Err(Error::SaleNotStarted);
+        }
+        if tier.sale_end != 0 && now > tier.sale_end {
+            return Err(Error::SaleEnded);
+        }
+        if tier.minted >= tier.max_supply {
+            return Err(Error::TierSoldOut);
+        }
+        if Self::session_seats_left(e, &tier_symbol) == 0 {
+            return Err(Error::SessionFull);
+        }
+        // A cancelled lottery gives the tier back to regular sales
+        let lottery: Option<Lottery> = storage::read(e, &DataKey::Lottery(tier_symbol.clone()));
+        if lottery.is_some_and(|lottery| !lottery.cancelled) {
+            return Err(Error::TierAllocatedByLottery);
+        }
+        if let PricingStrategy::DutchAuction(auction) = &tier.strategy {
+            if now < auction.start_time {
+                return Err(Error::AuctionNotStarted);
+            }
+            if now > auction.end_time {
+                return Err(Error::AuctionEnded);
+            }
+        }
+
+        let mut config: PricingConfig =
+            e.storage().instance().get(&DataKey::PricingConfig).unwrap();
+        Self::observe_price(e, &config);
+        if Self::refresh_due(e, &config) {
+            Self::refresh_multiplier(e, &mut config)?;
+        }
+
+        let assignment = Self::active_variant(e, &tier_symbol, &payer);
+        let list_price = Self::get_ticket_price_for(e, tier_symbol.clone(), payer.clone())?;
+        let price = match promo_code {
+            Some(code) => Self::redeem_promo_code(e, &payer, &tier_symbol, code, list_price)?,
+            None => list_price,
+        };
+
+        // Fiat tiers are charged the equivalent amount of the payment token
+        let price = match &tier.currency {
+            Some(currency) => Self::charge_in_token(e, &payment_token, currency, price)?,
+            None => price,
+        };
+        if max_amount.is_some_and(|max_amount| price > max_amount) {
+            return Err(Error::MaxAmountExceeded);
+        }
+
+        // Process payment; proceeds stay in the contract until withdrawn
+        let token_client = token::Client::new(e, &payment_token);
+        token_client.transfer(&payer, e.current_contract_address(), &price);
+        Self::record_sale(e, &payment_token, price);
+
+        // Mint Token
+        let variant = assignment
+            .as_ref()
+            .map(|(experiment, index)| (experiment.id, *index));
+        let token_id = Self::mint_ticket(
+            e,
+            &payer,
+            &recipient,
+            &tier_symbol,
+            price,
+            Some(payment_token.clone()),
+            variant,
+        );
+
+        if let Some((experiment, index)) = assignment {
+            Self::record_exposure(e, &experiment, index, &payer);
+            let mut stats = Self::read_variant_stats(e, experiment.id, index);
+            stats.conversions += 1;
+            stats.revenue += price;
+            storage::write(e, &DataKey::VariantStats(experiment.id, index), &stats);
+        }
+
+        // Promo discounts are per buyer; the tier keeps the undiscounted price
+        tier.minted += 1;
+        tier.current_price = list_price; // Update the current recorded price for this tier
+        storage::write(e, &key, &tier);
+
+        // Track the lowest accepted auction price for uniform clearing rebates
+        if let PricingStrategy::DutchAuction(_) = tier.strategy {
+            let clearing_key = DataKey::AuctionClearingPrice(tier_symbol.clone());
+            let clearing: i128 = storage::read(e, &clearing_key).unwrap_or(list_price);
+            storage::write(e, &clearing_key, &clearing.min(list_price));
+        }
+
+        TicketPurchased {
+            buyer: payer,
+            tier: tier_symbol,
+            recipient,
+            token_id,
+            price,
+            payment_token,
+        }
+        .publish(e);
+        Ok(token_id)
+    }
+
+    /// Admin-only: register a promo code by the sha256 hash of its text, so
+    /// the code itself stays off-chain until someone redeems it.
+    pub fn register_promo_code(
+        e: &Env,
+        code_hash: BytesN<32>,
+        discount: PromoDiscount,
+        max_redemptions: u32,
+        tiers: Vec<Symbol>,
+        expires_at: u64,
+    ) -> Result<(), Error> {
+        Self::require_admin(e);
+
+        let key = DataKey::PromoCode(code_hash.clone());
+        if e.storage().persistent().has(&key) {
+            return Err(Error::PromoCodeExists);
+        }
+        match discount {
+            PromoDiscount::Bps(bps) if bps > 10000 => return Err(Error::InvalidPromoCode),
+            PromoDiscount::Fixed(amount) if amount < 0 => return Err(Error::InvalidPromoCode),
+            _ => {}
+        }
+        if max_redemptions == 0 {
+            return Err(Error::InvalidPromoCode);
+        }
+
+        let promo = PromoCode {
+            discount,
+            max_redemptions,
+            redemptions: 0,
+            tiers,
+            expires_at,
+        };
+        storage::write(e, &key, &promo);
+
+        PromoRegistered {
+            code_hash,
+            max_redemptions,
+            expires_at,
+        }
+        .publish(e);
+        Ok(())
+    }
+
+    pub fn get_promo_code(e: &Env, code_hash: BytesN<32>) -> Result<PromoCode, Error> {
+        storage::read(e, &DataKey::PromoCode(code_hash)).ok_or(Error::PromoCodeNotFound)
+    }
+
+    pub fn get_promo_redemptions(e: &Env, code_hash: BytesN<32>) -> Result<u32, Error> {
+        Ok(Self::get_promo_code(e, code_hash)?.redemptions)
+    }
+
+    /// Check `code` against its registration, count the redemption and
+    /// return `price` after the discount.
+    fn redeem_promo_code(
+        e: &Env,
+        buyer: &Address,
+        tier_symbol: &Symbol,
+        code: String,
+        price: i128,
+    ) -> Result<i128, Error> {
+        let code_hash: BytesN<32> = e.crypto().sha256(&code.to_bytes()).into();
+        let key = DataKey::PromoCode(code_hash.clone());
+        let mut promo: PromoCode = storage::read(e, &key).ok_or(Error::PromoCodeNotFound)?;
+
+        if e.ledger().timestamp() > promo.expires_at {
+            return Err(Error::PromoCodeExpired);
+        }
+        if promo.redemptions >= promo.max_redemptions {
+            return Err(Error::PromoCodeExhausted);
+        }
+        if !promo.tiers.is_empty() && !promo.tiers.contains(tier_symbol) {
+            return Err(Error::PromoCodeNotValidForTier);
+        }
+
+        let discount = match promo.discount {
+            PromoDiscount::Bps(bps) => mul_div(price, bps as i128, 10000),
+            PromoDiscount::Fixed(amount) => amount.min(price),
+        };
+
+        promo.redemptions += 1;
+        storage::write(e, &key, &promo);
+
+        PromoRedeemed {
+            code_hash,
+            buyer: buyer.clone(),
+            tier: tier_symbol.clone(),
+            discount,
+        }
+        .publish(e);
+
+        Ok(price - discount)
+    }
+
+    /// Admin-only: set the identity registry used to enforce one lottery
+    /// entry per DID.  Without a registry, entries are limited per address.
+    pub fn set_identity_registry(e: &Env, registry: Address) {
+        Self::require_admin(e);
+        e.storage()
+            .instance()
+            .set(&DataKey::IdentityRegistry, &registry);
+    }
+
+    /// Admin-only: allocate the remaining supply of a tier by lottery instead
+    /// of first-come-first-served.  `purchase` is disabled for the tier.
+    ///
+    /// At most `max_entrants` (up to 10,000) can enter.  The draw must finish
+    /// by `draw_deadline`; after that every entrant not yet picked can reclaim
+    /// their deposit.
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure_lottery(
+        e: &Env,
+        tier_symbol: Symbol,
+        payment_token: Address,
+        price: i128,
+        registration_start: u64,
+        registration_end: u64,
+        max_entrants: u32,
+        draw_deadline: u64,
+    ) -> Result<(), LotteryError> {
+        Self::require_admin(e);
+
+        Self::require_tier(e, &tier_symbol);
+        let key = DataKey::Lottery(tier_symbol);
+        if e.storage().persistent().has(&key) {
+            return Err(LotteryError::LotteryExists);
+        }
+        if price < 0 {
+            return Err(LotteryError::InvalidLottery);
+        }
+        if registration_start >= registration_end || draw_deadline <= registration_end {
+            return Err(LotteryError::InvalidLottery);
+        }
+        if max_entrants == 0 || max_entrants > MAX_LOTTERY_ENTRANTS {
+            return Err(LotteryError::InvalidLottery);
+        }
+
+        let lottery = Lottery {
+            payment_token,
+            price,
+            registration_start,
+            registration_end,
+            max_entrants,
+            draw_deadline,
+            entrants: 0,
+            winners: 0,
+            picked: 0,
+            drawn: false,
+            refunded_until: 0,
+            settled: false,
+            cancelled: false,
+        };
+        storage::write(e, &key, &lottery);
+        Ok(())
+    }
+
+    /// Enter a tier's lottery, escrowing the ticket price in this contract.
+    ///
+    /// When an identity registry is configured the entrant must own a DID and
+    /// each DID can enter once; otherwise each address can enter once.
+    pub fn enter_lottery(
+        e: &Env,
+        entrant: Address,
+        tier_symbol: Symbol,
+    ) -> Result<(), LotteryError> {
+        storage::extend_instance(e);
+        entrant.require_auth();
+
+        let lottery_key = DataKey::Lottery(tier_symbol.clone());
+        let mut lottery = Self::get_lottery(e, tier_symbol.clone())?;
+        if lottery.cancelled {
+            return Err(LotteryError::LotteryCancelled);
+        }
+        let now = e.ledger().timestamp();
+        if now < lottery.registration_start || now > lottery.registration_end {
+            return Err(LotteryError::RegistrationClosed);
+        }
+        if lottery.entrants >= lottery.max_entrants {
+            return Err(LotteryError::LotteryFull);
+        }
+
+        let entry_key = DataKey::LotteryEntry(tier_symbol.clone(), entrant.clone());
+        if e.storage().persistent().has(&entry_key) {
+            return Err(LotteryError::AlreadyEntered);
+        }
+
+        if let Some(registry) = e
+            .storage()
+            .instance()
+            .get::<_, Address>(&DataKey::IdentityRegistry)
+        {
+            let did = IdentityRegistryClient::new(e, &registry)
+                .get_did_by_address(&entrant)
+                .unwrap_or_else(|| panic_with_error!(e, Error::DidRequired));
+            let did_key = DataKey::LotteryDid(tier_symbol.clone(), did);
+            if e.storage().persistent().has(&did_key) {
+                return Err(LotteryError::AlreadyEntered);
+            }
+            storage::write(e, &did_key, &entrant);
+        }
+
+        let token_client = token::Client::new(e, &lottery.payment_token);
+        token_client.transfer(&entrant, e.current_contract_address(), &lottery.price);
+
+        let index = lottery.entrants;
+        storage::write(
+            e,
+            &DataKey::LotteryEntrant(tier_symbol.clone(), index),
+            &entrant,
+        );
+        storage::write(
+            e,
+            &entry_key,
+            &LotteryEntry {
+                index,
+                refunded: false,
+            },
+        );
+        lottery.entrants += 1;
+        storage::write(e, &lottery_key, &lottery);
+        Ok(())
+    }
+
+    /// Admin-only: run the next `batch` (at most 50) steps of a tier's
+    /// lottery once registration has closed.  Call repeatedly until
+    /// `get_lottery` reports `settled`.
+    ///
+    /// Winners are picked first.  The first batch fixes their number at the
+    /// tickets left in the tier and its sessions (or every entrant, if
+    /// fewer).  Each pick is a step of a Fisher-Yates shuffle with the
+    /// Soroban PRNG, seeded when the batch runs, i.e. after the entrant list
+    /// is frozen: a random entrant from the unpicked tail is swapped to the
+    /// front and wins a ticket, and their deposit becomes sale proceeds.
+    /// The draw is admin-only so nobody can retry a batch until it picks
+    /// them.
+    ///
+    /// Once every winner is picked, the remaining steps refund losers their
+    /// deposit automatically, in entry order.  Winners must be picked by
+    /// `draw_deadline`; refunds may run later.  If the lottery is cancelled
+    /// or the deadline passes first, entrants reclaim their deposits with
+    /// `claim_lottery_refund` instead.
+    pub fn draw_lottery(e: &Env, tier_symbol: Symbol, batch: u32) -> Result<(), LotteryError> {
+        Self::require_admin(e);
+
+        if batch == 0 || batch > MAX_DRAW_BATCH {
+            return Err(LotteryError::InvalidDrawBatch);
+        }
+        let lottery_key = DataKey::Lottery(tier_symbol.clone());
+        let mut lottery = Self::get_lottery(e, tier_symbol.clone())?;
+        if lottery.cancelled {
+            return Err(LotteryError::LotteryCancelled);
+        }
+        if lottery.settled {
+            return Err(LotteryError::AlreadyDrawn);
+        }
+        let now = e.ledger().timestamp();
+        if now <= lottery.registration_end {
+            return Err(LotteryError::RegistrationOpen);
+        }
+        if !lottery.drawn && now > lottery.draw_deadline {
+            return Err(LotteryError::DrawDeadlinePassed);
+        }
+
+        let mut steps = batch;
+        if !lottery.drawn {
+            steps -= Self::pick_lottery_winners(e, &tier_symbol, &mut lottery, batch);
+        }
+        if lottery.drawn {
+            Self::refund_lottery_losers(e, &tier_symbol, &mut lottery, steps);
+        }
+        storage::write(e, &lottery_key, &lottery);
+        Ok(())
+    }
+
+    /// Pick up to `batch` winners and mint their tickets; returns how many
+    /// were picked.  The winner count shrinks if admin mints or other tiers
+    /// sharing a session use up seats between batches.
+    fn pick_lottery_winners(
+        e: &Env,
+        tier_symbol: &Symbol,
+        lottery: &mut Lottery,
+        batch: u32,
+    ) -> u32 {
+        let tier_key = DataKey::Tier(tier_symbol.clone());
+        let mut tier: Tier = storage::read(e, &tier_key).unwrap();
+        let seats_left = tier
+            .max_supply
+            .saturating_sub(tier.minted)
+            .min(Self::session_seats_left(e, tier_symbol));
+        if lottery.picked == 0 {
+            lottery.winners = seats_left.min(lottery.entrants);
+        }
+
+        let first = lottery.picked;
+        let last = lottery.winners.min(first + batch).min(first + seats_left);
+        let mut token_ids = Vec::new(e);
+        for index in first..last {
+            let pick = e
+                .prng()
+                .gen_range::<u64>(u64::from(index)..u64::from(lottery.entrants))
+                as u32;
+            let winner = Self::swap_lottery_entrants(e, tier_symbol, index, pick);
+            token_ids.push_back(Self::mint_ticket(
+                e,
+                &winner,
+                &winner,
+                tier_symbol,
+                lottery.price,
+                Some(lottery.payment_token.clone()),
+                None,
+            ));
+        }
+        let picked = last - first;
+
+        // Winning deposits become sale proceeds
+        Self::record_sale(e, &lottery.payment_token, lottery.price * picked as i128);
+
+        tier.minted += picked;
+        tier.current_price = lottery.price;
+        storage::write(e, &tier_key, &tier);
+
+        lottery.picked = last;
+        if lottery.picked >= lottery.winners || picked == seats_left {
+            lottery.winners = lottery.picked;
+            lottery.drawn = true;
+            lottery.refunded_until = lottery.picked;
+        }
+
+        LotteryDrawn {
+            tier: tier_symbol.clone(),
+            token_ids,
+            price: lottery.price,
+            entrants: lottery.entrants,
+        }
+        .publish(e);
+        picked
+    }
+
+    /// Pay back up to `batch` losers, skipping any who already claimed
+    /// their deposit.
+    fn refund_lottery_losers(e: &Env, tier_symbol: &Symbol, lottery: &mut Lottery, batch: u32) {
+        let first = lottery.refunded_until;
+        let last = lottery.entrants.min(first + batch);
+        for index in first..last {
+            let entrant: Address =
+                storage::read(e, &DataKey::LotteryEntrant(tier_symbol.clone(), index)).unwrap();
+            Self::refund_lottery_entry(e, tier_symbol, lottery, &entrant);
+        }
+        lottery.refunded_until = last;
+        lottery.settled = last >= lottery.entrants;
+    }
+
+    /// Swap the entrants at `index` and `pick`, keeping their entries in
+    /// step, and return the entrant now at `index`.
+    fn swap_lottery_entrants(e: &Env, tier_symbol: &Symbol, index: u32, pick: u32) -> Address {
+        let index_key = DataKey::LotteryEntrant(tier_symbol.clone(), index);
+        let picked: Address =
+            storage::read(e, &DataKey::LotteryEntrant(tier_symbol.clone(), pick)).unwrap();
+        if pick == index {
+            return picked;
+        }
+        let displaced: Address = storage::read(e, &index_key).unwrap();
+        storage::write(
+            e,
+            &DataKey::LotteryEntrant(tier_symbol.clone(), pick),
+            &displaced,
+        );
+        storage::write(e, &index_key, &picked);
+        for (entrant, index) in [(&displaced, pick), (&picked, index)] {
+            storage::write(
+                e,
+                &DataKey::LotteryEntry(tier_symbol.clone(), entrant.clone()),
+                &LotteryEntry {
+                    index,
+                    refunded: false,
+                },
+            );
+        }
+        picked
+    }
+
+    /// Admin-only: cancel a lottery that has not finished drawing.  Winners
+    /// already picked keep their tickets; everyone else can reclaim their
+    /// deposit with `claim_lottery_refund`, and the tier's remaining supply
+    /// goes on regular sale.
+    pub fn cancel_lottery(e: &Env, tier_symbol: Symbol) -> Result<(), LotteryError> {
+        Self::require_admin(e);
+
+        let mut lottery = Self::get_lottery(e, tier_symbol.clone())?;
+        if lottery.cancelled {
+            return Err(LotteryError::LotteryCancelled);
+        }
+        if lottery.drawn {
+            return Err(LotteryError::AlreadyDrawn);
+        }
+        lottery.cancelled = true;
+        storage::write(e, &DataKey::Lottery(tier_symbol.clone()), &lottery);
+
+        LotteryCancelled { tier: tier_symbol }.publish(e);
+        Ok(())
+    }
+
+    /// Reclaim `entrant`'s deposit when the draw will not refund it: the
+    /// lottery was cancelled or `draw_deadline` passed before every winner
+    /// was picked.  Losers of a completed draw may also claim rather than
+    /// wait for the draw to reach them.  Returns the amount refunded.
+    pub fn claim_lottery_refund(
+        e: &Env,
+        entrant: Address,
+        tier_symbol: Symbol,
+    ) -> Result<i128, LotteryError> {
+        storage::extend_instance(e);
+        entrant.require_auth();
+
+        let lottery = Self::get_lottery(e, tier_symbol.clone())?;
+        let entry: LotteryEntry = storage::read(
+            e,
+            &DataKey::LotteryEntry(tier_symbol.clone(), entrant.clone()),
+        )
+        .ok_or(LotteryError::NotEntered)?;
+        if entry.refunded {
+            return Err(LotteryError::AlreadyRefunded);
+        }
+        let settled =
+            lottery.drawn || lottery.cancelled || e.ledger().timestamp() > lottery.draw_deadline;
+        if entry.index < lottery.picked || !settled {
+            return Err(LotteryError::RefundUnavailable);
+        }
+
+        Self::refund_lottery_entry(e, &tier_symbol, &lottery, &entrant);
+        Ok(lottery.price)
+    }
+
+    /// Return `entrant`'s deposit unless it was already returned.
+    fn refund_lottery_entry(e: &Env, tier_symbol: &Symbol, lottery: &Lottery, entrant: &Address) {
+        let entry_key = DataKey::LotteryEntry(tier_symbol.clone(), entrant.clone());
+        let mut entry: LotteryEntry = storage::read(e, &entry_key).unwrap();
+        if entry.refunded {
+            return;
+        }
+        entry.refunded = true;
+        storage::write(e, &entry_key, &entry);
+        token::Client::new(e, &lottery.payment_token).transfer(
+            &e.current_contract_address(),
+            entrant,
+            &lottery.price,
+        );
+
+        LotteryRefunded {
+            entrant: entrant.clone(),
+            tier: tier_symbol.clone(),
+            amount: lottery.price,
+        }
+        .publish(e);
+    }
+
+    pub fn get_lottery(e: &Env, tier_symbol: Symbol) -> Result<Lottery, LotteryError> {
+        storage::read(e, &DataKey::Lottery(tier_symbol)).ok_or(LotteryError::LotteryNotFound)
+    }
+
+    /// Lottery entrants, `limit` (at most 100) from `start`.  Order follows
+    /// entry, except that each draw batch swaps its winners to the front.
+    pub fn get_lottery_entrants(
+        e: &Env,
+        tier_symbol: Symbol,
+        start: u32,
+        limit: u32,
+    ) -> Vec<Address> {
+        let count = Self::get_lottery(e, tier_symbol.clone()).map_or(0, |l| l.entrants);
+        let end = count.min(start.saturating_add(limit.min(MAX_PAGE_SIZE)));
+        let mut page = Vec::new(e);
+        for index in start..end {
+            let entrant: Address =
+                storage::read(e, &DataKey::LotteryEntrant(tier_symbol.clone(), index)).unwrap();
+            page.push_back(entrant);
+        }
+        page
+    }
+
+    pub fn get_lottery_entry(
+        e: &Env,
+        tier_symbol: Symbol,
+        entrant: Address,
+    ) -> Option<LotteryEntry> {
+        storage::read(e, &DataKey::LotteryEntry(tier_symbol, entrant))
+    }
+
+    /// Refund and burn a ticket.  Either the holder or the payer may ask;
+    /// the price paid always goes back to the payer.
+    pub fn refund(
+        e: &Env,
+        caller: Address,
+        payment_token: Address,
+        token_id: u32,
+    ) -> Result<(), Error> {
+        storage::extend_instance(e);
+        caller.require_auth();
+
+        let mut ticket = Self::get_ticket(e, token_id)?;
+        if !ticket.is_valid {
+            return Err(Error::TicketInvalidated);
+        }
+        let owner = Self::owner_of(e, token_id);
+        if caller != owner && caller != ticket.payer {
+            return Err(Error::NotTicketOwner);
+        }
+
+        let event_info: EventInfo = e.storage().instance().get(&DataKey::EventInfo).unwrap();
+        if e.ledger().timestamp() > event_info.refund_cutoff_time {
+            return Err(Error::RefundWindowClosed);
+        }
+
+        // Process refund
+        Self::check_payment_token(&ticket, &payment_token)?;
+        let token_client = token::Client::new(e, &payment_token);
+        token_client.transfer(
+            &e.current_contract_address(),
+            &ticket.payer,
+            &ticket.price_paid,
+        );
+        Self::record_refund(e, &payment_token, ticket.price_paid);
+
+        // Invalidate and Burn
+        ticket.is_valid = false;
+        storage::write(e, &DataKey::Ticket(token_id), &ticket);
+        // The caller already authorized this call; `Base::burn` would ask
+        // the owner again.
+        Base::update(e, Some(&owner), None, token_id);
+        Enumerable::remove_from_enumerations(e, &owner, token_id);
+        Self::remove_from_tier_index(e, &ticket.tier_symbol, token_id);
+        Self::release_sessions(e, &ticket.tier_symbol);
+        Self::refund_addons(e, token_id);
+        burnable::emit_burn(e, &owner, token_id);
+
+        TicketRefunded {
+            owner,
+            tier: ticket.tier_symbol,
+            token_id,
+            amount: ticket.price_paid,
+            payment_token,
+        }
+        .publish(e);
+        Ok(())
+    }
+
+    /// Final uniform price of a Dutch-auction tier, once it has settled.
+    ///
+    /// An auction settles when the tier sells out or `end_time` has passed.
+    /// Returns `None` while the auction is still running, when nothing was
+    /// sold, or when the tier is not a uniform-clearing auction.
+    pub fn get_clearing_price(e: &Env, tier_symbol: Symbol) -> Result<Option<i128>, Error> {
+        let tier = Self::read_tier(e, &tier_symbol)?;
+
+        let auction = match tier.strategy {
+            PricingStrategy::DutchAuction(auction) if auction.uniform_clearing => auction,
+            _ => return Ok(None),
+        };
+        let settled = tier.minted >= tier.max_supply || e.ledger().timestamp() > auction.end_time;
+        if !settled {
+            return Ok(None);
+        }
+
+        Ok(storage::read(
+            e,
+            &DataKey::AuctionClearingPrice(tier_symbol),
+        ))
+    }
+
+    /// Pay `ticket.payer` the difference between the price paid and the
+    /// clearing price of a uniform-clearing auction.  Either the holder or
+    /// the payer may claim, until the refund cutoff.  The ticket's
+    /// `price_paid` is lowered to the clearing price, so a rebate can be
+    /// claimed only once and a later `refund` returns the net amount.
+    pub fn claim_auction_rebate(
+        e: &Env,
+        caller: Address,
+        payment_token: Address,
+        token_id: u32,
+    ) -> Result<(), Error> {
+        storage::extend_instance(e);
+        caller.require_auth();
+
+        let mut ticket = Self::get_ticket(e, token_id)?;
+        let owner = Self::owner_of(e, token_id);
+        if caller != owner && caller != ticket.payer {
+            return Err(Error::NotTicketOwner);
+        }
+        let clearing = Self::get_clearing_price(e, ticket.tier_symbol.clone())?
+            .ok_or(Error::AuctionNotSettled)?;
+        // Withdrawals open after the cutoff and assume no more money goes out
+        if e.ledger().timestamp() > Self::get_event_info(e).refund_cutoff_time {
+            return Err(Error::RefundWindowClosed);
+        }
+
+        let rebate = ticket.price_paid - clearing;
+        if rebate <= 0 {
+            return Err(Error::NoRebateDue);
+        }
+
+        Self::check_payment_token(&ticket, &payment_token)?;
+        let token_client = token::Client::new(e, &payment_token);
+        token_client.transfer(&e.current_contract_address(), &ticket.payer, &rebate);
+        Self::record_refund(e, &payment_token, rebate);
+
+        ticket.price_paid = clearing;
+        storage::write(e, &DataKey::Ticket(token_id), &ticket);
+
+        RebateClaimed {
+            owner,
+            tier: ticket.tier_symbol,
+            token_id,
+            amount: rebate,
+            payment_token,
+        }
+        .publish(e);
+        Ok(())
+    }
+
+    // Ticket Validation
+    pub fn validate_ticket(e: &Env, token_id: u32) -> bool {
+        storage::extend_instance(e);
+        storage::read::<Ticket>(e, &DataKey::Ticket(token_id))
+            .map(|ticket| ticket.is_valid)
+            .unwrap_or(false)
+    }
+
+    // Check-in and metadata
+
+    /// Admin-only: record when the event ends.  Tickets checked in by then
+    /// read as attended afterwards, and check-in closes.
+    pub fn set_event_end_time(e: &Env, end_time: u64) -> Result<(), TicketError> {
+        Self::require_admin(e);
+
+        let mut event_info: EventInfo = e.storage().instance().get(&DataKey::EventInfo).unwrap();
+        if end_time < event_info.start_time {
+            return Err(TicketError::InvalidEventEndTime);
+        }
+        event_info.end_time = end_time;
+        e.storage().instance().set(&DataKey::EventInfo, &event_info);
+        Ok(())
+    }
+
+    pub fn get_event_info(e: &Env) -> EventInfo {
+        e.storage().instance().get(&DataKey::EventInfo).unwrap()
+    }
+
+    /// Admin-only: admit the holder of `token_id` at the door.
+    pub fn check_in(e: &Env, token_id: u32) -> Result<(), TicketError> {
+        Self::require_admin(e);
+
+        let mut ticket = Self::require_live_ticket(e, token_id);
+        if ticket.checked_in_at.is_some() {
+            return Err(TicketError::AlreadyCheckedIn);
+        }
+        let now = e.ledger().timestamp();
+        let event_info = Self::get_event_info(e);
+        if event_info.end_time != 0 && now > event_info.end_time {
+            return Err(TicketError::CheckInClosed);
+        }
+
+        ticket.checked_in_at = Some(now);
+        storage::write(e, &DataKey::Ticket(token_id), &ticket);
+        TicketCheckedIn {
+            owner: Base::owner_of(e, token_id),
+            tier: ticket.tier_symbol,
+            token_id,
+            timestamp: now,
+        }
+        .publish(e);
+        Ok(())
+    }
+
+    /// Payer-only, once per ticket and before the event starts: move the
+    /// ticket to the person who will attend.  When an identity registry is
+    /// configured the attendee must own a DID.  Refunds still go to the
+    /// payer.
+    pub fn assign_attendee(e: &Env, token_id: u32, attendee: Address) -> Result<(), TicketError> {
+        storage::extend_instance(e);
+        let mut ticket = Self::require_live_ticket(e, token_id);
+        ticket.payer.require_auth();
+
+        if ticket.attendee_assigned {
+            return Err(TicketError::AttendeeAlreadyAssigned);
+        }
+        if ticket.checked_in_at.is_some() {
+            return Err(TicketError::AlreadyCheckedIn);
+        }
+        if e.ledger().timestamp() >= Self::get_event_info(e).start_time {
+            return Err(TicketError::EventStarted);
+        }
+        let registry: Option<Address> = e.storage().instance().get(&DataKey::IdentityRegistry);
+        if let Some(registry) = registry {
+            IdentityRegistryClient::new(e, &registry)
+                .get_did_by_address(&attendee)
+                .unwrap_or_else(|| panic_with_error!(e, Error::DidRequired));
+        }
+
+        // Soulbound tickets cannot be transferred by holders; move the token
+        // directly and keep the owner enumeration in step.
+        let previous = Base::owner_of(e, token_id);
+        if previous != attendee {
+            Base::update(e, Some(&previous), Some(&attendee), token_id);
+            Enumerable::remove_from_owner_enumeration(e, &previous, token_id);
+            Enumerable::add_to_owner_enumeration(e, &attendee, token_id);
+            emit_transfer(e, &previous, &attendee, token_id);
+        }
+        ticket.attendee_assigned = true;
+        storage::write(e, &DataKey::Ticket(token_id), &ticket);
+        storage::extend_ticket(e, token_id);
+
+        AttendeeAssigned {
+            attendee,
+            tier: ticket.tier_symbol,
+            token_id,
+            previous,
+        }
+        .publish(e);
+        Ok(())
+    }
+
+    pub fn get_ticket_status(e: &Env, token_id: u32) -> Result<TicketStatus, Error> {
+        let ticket = Self::get_ticket(e, token_id)?;
+        Ok(Self::ticket_status(e, &ticket))
+    }
+
+    fn ticket_status(e: &Env, ticket: &Ticket) -> TicketStatus {
+        if !ticket.is_valid {
+            return TicketStatus::Refunded;
+        }
+        if ticket.checked_in_at.is_none() {
+            return TicketStatus::Valid;
+        }
+        let end_time = Self::get_event_info(e).end_time;
+        if end_time != 0 && e.ledger().timestamp() > end_time {
+            TicketStatus::Attended
+        } else {
+            TicketStatus::CheckedIn
+        }
+    }
+
+    /// Admin-only: set the `token_uri` template of a tier, e.g.
+    /// `https://cdn.example/vip/{status}/{id}.json`.  `{id}` and `{status}`
+    /// are filled per ticket; `None` falls back to the collection URI.
+    pub fn set_tier_uri(
+        e: &Env,
+        tier_symbol: Symbol,
+        template: Option<String>,
+    ) -> Result<(), TicketError> {
+        Self::require_admin(e);
+
+        Self::require_tier(e, &tier_symbol);
+        let key = DataKey::TierUri(tier_symbol);
+        match template {
+            Some(template) if template.len() > MAX_URI_TEMPLATE_LEN => {
+                return Err(TicketError::UriTemplateTooLong);
+            }
+            Some(template) => storage::write(e, &key, &template),
+            None => e.storage().persistent().remove(&key),
+        }
+        Ok(())
+    }
+
+    pub fn get_tier_uri(e: &Env, tier_symbol: Symbol) -> Option<String> {
+        storage::read(e, &DataKey::TierUri(tier_symbol))
+    }
+
+    /// URI of a ticket from its record, so refunded tickets still resolve.
+    fn ticket_uri(e: &Env, token_id: u32, ticket: &Ticket) -> String {
+        let status = Self::ticket_status(e, ticket);
+        match Self::get_tier_uri(e, ticket.tier_symbol.clone()) {
+            Some(template) => metadata::render_uri(e, &template, token_id, &status),
+            None => Base::compose_uri_for_token(e, Base::base_uri(e), token_id),
+        }
+    }
+
+    /// JSON metadata of a ticket: collection name, tier, status, price
+    /// paid, purchase and event times, and `image` set to its `token_uri`.
+    pub fn ticket_metadata(e: &Env, token_id: u32) -> Result<String, Error> {
+        let ticket = Self::get_ticket(e, token_id)?;
+        let tier = Self::read_tier(e, &ticket.tier_symbol)?;
+        let event_info = Self::get_event_info(e);
+        let status = Self::ticket_status(e, &ticket);
+
+        let mut json = Bytes::from_slice(e, b"{\"name\":");
+        push_json_string(&mut json, &Base::name(e));
+        json.extend_from_slice(b",\"token_id\":");
+        push_u128(&mut json, token_id as u128);
+        json.extend_from_slice(b",\"tier\":");
+        push_json_string(&mut json, &tier.name);
+        json.extend_from_slice(b",\"status\":\"");
+        json.extend_from_slice(metadata::status_slug(&status).as_bytes());
+        json.extend_from_slice(b"\",\"price_paid\":");
+        push_i128(&mut json, ticket.price_paid);
+        json.extend_from_slice(b",\"purchase_time\":");
+        push_u128(&mut json, ticket.purchase_time as u128);
+        json.extend_from_slice(b",\"event_start\":");
+        push_u128(&mut json, event_info.start_time as u128);
+        json.extend_from_slice(b",\"event_end\":");
+        push_u128(&mut json, event_info.end_time as u128);
+        json.extend_from_slice(b",\"image\":");
+        push_json_string(&mut json, &Self::ticket_uri(e, token_id, &ticket));
+        json.push_back(b'}');
+        Ok(json.to_string())
+    }
+
+    // Attendance badges
+
+    /// Holder-only, once per ticket after the event has ended: claim a
+    /// non-transferable attendance badge for a checked-in ticket.  When an
+    /// identity registry is configured and the holder owns a DID, the
+    /// attendance is also reported to it for the DID's reputation; a
+    /// registry that declines (event not approved, DID already counted)
+    /// does not block the claim.
+    pub fn claim_badge(e: &Env, token_id: u32) -> Result<AttendanceBadge, BadgeError> {
+        storage::extend_instance(e);
+        let ticket = Self::require_live_ticket(e, token_id);
+        let holder = Base::owner_of(e, token_id);
+        holder.require_auth();
+
+        let checked_in_at = ticket
+            .checked_in_at
+            .unwrap_or_else(|| panic_with_error!(e, TicketError::NotCheckedIn));
+        if Self::ticket_status(e, &ticket) != TicketStatus::Attended {
+            return Err(BadgeError::EventNotEnded);
+        }
+        if e.storage().persistent().has(&DataKey::Badge(token_id)) {
+            return Err(BadgeError::AlreadyClaimed);
+        }
+
+        let event_info = Self::get_event_info(e);
+        let badge = AttendanceBadge {
+            holder: holder.clone(),
+            token_id,
+            tier: ticket.tier_symbol.clone(),
+            event_name: Base::name(e),
+            event_start: event_info.start_time,
+            event_end: event_info.end_time,
+            checked_in_at,
+            claimed_at: e.ledger().timestamp(),
+        };
+        storage::write(e, &DataKey::Badge(token_id), &badge);
+        let mut badges = Self::get_badges(e, holder.clone());
+        badges.push_back(token_id);
+        storage::write(e, &DataKey::HolderBadges(holder.clone()), &badges);
+
+        let registry: Option<Address> = e.storage().instance().get(&DataKey::IdentityRegistry);
+        let reputation_recorded = registry.is_some_and(|registry| {
+            let registry = IdentityRegistryClient::new(e, &registry);
+            registry.get_did_by_address(&holder).is_some_and(|did| {
+                matches!(
+                    registry.try_record_attendance(&e.current_contract_address(), &did),
+                    Ok(Ok(()))
+                )
+            })
+        });
+
+        BadgeClaimed {
+            holder,
+            tier: ticket.tier_symbol,
+            token_id,
+            reputation_recorded,
+        }
+        .publish(e);
+        Ok(badge)
+    }
+
+    pub fn get_badge(e: &Env, token_id: u32) -> Option<AttendanceBadge> {
+        storage::read(e, &DataKey::Badge(token_id))
+    }
+
+    /// Token ids of the tickets `holder` claimed badges for.
+    pub fn get_badges(e: &Env, holder: Address) -> Vec<u32> {
+        storage::read(e, &DataKey::HolderBadges(holder)).unwrap_or(Vec::new(e))
+    }
+
+    // Sessions
+
+    /// Admin-only: add a day or workshop session.  Tiers grant access to
+    /// sessions through `set_tier_sessions`.
+    pub fn add_session(
+        e: &Env,
+        session_id: Symbol,
+        start_time: u64,
+        end_time: u64,
+        capacity: u32,
+    ) -> Result<(), SessionError> {
+        Self::require_admin(e);
+
+        let key = DataKey::Session(session_id.clone());
+        if e.storage().persistent().has(&key) {
+            return Err(SessionError::SessionExists);
+        }
+        if end_time <= start_time {
+            return Err(SessionError::InvalidSessionWindow);
+        }
+        let session = Session {
+            start_time,
+            end_time,
+            capacity,
+            sold: 0,
+            checked_in: 0,
+        };
+        storage::write(e, &key, &session);
+
+        let mut index = Self::list_sessions(e);
+        index.push_back(session_id.clone());
+        storage::write(e, &DataKey::SessionIndex, &index);
+
+        SessionAdded {
+            session_id,
+            session,
+        }
+        .publish(e);
+        Ok(())
+    }
+
+    /// Admin-only: change a session's capacity; it cannot drop below the
+    /// tickets already sold for it.
+    pub fn set_session_capacity(
+        e: &Env,
+        session_id: Symbol,
+        capacity: u32,
+    ) -> Result<(), SessionError> {
+        Self::require_admin(e);
+
+        let mut session = Self::get_session(e, session_id.clone())?;
+        if capacity != 0 && capacity < session.sold {
+            return Err(SessionError::CapacityBelowSold);
+        }
+        session.capacity = capacity;
+        storage::write(e, &DataKey::Session(session_id.clone()), &session);
+        SessionUpdated {
+            session_id,
+            session,
+        }
+        .publish(e);
+        Ok(())
+    }
+
+    pub fn get_session(e: &Env, session_id: Symbol) -> Result<Session, SessionError> {
+        storage::read(e, &DataKey::Session(session_id)).ok_or(SessionError::SessionNotFound)
+    }
+
+    /// Ids of every session, in the order they were added.
+    pub fn list_sessions(e: &Env) -> Vec<Symbol> {
+        storage::read(e, &DataKey::SessionIndex).unwrap_or(Vec::new(e))
+    }
+
+    /// Admin-only: set the sessions a tier grants, e.g. one day for a day
+    /// pass or every session for a full pass.  Fixed once the tier has sold
+    /// tickets, so refunds release the seats that were taken.
+    pub fn set_tier_sessions(
+        e: &Env,
+        tier_symbol: Symbol,
+        sessions: Vec<Symbol>,
+    ) -> Result<(), SessionError> {
+        Self::require_admin(e);
+
+        let tier = Self::require_tier(e, &tier_symbol);
+        if tier.minted > 0 {
+            return Err(SessionError::TierHasTickets);
+        }
+        for session_id in sessions.iter() {
+            Self::get_session(e, session_id)?;
+        }
+        storage::write(e, &DataKey::TierSessions(tier_symbol.clone()), &sessions);
+        TierSessionsSet {
+            tier_symbol,
+            sessions,
+        }
+        .publish(e);
+        Ok(())
+    }
+
+    pub fn get_tier_sessions(e: &Env, tier_symbol: Symbol) -> Vec<Symbol> {
+        storage::read(e, &DataKey::TierSessions(tier_symbol)).unwrap_or(Vec::new(e))
+    }
+
+    /// Admin-only: admit the holder of `token_id` to one session.  Open
+    /// until the session ends; each ticket enters each session once.
+    pub fn check_in_session(
+        e: &Env,
+        token_id: u32,
+        session_id: Symbol,
+    ) -> Result<(), SessionError> {
+        Self::require_admin(e);
+
+        let ticket = Self::require_live_ticket(e, token_id);
+        let mut session = Self::get_session(e, session_id.clone())?;
+        if !Self::get_tier_sessions(e, ticket.tier_symbol).contains(&session_id) {
+            return Err(SessionError::SessionNotGranted);
+        }
+        let check_in_key = DataKey::SessionCheckIn(session_id.clone(), token_id);
+        if e.storage().persistent().has(&check_in_key) {
+            return Err(SessionError::AlreadyAdmitted);
+        }
+        let now = e.ledger().timestamp();
+        if now > session.end_time {
+            return Err(SessionError::SessionEnded);
+        }
+
+        storage::write(e, &check_in_key, &now);
+        session.checked_in += 1;
+        storage::write(e, &DataKey::Session(session_id.clone()), &session);
+        SessionCheckedIn {
+            owner: Base::owner_of(e, token_id),
+            session: session_id,
+            token_id,
+            timestamp: now,
+        }
+        .publish(e);
+        Ok(())
+    }
+
+    /// When `token_id` was admitted to `session_id`, if it was.
+    pub fn get_session_check_in(e: &Env, token_id: u32, session_id: Symbol) -> Option<u64> {
+        storage::read(e, &DataKey::SessionCheckIn(session_id, token_id))
+    }
+
+    /// Tickets of `tier_symbol` its sessions still have seats for;
+    /// `u32::MAX` when none of them is capped.
+    fn session_seats_left(e: &Env, tier_symbol: &Symbol) -> u32 {
+        let mut seats = u32::MAX;
+        for session_id in Self::get_tier_sessions(e, tier_symbol.clone()).iter() {
+            let session: Session = storage::read(e, &DataKey::Session(session_id)).unwrap();
+            if session.capacity != 0 {
+                seats = seats.min(session.capacity.saturating_sub(session.sold));
+            }
+        }
+        seats
+    }
+
+    /// Take a seat in every session the tier grants.  Callers check
+    /// `session_seats_left` first.
+    fn reserve_sessions(e: &Env, tier_symbol: &Symbol) {
+        for session_id in Self::get_tier_sessions(e, tier_symbol.clone()).iter() {
+            let key = DataKey::Session(session_id);
+            let mut session: Session = storage::read(e, &key).unwrap();
+            session.sold += 1;
+            storage::write(e, &key, &session);
+        }
+    }
+
+    fn release_sessions(e: &Env, tier_symbol: &Symbol) {
+        for session_id in Self::get_tier_sessions(e, tier_symbol.clone()).iter() {
+            let key = DataKey::Session(session_id);
+            let mut session: Session = storage::read(e, &key).unwrap();
+            session.sold -= 1;
+            storage::write(e, &key, &session);
+        }
+    }
+
+    // Add-ons
+
+    /// Admin-only: add a product sold with tickets.  `stock` 0 means
+    /// unlimited; with `required_tier` only that tier's tickets may buy it.
+    pub fn add_addon(
+        e: &Env,
+        addon_id: Symbol,
+        name: String,
+        payment_token: Address,
+        price: i128,
+        stock: u32,
+        required_tier: Option<Symbol>,
+    ) -> Result<(), AddOnError> {
+        Self::require_admin(e);
+
+        let key = DataKey::AddOn(addon_id.clone());
+        if e.storage().persistent().has(&key) {
+            return Err(AddOnError::AddOnExists);
+        }
+        if price < 0 {
+            return Err(AddOnError::NegativePrice);
+        }
+        if let Some(tier_symbol) = &required_tier {
+            Self::require_tier(e, tier_symbol);
+        }
+        let addon = AddOn {
+            name,
+            payment_token,
+            price,
+            stock,
+            sold: 0,
+            required_tier,
+            active: true,
+        };
+        storage::write(e, &key, &addon);
+
+        let mut index = Self::list_addons(e);
+        index.push_back(addon_id.clone());
+        storage::write(e, &DataKey::AddOnIndex, &index);
+
+        AddOnAdded { addon_id, addon }.publish(e);
+        Ok(())
+    }
+
+    /// Admin-only: change how many units may be sold; 0 means unlimited.
+    pub fn set_addon_stock(e: &Env, addon_id: Symbol, stock: u32) -> Result<(), AddOnError> {
+        Self::require_admin(e);
+
+        let mut addon = Self::get_addon(e, addon_id.clone())?;
+        if stock != 0 && stock < addon.sold {
+            return Err(AddOnError::StockBelowSold);
+        }
+        addon.stock = stock;
+        Self::write_addon_update(e, addon_id, addon);
+        Ok(())
+    }
+
+    pub fn set_addon_active(e: &Env, addon_id: Symbol, active: bool) -> Result<(), AddOnError> {
+        Self::require_admin(e);
+
+        let mut addon = Self::get_addon(e, addon_id.clone())?;
+        addon.active = active;
+        Self::write_addon_update(e, addon_id, addon);
+        Ok(())
+    }
+
+    fn write_addon_update(e: &Env, addon_id: Symbol, addon: AddOn) {
+        storage::write(e, &DataKey::AddOn(addon_id.clone()), &addon);
+        AddOnUpdated { addon_id, addon }.publish(e);
+    }
+
+    pub fn get_addon(e: &Env, addon_id: Symbol) -> Result<AddOn, AddOnError> {
+        storage::read(e, &DataKey::AddOn(addon_id)).ok_or(AddOnError::AddOnNotFound)
+    }
+
+    /// Ids of every add-on, in the order they were added.
+    pub fn list_addons(e: &Env) -> Vec<Symbol> {
+        storage::read(e, &DataKey::AddOnIndex).unwrap_or(Vec::new(e))
+    }
+
+    /// Buy `quantity` units of an add-on for an existing ticket.  The
+    /// holder or the payer of the ticket may buy; units are refunded to
+    /// `buyer` if the ticket is refunded before they are redeemed.
+    pub fn purchase_addon(
+        e: &Env,
+        buyer: Address,
+        token_id: u32,
+        addon_id: Symbol,
+        quantity: u32,
+    ) -> Result<(), AddOnError> {
+        storage::extend_instance(e);
+        buyer.require_auth();
+        Self::buy_addon(e, &buyer, token_id, addon_id, quantity)
+    }
+
+    /// `purchase` a ticket and the given add-on quantities in one call.  An
+    /// add-on that cannot be bought aborts the whole call with its
+    /// `AddOnError` code.
+    pub fn purchase_with_addons(
+        e: &Env,
+        buyer: Address,
+        payment_token: Address,
+        tier_symbol: Symbol,
+        promo_code: Option<String>,
+        max_amount: Option<i128>,
+        addons: Map<Symbol, u32>,
+    ) -> Result<u32, Error> {
+        let token_id = Self::purchase(
+            e,
+            buyer.clone(),
+            payment_token,
+            tier_symbol,
+            promo_code,
+            max_amount,
+        )?;
+        for (addon_id, quantity) in addons.iter() {
+            if let Err(err) = Self::buy_addon(e, &buyer, token_id, addon_id, quantity) {
+                panic_with_error!(e, err);
+            }
+        }
+        Ok(token_id)
+    }
+
+    fn buy_addon(
+        e: &Env,
+        buyer: &Address,
+        token_id: u32,
+        addon_id: Symbol,
+        quantity: u32,
+    ) -> Result<(), AddOnError> {
+        let ticket = Self::require_live_ticket(e, token_id);
+        if *buyer != Base::owner_of(e, token_id) && *buyer != ticket.payer {
+            return Err(AddOnError::NotTicketHolder);
+        }
+        let mut addon = Self::get_addon(e, addon_id.clone())?;
+        if !addon.active {
+            return Err(AddOnError::AddOnInactive);
+        }
+        if quantity == 0 {
+            return Err(AddOnError::InvalidQuantity);
+        }
+        if let Some(required) = &addon.required_tier {
+            if *required != ticket.tier_symbol {
+                return Err(AddOnError::TierNotEligible);
+            }
+        }
+        if addon.stock != 0 && addon.sold + quantity > addon.stock {
+            return Err(AddOnError::OutOfStock);
+        }
+
+        let line_key = DataKey::TicketAddOn(token_id, addon_id.clone());
+        let mut line = match storage::read::<TicketAddOn>(e, &line_key) {
+            Some(line) if line.buyer != *buyer => return Err(AddOnError::NotAddOnBuyer),
+            Some(line) => line,
+            None => {
+                let mut addons = Self::get_ticket_addons(e, token_id);
+                addons.push_back(addon_id.clone());
+                storage::write(e, &DataKey::TicketAddOns(token_id), &addons);
+                TicketAddOn {
+                    buyer: buyer.clone(),
+                    quantity: 0,
+                    redeemed: 0,
+                    amount_paid: 0,
+                }
+            }
+        };
+        let amount = addon.price * quantity as i128;
+        line.quantity += quantity;
+        line.amount_paid += amount;
+        storage::write(e, &line_key, &line);
+
+        addon.sold += quantity;
+        storage::write(e, &DataKey::AddOn(addon_id.clone()), &addon);
+
+        token::Client::new(e, &addon.payment_token).transfer(
+            buyer,
+            e.current_contract_address(),
+            &amount,
+        );
+        Self::record_sale(e, &addon.payment_token, amount);
+
+        AddOnPurchased {
+            buyer: buyer.clone(),
+            addon_id,
+            token_id,
+            quantity,
+            amount,
+        }
+        .publish(e);
+        Ok(())
+    }
+
+    /// Ids of the add-ons attached to a ticket.
+    pub fn get_ticket_addons(e: &Env, token_id: u32) -> Vec<Symbol> {
+        storage::read(e, &DataKey::TicketAddOns(token_id)).unwrap_or(Vec::new(e))
+    }
+
+    pub fn get_ticket_addon(e: &Env, token_id: u32, addon_id: Symbol) -> Option<TicketAddOn> {
+        storage::read(e, &DataKey::TicketAddOn(token_id, addon_id))
+    }
+
+    /// Admin-only: hand out `quantity` units to a checked-in holder.
+    pub fn redeem_addon(
+        e: &Env,
+        token_id: u32,
+        addon_id: Symbol,
+        quantity: u32,
+    ) -> Result<(), AddOnError> {
+        Self::require_admin(e);
+
+        let ticket = Self::require_live_ticket(e, token_id);
+        if ticket.checked_in_at.is_none() {
+            panic_with_error!(e, TicketError::NotCheckedIn);
+        }
+        let line_key = DataKey::TicketAddOn(token_id, addon_id.clone());
+        let mut line: TicketAddOn =
+            storage::read(e, &line_key).ok_or(AddOnError::NothingToRedeem)?;
+        if quantity == 0 {
+            return Err(AddOnError::InvalidQuantity);
+        }
+        if line.redeemed + quantity > line.quantity {
+            return Err(AddOnError::NothingToRedeem);
+        }
+        line.redeemed += quantity;
+        storage::write(e, &line_key, &line);
+
+        AddOnRedeemed {
+            addon_id,
+            token_id,
+            quantity,
+        }
+        .publish(e);
+        Ok(())
+    }
+
+    /// Pay back every unredeemed add-on unit of a ticket being refunded and
+    /// return the units to stock.
+    fn refund_addons(e: &Env, token_id: u32) {
+        for addon_id in Self::get_ticket_addons(e, token_id).iter() {
+            let line_key = DataKey::TicketAddOn(token_id, addon_id.clone());
+            let mut line: TicketAddOn = storage::read(e, &line_key).unwrap();
+            let quantity = line.quantity - line.redeemed;
+            if quantity == 0 {
+                continue;
+            }
+            let addon_key = DataKey::AddOn(addon_id.clone());
+            let mut addon: AddOn = storage::read(e, &addon_key).unwrap();
+            // Pro rata, so units bought at different prices share the total
+            let amount = line.amount_paid * quantity as i128 / line.quantity as i128;
+            token::Client::new(e, &addon.payment_token).transfer(
+                &e.current_contract_address(),
+                &line.buyer,
+                &amount,
+            );
+            Self::record_refund(e, &addon.payment_token, amount);
+
+            addon.sold -= quantity;
+            storage::write(e, &addon_key, &addon);
+            line.quantity = line.redeemed;
+            line.amount_paid -= amount;
+            storage::write(e, &line_key, &line);
+
+            AddOnRefunded {
+                buyer: line.buyer,
+                addon_id,
+                token_id,
+                quantity,
+                amount,
+            }
+            .publish(e);
+        }
+    }
+
+    // Payouts
+
+    /// Admin-only: split sale proceeds between `payees`, whose shares must
+    /// add up to 10000 bps.  Until set, the admin receives everything.  Fixed
+    /// once any revenue has been recorded.
+    pub fn set_payees(e: &Env, payees: Vec<Payee>) -> Result<(), PayoutError> {
+        Self::require_admin(e);
+
+        if Self::revenue_recorded(e) {
+            return Err(PayoutError::RevenueRecorded);
+        }
+        let mut total: u64 = 0;
+        for (i, payee) in payees.iter().enumerate() {
+            total += payee.share_bps as u64;
+            for other in payees.slice(i as u32 + 1..).iter() {
+                if other.recipient == payee.recipient {
+                    return Err(PayoutError::DuplicatePayee);
+                }
+            }
+        }
+        if total != payout::BPS_DENOMINATOR as u64 {
+            return Err(PayoutError::InvalidShares);
+        }
+        storage::write(e, &DataKey::Payees, &payees);
+        PayeesSet { payees }.publish(e);
+        Ok(())
+    }
+
+    pub fn get_payees(e: &Env) -> Vec<Payee> {
+        storage::read(e, &DataKey::Payees).unwrap_or_else(|| {
+            let admin: Address = e.storage().instance().get(&DataKey::Admin).unwrap();
+            soroban_sdk::vec![
+                e,
+                Payee {
+                    recipient: admin,
+                    share_bps: payout::BPS_DENOMINATOR,
+                }
+            ]
+        })
+    }
+
+    /// Set the fee the platform keeps from net proceeds, in bps.  The first
+    /// call needs both the admin and `authority` (the factory, for events it
+    /// deploys); after that only `authority` may change it.  Fixed once any
+    /// revenue has been recorded.
+    pub fn set_platform_fee(
+        e: &Env,
+        authority: Address,
+        recipient: Address,
+        fee_bps: u32,
+    ) -> Result<(), PayoutError> {
+        storage::extend_instance(e);
+        authority.require_auth();
+        match Self::get_platform_fee(e) {
+            Some(current) if current.authority != authority => {
+                return Err(PayoutError::NotPlatform);
+            }
+            Some(_) => {}
+            None => {
+                Self::require_admin(e);
+            }
+        }
+        if Self::revenue_recorded(e) {
+            return Err(PayoutError::RevenueRecorded);
+        }
+        if fee_bps > payout::BPS_DENOMINATOR {
+            return Err(PayoutError::InvalidFee);
+        }
+        let fee = PlatformFee {
+            authority,
+            recipient,
+            fee_bps,
+        };
+        storage::write(e, &DataKey::PlatformFee, &fee);
+        PlatformFeeSet { fee }.publish(e);
+        Ok(())
+    }
+
+    pub fn get_platform_fee(e: &Env) -> Option<PlatformFee> {
+        storage::read(e, &DataKey::PlatformFee)
+    }
+
+    pub fn get_revenue(e: &Env, payment_token: Address) -> Revenue {
+        storage::read(e, &DataKey::Revenue(payment_token)).unwrap_or_default()
+    }
+
+    /// What `recipient` could withdraw in `payment_token` once withdrawals
+    /// open; 0 for addresses that are not payees.
+    pub fn get_withdrawable(e: &Env, recipient: Address, payment_token: Address) -> i128 {
+        let payees = Self::get_payees(e);
+        let Some(index) = payees.iter().position(|p| p.recipient == recipient) else {
+            return 0;
+        };
+        let (_, distributable) = Self::split_net_revenue(e, &payment_token);
+        let owed = payout::payee_share(distributable, &payees, index as u32);
+        let withdrawn: i128 =
+            storage::read(e, &DataKey::Withdrawn(payment_token, recipient)).unwrap_or(0);
+        (owed - withdrawn).max(0)
+    }
+
+    /// Pay `payee` their share of the proceeds in `payment_token`.  Opens
+    /// after the refund cutoff, once no more tickets can be refunded.
+    pub fn withdraw(e: &Env, payee: Address, payment_token: Address) -> Result<i128, PayoutError> {
+        storage::extend_instance(e);
+        payee.require_auth();
+        Self::check_withdrawals_open(e)?;
+
+        if !Self::get_payees(e).iter().any(|p| p.recipient == payee) {
+            return Err(PayoutError::NotPayee);
+        }
+        let amount = Self::get_withdrawable(e, payee.clone(), payment_token.clone());
+        if amount == 0 {
+            return Err(PayoutError::NothingToWithdraw);
+        }
+        let key = DataKey::Withdrawn(payment_token.clone(), payee.clone());
+        let withdrawn: i128 = storage::read(e, &key).unwrap_or(0);
+        storage::write(e, &key, &(withdrawn + amount));
+        token::Client::new(e, &payment_token).transfer(
+            &e.current_contract_address(),
+            &payee,
+            &amount,
+        );
+
+        Withdrawn {
+            recipient: payee,
+            payment_token,
+            amount,
+            platform: false,
+        }
+        .publish(e);
+        Ok(amount)
+    }
+
+    /// Pay the platform fee owed in `payment_token` to its recipient.
+    /// Anyone may call this once withdrawals open.
+    pub fn withdraw_platform_fee(e: &Env, payment_token: Address) -> Result<i128, PayoutError> {
+        storage::extend_instance(e);
+        Self::check_withdrawals_open(e)?;
+
+        let fee = Self::get_platform_fee(e).ok_or(PayoutError::NothingToWithdraw)?;
+        let (owed, _) = Self::split_net_revenue(e, &payment_token);
+        let key = DataKey::PlatformWithdrawn(payment_token.clone());
+        let withdrawn: i128 = storage::read(e, &key).unwrap_or(0);
+        let amount = owed - withdrawn;
+        if amount <= 0 {
+            return Err(PayoutError::NothingToWithdraw);
+        }
+        storage::write(e, &key, &(withdrawn + amount));
+        token::Client::new(e, &payment_token).transfer(
+            &e.current_contract_address(),
+            &fee.recipient,
+            &amount,
+        );
+
+        Withdrawn {
+            recipient: fee.recipient,
+            payment_token,
+            amount,
+            platform: true,
+        }
+        .publish(e);
+        Ok(amount)
+    }
+
+    /// Proceeds are final once refunds have closed and every auction tier
+    /// has settled, so no refund or rebate can be owed afterwards.
+    fn check_withdrawals_open(e: &Env) -> Result<(), PayoutError> {
+        let now = e.ledger().timestamp();
+        if now <= Self::get_event_info(e).refund_cutoff_time {
+            return Err(PayoutError::WithdrawLocked);
+        }
+        for tier_symbol in Self::list_tiers(e).iter() {
+            let tier = Self::require_tier(e, &tier_symbol);
+            if let PricingStrategy::DutchAuction(auction) = tier.strategy {
+                if tier.minted < tier.max_supply && now <= auction.end_time {
+                    panic_with_error!(e, Error::AuctionNotSettled);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Net proceeds in `payment_token` as (platform cut, amount for payees).
+    fn split_net_revenue(e: &Env, payment_token: &Address) -> (i128, i128) {
+        let revenue = Self::get_revenue(e, payment_token.clone());
+        let net = revenue.gross - revenue.refunded;
+        let fee_bps = Self::get_platform_fee(e).map_or(0, |fee| fee.fee_bps);
+        let cut = payout::platform_cut(net, fee_bps);
+        (cut, net - cut)
+    }
+
+    fn record_sale(e: &Env, payment_token: &Address, amount: i128) {
+        if amount == 0 {
+            return;
+        }
+        let key = DataKey::Revenue(payment_token.clone());
+        let mut revenue: Revenue = storage::read(e, &key).unwrap_or_else(|| {
+            let mut tokens: Vec<Address> =
+                storage::read(e, &DataKey::RevenueTokens).unwrap_or(Vec::new(e));
+            tokens.push_back(payment_token.clone());
+            storage::write(e, &DataKey::RevenueTokens, &tokens);
+            Revenue::default()
+        });
+        revenue.gross += amount;
+        storage::write(e, &key, &revenue);
+    }
+
+    fn record_refund(e: &Env, payment_token: &Address, amount: i128) {
+        if amount == 0 {
+            return;
+        }
+        let key = DataKey::Revenue(payment_token.clone());
+        let mut revenue: Revenue = storage::read(e, &key).unwrap_or_default();
+        revenue.refunded += amount;
+        storage::write(e, &key, &revenue);
+    }
+
+    fn revenue_recorded(e: &Env) -> bool {
+        e.storage().persistent().has(&DataKey::RevenueTokens)
+    }
+
+    /// Refunds and rebates must be paid in the token the ticket was bought
+    /// with, so each token's escrow only pays back its own sales.
+    fn check_payment_token(ticket: &Ticket, payment_token: &Address) -> Result<(), Error> {
+        match &ticket.payment_token {
+            Some(paid) if paid != payment_token => Err(Error::WrongPaymentToken),
+            _ => Ok(()),
+        }
+    }
+
+    /// Extend the TTL of contract instance storage and of each of `keys`
+    /// that exists, e.g. tiers and tickets ahead of the event.  Bumping a
+    /// `Ticket` also extends the NFT owner entry.  Anyone may call this;
+    /// returns how many of `keys` were found.
+    pub fn bump(e: &Env, keys: Vec<DataKey>) -> u32 {
+        storage::extend_instance(e);
+        let mut found = 0;
+        for key in keys.iter() {
+            let exists = match key {
+                DataKey::Ticket(token_id) => storage::extend_ticket(e, token_id),
+                key => storage::extend_persistent(e, &key),
+            };
+            if exists {
+                found += 1;
+            }
+        }
+        found
+    }
+
+    /// Token ids held by `owner`, `limit` (at most 100) from `start`.
+    pub fn get_tickets_by_owner(e: &Env, owner: Address, start: u32, limit: u32) -> Vec<u32> {
+        let end = Base::balance(e, &owner).min(start.saturating_add(limit.min(MAX_PAGE_SIZE)));
+        let mut page = Vec::new(e);
+        for index in start..end {
+            page.push_back(Enumerable::get_owner_token_id(e, &owner, index));
+        }
+        page
+    }
+
+    /// Number of live (unrefunded) tickets in a tier.
+    pub fn get_tier_ticket_count(e: &Env, tier_symbol: Symbol) -> u32 {
+        storage::read(e, &DataKey::TierTicketCount(tier_symbol)).unwrap_or(0)
+    }
+
+    /// Live token ids of a tier, `limit` (at most 100) from `start`.  Order
+    /// follows minting until a refund moves the last ticket into the gap.
+    pub fn get_tickets_by_tier(e: &Env, tier_symbol: Symbol, start: u32, limit: u32) -> Vec<u32> {
+        let count = Self::get_tier_ticket_count(e, tier_symbol.clone());
+        let end = count.min(start.saturating_add(limit.min(MAX_PAGE_SIZE)));
+        let mut page = Vec::new(e);
+        for index in start..end {
+            let token_id: u32 =
+                storage::read(e, &DataKey::TierTicket(tier_symbol.clone(), index)).unwrap();
+            page.push_back(token_id);
+        }
+        page
+    }
+
+    fn add_to_tier_index(e: &Env, tier_symbol: &Symbol, token_id: u32) {
+        let index = Self::get_tier_ticket_count(e, tier_symbol.clone());
+        storage::write(
+            e,
+            &DataKey::TierTicket(tier_symbol.clone(), index),
+            &token_id,
+        );
+        storage::write(e, &DataKey::TierTicketIndex(token_id), &index);
+        storage::write(
+            e,
+            &DataKey::TierTicketCount(tier_symbol.clone()),
+            &(index + 1),
+        );
+    }
+
+    /// Swap-remove: the tier's last ticket takes the removed ticket's slot.
+    fn remove_from_tier_index(e: &Env, tier_symbol: &Symbol, token_id: u32) {
+        let Some(index) = storage::read::<u32>(e, &DataKey::TierTicketIndex(token_id)) else {
+            return;
+        };
+        let last = Self::get_tier_ticket_count(e, tier_symbol.clone()) - 1;
+        if index != last {
+            let moved: u32 =
+                storage::read(e, &DataKey::TierTicket(tier_symbol.clone(), last)).unwrap();
+            storage::write(e, &DataKey::TierTicket(tier_symbol.clone(), index), &moved);
+            storage::write(e, &DataKey::TierTicketIndex(moved), &index);
+        }
+        let persistent = e.storage().persistent();
+        persistent.remove(&DataKey::TierTicket(tier_symbol.clone(), last));
+        persistent.remove(&DataKey::TierTicketIndex(token_id));
+        storage::write(e, &DataKey::TierTicketCount(tier_symbol.clone()), &last);
+    }
+
+    /// Mint the next sequential token to `to` and record its ticket data.
+    fn mint_ticket(
+        e: &Env,
+        payer: &Address,
+        to: &Address,
+        tier_symbol: &Symbol,
+        price_paid: i128,
+        payment_token: Option<Address>,
+        variant: Option<(u32, u32)>,
+    ) -> u32 {
+        // The NFT id is the only token id; the ticket record follows it
+        let token_id = Enumerable::sequential_mint(e, to);
+        if e.storage().persistent().has(&DataKey::Ticket(token_id)) {
+            panic_with_error!(e, Error::TicketIdInUse);
+        }
+        Self::add_to_tier_index(e, tier_symbol, token_id);
+        Self::reserve_sessions(e, tier_symbol);
+
+        let ticket = Ticket {
+            tier_symbol: tier_symbol.clone(),
+            purchase_time: e.ledger().timestamp(),
+            price_paid,
+            is_valid: true,
+            experiment_id: variant.map(|(experiment_id, _)| experiment_id),
+            variant: variant.map(|(_, index)| index),
+            checked_in_at: None,
+            payer: payer.clone(),
+            payment_token,
+            attendee_assigned: false,
+        };
+        storage::write(e, &DataKey::Ticket(token_id), &ticket);
+        storage::extend_ticket(e, token_id);
+        token_id
+    }
+
+    // View functions logic
+    pub fn get_ticket(e: &Env, token_id: u32) -> Result<Ticket, Error> {
+        storage::read(e, &DataKey::Ticket(token_id)).ok_or(Error::TicketNotFound)
+    }
+
+    /// `get_ticket` for entry points declared with a feature error enum:
+    /// aborts with the shared `Error::TicketNotFound` code.
+    fn require_ticket(e: &Env, token_id: u32) -> Ticket {
+        Self::get_ticket(e, token_id).unwrap_or_else(|err| panic_with_error!(e, err))
+    }
+
+    /// `require_ticket`, also aborting with `Error::TicketInvalidated` once
+    /// the ticket has been refunded.
+    fn require_live_ticket(e: &Env, token_id: u32) -> Ticket {
+        let ticket = Self::require_ticket(e, token_id);
+        if !ticket.is_valid {
+            panic_with_error!(e, Error::TicketInvalidated);
+        }
+        ticket
+    }
+}
+
+// Implement SEP-0054 via OpenZeppelin Interface
+#[contractimpl]
+impl NonFungibleToken for SoulboundTicketContract {
+    type ContractType = Enumerable;
+
+    fn balance(e: &Env, owner: Address) -> u32 {
+        Self::ContractType::balance(e, &owner)
+    }
+
+    fn owner_of(e: &Env, token_id: u32) -> Address {
+        Self::ContractType::owner_of(e, token_id)
+    }
+
+    // Soulbound restrictions overrides
+    fn transfer(e: &Env, _from: Address, _to: Address, _token_id: u32) {
+        panic_with_error!(e, Error::Soulbound);
+    }
+
+    fn transfer_from(e: &Env, _spender: Address, _from: Address, _to: Address, _token_id: u32) {
+        panic_with_error!(e, Error::Soulbound);
+    }
+
+    fn approve(
+        e: &Env,
+        _approver: Address,
+        _approved: Address,
+        _token_id: u32,
+        _live_until_ledger: u32,
+    ) {
+        panic_with_error!(e, Error::Soulbound);
+    }
+
+    fn approve_for_all(e: &Env, _owner: Address, _operator: Address, _live_until_ledger: u32) {
+        panic_with_error!(e, Error::Soulbound);
+    }
+
+    fn get_approved(_e: &Env, _token_id: u32) -> Option<Address> {
+        None
+    }
+
+    fn is_approved_for_all(_e: &Env, _owner: Address, _operator: Address) -> bool {
+        false
+    }
+
+    // Metadata
+    fn name(e: &Env) -> String {
+        Self::ContractType::name(e)
+    }
+
+    fn symbol(e: &Env) -> String {
+        Self::ContractType::symbol(e)
     }
 
     fn token_uri(e: &Env, token_id: u32) -> String {
-        Self::ContractType::token_uri(e, token_id)
+        let ticket = Self::require_ticket(e, token_id);
+        Self::ticket_uri(e, token_id, &ticket)
+    }
+}
+
+#[contractimpl]
+impl NonFungibleEnumerable for SoulboundTicketContract {
+    fn total_supply(e: &Env) -> u32 {
+        Enumerable::total_supply(e)
+    }
+
+    fn get_owner_token_id(e: &Env, owner: Address, index: u32) -> u32 {
+        Enumerable::get_owner_token_id(e, &owner, index)
+    }
+
+    fn get_token_id(e: &Env, index: u32) -> u32 {
+        Enumerable::get_token_id(e, index)
     }
 }
 
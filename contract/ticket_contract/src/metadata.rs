@@ -0,0 +1,99 @@
+//! metadata.rs — Token URIs and on-chain JSON metadata for tickets
+//!
+//! Strings are assembled in host `Bytes`, so no fixed-size buffers are
+//! involved.  URI templates may contain `{id}` and `{status}`, replaced by
+//! the token id and the ticket's status slug; any other text is copied as is.
+use soroban_sdk::{Bytes, Env, String};
+
+use crate::storage_types::TicketStatus;
+
+/// Longest accepted URI template, in bytes.
+pub const MAX_URI_TEMPLATE_LEN: u32 = 200;
+
+const ID_PLACEHOLDER: &[u8] = b"{id}";
+const STATUS_PLACEHOLDER: &[u8] = b"{status}";
+
+/// Lowercase name of `status` used in URIs and metadata.
+pub fn status_slug(status: &TicketStatus) -> &'static str {
+    match status {
+        TicketStatus::Valid => "valid",
+        TicketStatus::CheckedIn => "checked_in",
+        TicketStatus::Refunded => "refunded",
+        TicketStatus::Attended => "attended",
+    }
+}
+
+/// Fill `{id}` and `{status}` in `template`.
+pub fn render_uri(e: &Env, template: &String, token_id: u32, status: &TicketStatus) -> String {
+    let source = template.to_bytes();
+    let mut out = Bytes::new(e);
+    let mut i = 0;
+    while i < source.len() {
+        if starts_with(e, &source, i, ID_PLACEHOLDER) {
+            push_u128(&mut out, token_id as u128);
+            i += ID_PLACEHOLDER.len() as u32;
+        } else if starts_with(e, &source, i, STATUS_PLACEHOLDER) {
+            out.extend_from_slice(status_slug(status).as_bytes());
+            i += STATUS_PLACEHOLDER.len() as u32;
+        } else {
+            out.push_back(source.get_unchecked(i));
+            i += 1;
+        }
+    }
+    out.to_string()
+}
+
+fn starts_with(e: &Env, source: &Bytes, at: u32, pattern: &[u8]) -> bool {
+    let end = at + pattern.len() as u32;
+    end <= source.len() && source.slice(at..end) == Bytes::from_slice(e, pattern)
+}
+
+/// Append the decimal representation of `value`.
+pub fn push_u128(out: &mut Bytes, mut value: u128) {
+    let mut digits = [0u8; 39];
+    let mut start = digits.len();
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    out.extend_from_slice(&digits[start..]);
+}
+
+pub fn push_i128(out: &mut Bytes, value: i128) {
+    if value < 0 {
+        out.push_back(b'-');
+    }
+    push_u128(out, value.unsigned_abs());
+}
+
+/// Append `value` as a quoted JSON string, escaping quotes, backslashes and
+/// control characters.
+pub fn push_json_string(out: &mut Bytes, value: &String) {
+    out.push_back(b'"');
+    for byte in value.to_bytes().iter() {
+        match byte {
+            b'"' | b'\\' => {
+                out.push_back(b'\\');
+                out.push_back(byte);
+            }
+            0x00..=0x1f => {
+                out.extend_from_slice(b"\\u00");
+                out.push_back(hex_digit(byte >> 4));
+                out.push_back(hex_digit(byte & 0x0f));
+            }
+            _ => out.push_back(byte),
+        }
+    }
+    out.push_back(b'"');
+}
+
+fn hex_digit(nibble: u8) -> u8 {
+    match nibble {
+        0..=9 => b'0' + nibble,
+        _ => b'a' + nibble - 10,
+    }
+}
@@ -11,7 +11,13 @@
 ///   get_value(key: String) -> (i128, u64)
 ///     - i128: price with 8 decimal places (e.g. 100_000_000 = $1.00)
 ///     - u64:  UNIX timestamp of the last price update
-use soroban_sdk::{contractclient, symbol_short, Address, Env, IntoVal, String, TryFromVal, Val};
+///
+/// SEP-40 oracles (e.g. Reflector) are supported as well; their prices are
+/// normalised to the same 8-decimal format.
+use soroban_sdk::{contractclient, contracttype, Address, Env, String, Symbol, Vec};
+
+use crate::pricing::mul_div;
+use crate::storage_types::{Asset, OracleKind, OracleSource, PriceObservation};
 
 /// How long (in seconds) a price is considered fresh. Default: 24 hours.
 pub const DEFAULT_STALENESS_SECONDS: u64 = 86_400;
@@ -19,6 +25,9 @@ pub const DEFAULT_STALENESS_SECONDS: u64 = 86_400;
 /// DIA oracle returns prices with 8 decimal places: 1.0 == 100_000_000
 pub const DIA_ORACLE_DECIMALS: i128 = 100_000_000;
 
+/// Number of decimals every source is normalised to (matches DIA).
+pub const NORMALIZED_DECIMALS: u32 = 8;
+
 // --------------------------------------------------------------------------
 // DIA Oracle client
 //
@@ -53,11 +62,54 @@ pub trait DexPriceRouterInterface {
     fn get_spot_price(env: Env, pair: String) -> i128;
 }
 
+// --------------------------------------------------------------------------
+// SEP-40 oracle client
+//
+// SEP-40 is the common Stellar price-feed interface (Reflector and others):
+//   lastprice(asset: Asset) -> Option<PriceData>
+//   decimals() -> u32
+//   resolution() -> u32
+// --------------------------------------------------------------------------
+
+/// Price record returned by SEP-40 oracles.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    /// Price scaled by `10^decimals()`.
+    pub price: i128,
+    /// UNIX timestamp (seconds) of the price.
+    pub timestamp: u64,
+}
+
+/// Trait mirroring the SEP-40 price-feed interface.
+/// `contractclient` generates `Sep40OracleClient`.
+#[contractclient(name = "Sep40OracleClient")]
+pub trait Sep40OracleInterface {
+    /// Most recent price of `asset`, if the oracle tracks it.
+    fn lastprice(env: Env, asset: Asset) -> Option<PriceData>;
+    /// Number of decimals of every returned price.
+    fn decimals(env: Env) -> u32;
+    /// Seconds between price updates.
+    fn resolution(env: Env) -> u32;
+}
+
+/// Rescale `price` from `decimals` to `NORMALIZED_DECIMALS`.  Returns `None`
+/// if the result would not fit an `i128`.
+pub fn normalize_decimals(price: i128, decimals: u32) -> Option<i128> {
+    if decimals >= NORMALIZED_DECIMALS {
+        let factor = 10i128.checked_pow(decimals - NORMALIZED_DECIMALS)?;
+        Some(price / factor)
+    } else {
+        let factor = 10i128.checked_pow(NORMALIZED_DECIMALS - decimals)?;
+        price.checked_mul(factor)
+    }
+}
+
 // --------------------------------------------------------------------------
 // Fetching logic
 // --------------------------------------------------------------------------
 
-/// Result returned by `fetch_oracle_price`.
+/// Result returned by `fetch_oracle_result`.
 pub struct OracleResult {
     /// Price with 8 decimal places.
     pub price: i128,
@@ -67,93 +119,216 @@ pub struct OracleResult {
     pub from_primary: bool,
 }
 
-/// Fetch a live price from the DIA oracle at `oracle_address` for `pair`
-/// (e.g. `"XLM/USD"`).
+/// A ticket price multiplier as read from the oracle, before anything is
+/// stored or published.
+pub struct MultiplierReading {
+    pub multiplier: i128,
+    /// Computed from a live price, so it can become the last known good one.
+    pub live: bool,
+    /// Fallback path taken (`dex`, `last_good` or `neutral`) and the price
+    /// or multiplier it used.
+    pub fallback: Option<(Symbol, i128)>,
+}
+
+/// High-level helper: try the primary oracle first; fall back to the DEX
+/// router if the oracle is stale or fails.
 ///
-/// # Staleness guard
-/// If the price timestamp is older than `max_age_seconds`, the function
-/// panics with an informative message so the caller can decide to fall back.
+/// `oracle_kind` selects the interface spoken by `oracle_address`.
 ///
-/// # Returns
-/// `OracleResult` with `from_primary = true`.
-pub fn fetch_primary_oracle_price(
+/// Returns `None` only when both sources are unavailable (to let the caller
+/// choose between using the cached price or panicking).
+pub fn fetch_price_with_fallback(
     e: &Env,
+    oracle_kind: &OracleKind,
     oracle_address: &Address,
+    dex_address: &Address,
     pair: String,
     max_age_seconds: u64,
-) -> OracleResult {
-    let client = DiaOraclePriceClient::new(e, oracle_address);
-    let (raw_price, timestamp) = client.get_value(&pair);
+) -> Option<OracleResult> {
+    fetch_oracle_result(
+        e,
+        oracle_kind,
+        oracle_address,
+        pair.clone(),
+        max_age_seconds,
+    )
+    .or_else(|| fetch_oracle_result(e, &OracleKind::Dex, dex_address, pair, max_age_seconds))
+}
 
-    // Staleness check
+/// Query one oracle of the given kind.  Returns `None` when the call fails,
+/// the asset is unknown or the reported price is stale or non-positive;
+/// prices are normalised to `NORMALIZED_DECIMALS`.
+pub fn fetch_oracle_result(
+    e: &Env,
+    kind: &OracleKind,
+    address: &Address,
+    pair: String,
+    max_age_seconds: u64,
+) -> Option<OracleResult> {
     let now = e.ledger().timestamp();
-    if now > timestamp && (now - timestamp) > max_age_seconds {
-        panic!("oracle price is stale");
-    }
+    let (price, timestamp, from_primary) = match kind {
+        OracleKind::Dia => {
+            let client = DiaOraclePriceClient::new(e, address);
+            match client.try_get_value(&pair) {
+                Ok(Ok((price, timestamp))) => (price, timestamp, true),
+                _ => return None,
+            }
+        }
+        OracleKind::Dex => {
+            let client = DexPriceRouterClient::new(e, address);
+            match client.try_get_spot_price(&pair) {
+                Ok(Ok(price)) => (price, now, false),
+                _ => return None,
+            }
+        }
+        OracleKind::Sep40(asset) => {
+            let client = Sep40OracleClient::new(e, address);
+            let data = match client.try_lastprice(asset) {
+                Ok(Ok(Some(data))) => data,
+                _ => return None,
+            };
+            let decimals = match client.try_decimals() {
+                Ok(Ok(decimals)) => decimals,
+                _ => return None,
+            };
+            (
+                normalize_decimals(data.price, decimals)?,
+                data.timestamp,
+                true,
+            )
+        }
+    };
 
-    OracleResult {
-        price: raw_price,
-        timestamp,
-        from_primary: true,
+    if price <= 0 || (now > timestamp && (now - timestamp) > max_age_seconds) {
+        return None;
     }
+    Some(OracleResult {
+        price,
+        timestamp,
+        from_primary,
+    })
 }
 
-/// Fetch a spot price from the DEX router at `dex_address` for `pair`.
-/// This is the fallback when the primary oracle is unavailable or stale.
-pub fn fetch_dex_price(e: &Env, dex_address: &Address, pair: String) -> OracleResult {
-    let client = DexPriceRouterClient::new(e, dex_address);
-    let raw_price = client.get_spot_price(&pair);
+// --------------------------------------------------------------------------
+// Multi-source aggregation
+// --------------------------------------------------------------------------
 
-    OracleResult {
-        price: raw_price,
-        timestamp: e.ledger().timestamp(),
-        from_primary: false,
+/// Query a single configured source.  Returns `None` when the call fails or
+/// the reported price is stale or non-positive, so a broken source simply
+/// drops out of the aggregation.
+pub fn fetch_source_price(
+    e: &Env,
+    source: &OracleSource,
+    pair: String,
+    max_age_seconds: u64,
+) -> Option<i128> {
+    fetch_oracle_result(e, &source.kind, &source.address, pair, max_age_seconds)
+        .map(|result| result.price)
+}
+
+/// Median of `values`; the mean of the two middle values for an even count.
+pub fn median(values: &Vec<i128>) -> Option<i128> {
+    let len = values.len();
+    if len == 0 {
+        return None;
+    }
+
+    // Insertion sort: source lists are a handful of entries long
+    let mut sorted = values.clone();
+    for i in 1..len {
+        let mut j = i;
+        while j > 0 && sorted.get_unchecked(j - 1) > sorted.get_unchecked(j) {
+            let prev = sorted.get_unchecked(j - 1);
+            sorted.set(j - 1, sorted.get_unchecked(j));
+            sorted.set(j, prev);
+            j -= 1;
+        }
+    }
+
+    let mid = len / 2;
+    if len % 2 == 1 {
+        Some(sorted.get_unchecked(mid))
+    } else {
+        let low = sorted.get_unchecked(mid - 1);
+        let high = sorted.get_unchecked(mid);
+        Some(low + (high - low) / 2)
     }
 }
 
-/// High-level helper: try the primary oracle first; fall back to the DEX
-/// router if the oracle is stale or panics.
-///
-/// Returns `None` only when both sources are unavailable (to let the caller
-/// choose between using the cached price or panicking).
-pub fn fetch_price_with_fallback(
+/// Fetch every source, take the median, drop sources that deviate from it by
+/// more than `max_deviation_bps`, and return the median of the remaining
+/// ones.  Returns `None` when fewer than `min_sources` agree.
+pub fn fetch_aggregated_price(
     e: &Env,
-    oracle_address: &Address,
-    dex_address: &Address,
+    sources: &Vec<OracleSource>,
     pair: String,
     max_age_seconds: u64,
-) -> Option<OracleResult> {
-    // --- Primary oracle ---
-    let client = DiaOraclePriceClient::new(e, oracle_address);
-    let oracle_result = client.try_get_value(&pair);
-
-    if let Ok(Ok((raw_price, timestamp))) = oracle_result {
-        let now = e.ledger().timestamp();
-        // Accept if fresh enough
-        if now <= timestamp || (now - timestamp) <= max_age_seconds {
-            return Some(OracleResult {
-                price: raw_price,
-                timestamp,
-                from_primary: true,
-            });
+    max_deviation_bps: u32,
+    min_sources: u32,
+) -> Option<i128> {
+    let mut prices = Vec::new(e);
+    for source in sources.iter() {
+        if let Some(price) = fetch_source_price(e, &source, pair.clone(), max_age_seconds) {
+            prices.push_back(price);
         }
-        // else fall through to DEX fallback
     }
 
-    // --- DEX fallback ---
-    let dex_client = DexPriceRouterClient::new(e, dex_address);
-    let dex_result = dex_client.try_get_spot_price(&pair);
+    let mid = median(&prices)?;
+    let band = mid * max_deviation_bps as i128 / 10000;
+    let mut agreeing = Vec::new(e);
+    for price in prices.iter() {
+        if (price - mid).abs() <= band {
+            agreeing.push_back(price);
+        }
+    }
+
+    if agreeing.len() < min_sources.max(1) {
+        return None;
+    }
+    median(&agreeing)
+}
+
+/// Time-weighted average of `observations` (oldest first) up to `now`.
+///
+/// Each price is weighted by how long it stayed the latest observation, so a
+/// price recorded in the current ledger carries no weight until time passes.
+/// Falls back to the plain mean when all observations share one timestamp.
+pub fn time_weighted_average(observations: &Vec<PriceObservation>, now: u64) -> Option<i128> {
+    let len = observations.len();
+    if len == 0 {
+        return None;
+    }
+
+    let mut weighted: i128 = 0;
+    let mut total: i128 = 0;
+    let mut sum: i128 = 0;
+    for i in 0..len {
+        let observation = observations.get_unchecked(i);
+        let until = if i + 1 < len {
+            observations.get_unchecked(i + 1).timestamp
+        } else {
+            now
+        };
+        let duration = until.saturating_sub(observation.timestamp) as i128;
+        weighted = weighted.saturating_add(observation.price.saturating_mul(duration));
+        total += duration;
+        sum = sum.saturating_add(observation.price);
+    }
 
-    if let Ok(Ok(raw_price)) = dex_result {
-        return Some(OracleResult {
-            price: raw_price,
-            timestamp: e.ledger().timestamp(),
-            from_primary: false,
-        });
+    if total == 0 {
+        return Some(sum / len as i128);
     }
+    Some(weighted / total)
+}
 
-    // Both unavailable
-    None
+/// Limit the move from `previous` to `next` to `max_change_bps` of
+/// `previous`.  A zero limit leaves `next` untouched.
+pub fn clamp_change(previous: i128, next: i128, max_change_bps: u32) -> i128 {
+    if max_change_bps == 0 {
+        return next;
+    }
+    let band = previous.abs() * max_change_bps as i128 / 10000;
+    next.clamp(previous - band, previous + band)
 }
 
 /// Convert a raw DIA price (8 decimals) into a `ORACLE_PRECISION`-scaled
@@ -167,13 +342,14 @@ pub fn fetch_price_with_fallback(
 /// market conditions should adjust the ticket price.  A neutral market =
 /// `oracle_precision` (1x).  Use `reference_price_8dec` = the expected
 /// baseline price from the oracle (e.g. last known good price or 1 USD).
+/// The product goes through `mul_div`, so extreme prices cannot overflow.
 pub fn oracle_price_to_multiplier(
     raw_price: i128,
     reference_price_8dec: i128,
     oracle_precision: i128,
 ) -> i128 {
-    if reference_price_8dec == 0 {
+    if reference_price_8dec <= 0 {
         return oracle_precision; // no basis for ratio — neutral
     }
-    (raw_price * oracle_precision) / reference_price_8dec
+    mul_div(raw_price, oracle_precision, reference_price_8dec)
 }
@@ -0,0 +1,38 @@
+//! payout.rs — Splitting sale proceeds between the platform and payees
+//!
+//! Proceeds are tracked cumulatively per payment token, so a payee's share is
+//! always recomputed from the net revenue (sales minus refunds) rather than
+//! accrued sale by sale.  Shares round down; the first payee receives whatever
+//! rounding leaves over, so the platform cut and all shares add up to the net
+//! revenue exactly.
+use soroban_sdk::Vec;
+
+use crate::pricing::mul_div;
+use crate::storage_types::Payee;
+
+/// Shares are expressed in basis points of the amount after the platform fee.
+pub const BPS_DENOMINATOR: u32 = 10_000;
+
+/// Platform fee on `net`, rounded down.
+pub fn platform_cut(net: i128, fee_bps: u32) -> i128 {
+    mul_div(net, fee_bps as i128, BPS_DENOMINATOR as i128)
+}
+
+/// Share of `distributable` owed to `payees[index]`.
+pub fn payee_share(distributable: i128, payees: &Vec<Payee>, index: u32) -> i128 {
+    let share = |payee: Payee| {
+        mul_div(
+            distributable,
+            payee.share_bps as i128,
+            BPS_DENOMINATOR as i128,
+        )
+    };
+    if index > 0 {
+        return share(payees.get_unchecked(index));
+    }
+    let mut rest = distributable;
+    for i in 1..payees.len() {
+        rest -= share(payees.get_unchecked(i));
+    }
+    rest
+}
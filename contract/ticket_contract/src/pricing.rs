@@ -0,0 +1,201 @@
+//! pricing.rs — Fixed-point helpers for continuous pricing curves
+//!
+//! All curve math runs on `i128` with `CURVE_PRECISION` (1e9) fixed-point
+//! values.  Intermediate products go through `mul_div`, which never overflows:
+//! it falls back to a split multiplication and saturates at `i128::MAX`.
+use crate::storage_types::{BondingCurve, CurveShape, DutchAuction, TimeDecaySchedule};
+
+/// Fixed-point scale used by the curve math: 1.0 == 1_000_000_000.
+pub const CURVE_PRECISION: i128 = 1_000_000_000;
+
+/// `BondingCurve::steepness` is expressed in thousandths.
+pub const STEEPNESS_PRECISION: i128 = 1_000;
+
+/// Largest accepted `steepness` (20.0).  Keeps `exp` below ~4.9e17 so every
+/// intermediate fixed-point value stays far away from the `i128` limit.
+pub const MAX_CURVE_STEEPNESS: u32 = 20_000;
+
+/// Fiat tier prices are expressed in hundredths of the currency (cents).
+pub const FIAT_DECIMALS: u32 = 2;
+
+/// Oracle rates are normalised to 8 decimals.
+const RATE_PRECISION: i128 = 100_000_000;
+
+/// e (2.718281828...) in `CURVE_PRECISION` fixed point.
+const E_FIXED: i128 = 2_718_281_828;
+
+/// Number of Taylor terms used for the fractional part of `exp_fixed`.
+const EXP_TAYLOR_TERMS: i128 = 20;
+
+/// `a * b / d` (rounded toward zero) without intermediate overflow.
+///
+/// Uses the direct product when it fits, otherwise splits `a` into
+/// `(a / d) * d + a % d` so that only the quotient part can grow large; that
+/// part saturates instead of wrapping.  `d` must be positive.
+pub fn mul_div(a: i128, b: i128, d: i128) -> i128 {
+    match a.checked_mul(b) {
+        Some(product) => product / d,
+        None => (a / d)
+            .saturating_mul(b)
+            .saturating_add((a % d).saturating_mul(b) / d),
+    }
+}
+
+/// `e^x` for a non-negative fixed-point `x` (at most `MAX_CURVE_STEEPNESS`).
+///
+/// Splits `x` into integer and fractional parts: `e^n` is built by repeated
+/// multiplication with `E_FIXED`, `e^r` (with `r < 1`) from a Taylor series.
+/// Every step is non-decreasing in `x`, so the result is monotonic.
+pub fn exp_fixed(x: i128) -> i128 {
+    let whole = x / CURVE_PRECISION;
+    let frac = x % CURVE_PRECISION;
+
+    let mut int_part = CURVE_PRECISION;
+    for _ in 0..whole {
+        int_part = int_part * E_FIXED / CURVE_PRECISION;
+    }
+
+    let mut term = CURVE_PRECISION;
+    let mut frac_part = CURVE_PRECISION;
+    for k in 1..=EXP_TAYLOR_TERMS {
+        term = term * frac / CURVE_PRECISION / k;
+        if term == 0 {
+            break;
+        }
+        frac_part += term;
+    }
+
+    int_part * frac_part / CURVE_PRECISION
+}
+
+/// Logistic function `1 / (1 + e^-y)` for a fixed-point `y` of either sign.
+fn logistic_fixed(y: i128) -> i128 {
+    let ey = exp_fixed(y.abs());
+    if y >= 0 {
+        ey * CURVE_PRECISION / (ey + CURVE_PRECISION)
+    } else {
+        CURVE_PRECISION * CURVE_PRECISION / (ey + CURVE_PRECISION)
+    }
+}
+
+/// Normalised curve progress: maps sale progress `s` in `[0, 1]` onto
+/// `[0, 1]` following `shape`.  Both ends are pinned, so the curve only
+/// decides how the increase is distributed, never its total size.
+pub fn curve_factor(shape: &CurveShape, steepness: u32, progress: i128) -> i128 {
+    let s = progress.clamp(0, CURVE_PRECISION);
+    let k = (steepness.min(MAX_CURVE_STEEPNESS) as i128) * CURVE_PRECISION / STEEPNESS_PRECISION;
+    if k == 0 {
+        return s;
+    }
+
+    match shape {
+        CurveShape::Linear => s,
+        CurveShape::Exponential => {
+            // (e^(k*s) - 1) / (e^k - 1)
+            let num = exp_fixed(k * s / CURVE_PRECISION) - CURVE_PRECISION;
+            let den = exp_fixed(k) - CURVE_PRECISION;
+            num * CURVE_PRECISION / den
+        }
+        CurveShape::Sigmoid => {
+            // Logistic centred at half supply, rescaled to hit 0 and 1 at the ends.
+            let half = CURVE_PRECISION / 2;
+            let low = logistic_fixed(-k / 2);
+            let high = logistic_fixed(k / 2);
+            let at = logistic_fixed(k * (s - half) / CURVE_PRECISION);
+            (at - low) * CURVE_PRECISION / (high - low)
+        }
+    }
+}
+
+/// Price of the next ticket of a tier that follows `curve`.
+///
+/// `base_price` is charged for the first ticket and
+/// `base_price * (1 + max_increase_bps / 10000)` for the last one; the shape
+/// decides the path in between.  The result is clamped into that range.
+pub fn bonding_curve_price(
+    base_price: i128,
+    minted: u32,
+    max_supply: u32,
+    curve: &BondingCurve,
+) -> i128 {
+    let progress = (minted as i128) * CURVE_PRECISION / (max_supply.max(1) as i128);
+    let factor = curve_factor(&curve.shape, curve.steepness, progress);
+
+    let max_increase = mul_div(base_price, curve.max_increase_bps as i128, 10000);
+    let increase = mul_div(max_increase, factor, CURVE_PRECISION);
+    base_price.saturating_add(increase.clamp(0, max_increase.max(0)))
+}
+
+/// Current price of a Dutch auction at `now`.
+///
+/// Before `start_time` this is the ceiling, after `end_time` the floor; in
+/// between the price decays linearly, rounded down to whole `step_seconds`.
+pub fn dutch_auction_price(auction: &DutchAuction, now: u64) -> i128 {
+    if now <= auction.start_time {
+        return auction.start_price;
+    }
+    if now >= auction.end_time {
+        return auction.floor_price;
+    }
+
+    let duration = (auction.end_time - auction.start_time) as i128;
+    let mut elapsed = now - auction.start_time;
+    if auction.step_seconds > 0 {
+        elapsed -= elapsed % auction.step_seconds;
+    }
+
+    let drop = mul_div(
+        auction.start_price - auction.floor_price,
+        elapsed as i128,
+        duration,
+    );
+    auction.start_price - drop
+}
+
+/// Start of the last-minute surge window, if the schedule has one.
+pub fn surge_start(schedule: &TimeDecaySchedule, event_start: u64) -> Option<u64> {
+    if schedule.surge_window_seconds == 0 || schedule.surge_premium_bps == 0 {
+        return None;
+    }
+    Some(event_start.saturating_sub(schedule.surge_window_seconds))
+}
+
+/// Price of a `TimeDecay` tier at `now`: the discount of the first early-bird
+/// phase still running, or the surge premium inside the surge window.
+pub fn time_decay_price(
+    base_price: i128,
+    schedule: &TimeDecaySchedule,
+    event_start: u64,
+    now: u64,
+) -> i128 {
+    for phase in schedule.phases.iter() {
+        if now < phase.until {
+            return base_price - mul_div(base_price, phase.discount_bps as i128, 10000);
+        }
+    }
+
+    match surge_start(schedule, event_start) {
+        Some(from) if now >= from => base_price.saturating_add(mul_div(
+            base_price,
+            schedule.surge_premium_bps as i128,
+            10000,
+        )),
+        _ => base_price,
+    }
+}
+
+/// Payment token amount (in `token_decimals` units) for `fiat_amount` cents
+/// at `rate`, the token price in the fiat currency with 8 decimals.
+///
+/// Rounds up so the organizer never receives less than the fiat price.
+/// Returns `None` for a non-positive rate or on overflow.
+pub fn fiat_to_token_amount(fiat_amount: i128, rate: i128, token_decimals: u32) -> Option<i128> {
+    if rate <= 0 {
+        return None;
+    }
+    let numerator = fiat_amount
+        .checked_mul(10i128.checked_pow(token_decimals)?)?
+        .checked_mul(RATE_PRECISION)?;
+    let denominator = rate.checked_mul(10i128.pow(FIAT_DECIMALS))?;
+    Some((numerator + denominator - 1) / denominator)
+}
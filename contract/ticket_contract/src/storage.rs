@@ -0,0 +1,62 @@
+//! storage.rs — TTL-aware access to persistent and instance storage
+//!
+//! Every persistent entry read or written through these helpers has its TTL
+//! pushed out to `TTL_PERSISTENT`, so tiers and tickets in active use are
+//! never archived mid-sale.  Entries nobody touches can be kept alive with
+//! the permissionless `bump` entry point.  Every state-changing entry point
+//! extends the instance TTL; admin ones do so in `require_admin`.
+use soroban_sdk::{Env, IntoVal, TryFromVal, Val};
+use stellar_tokens::non_fungible::NFTStorageKey;
+
+use crate::storage_types::DataKey;
+
+pub const TTL_INSTANCE: u32 = 17280 * 30; // 30 days
+pub const TTL_PERSISTENT: u32 = 17280 * 90; // 90 days
+
+pub fn extend_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(TTL_INSTANCE, TTL_INSTANCE);
+}
+
+/// Extend `key` if it exists; returns whether it did.
+pub fn extend_persistent(e: &Env, key: &DataKey) -> bool {
+    let exists = e.storage().persistent().has(key);
+    if exists {
+        e.storage()
+            .persistent()
+            .extend_ttl(key, TTL_PERSISTENT, TTL_PERSISTENT);
+    }
+    exists
+}
+
+pub fn read<V: TryFromVal<Env, Val>>(e: &Env, key: &DataKey) -> Option<V> {
+    let val = e.storage().persistent().get(key);
+    if val.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(key, TTL_PERSISTENT, TTL_PERSISTENT);
+    }
+    val
+}
+
+pub fn write<V: IntoVal<Env, Val>>(e: &Env, key: &DataKey, val: &V) {
+    e.storage().persistent().set(key, val);
+    e.storage()
+        .persistent()
+        .extend_ttl(key, TTL_PERSISTENT, TTL_PERSISTENT);
+}
+
+/// Keep a ticket's record and its NFT owner entry alive together.  The
+/// owner entry is only extended by 30 days on reads inside `stellar-tokens`,
+/// which could let it lapse before the event.
+pub fn extend_ticket(e: &Env, token_id: u32) -> bool {
+    let exists = extend_persistent(e, &DataKey::Ticket(token_id));
+    let owner_key = NFTStorageKey::Owner(token_id);
+    if e.storage().persistent().has(&owner_key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&owner_key, TTL_PERSISTENT, TTL_PERSISTENT);
+    }
+    exists
+}
@@ -1,33 +1,293 @@
-use soroban_sdk::{contracttype, Address, String, Symbol};
+//! storage_types.rs — Storage keys and the records stored under them
+//!
+//! Records are stored as `contracttype` maps keyed by field name, so adding
+//! a field changes how they decode.  `Tier`, `Ticket`, `PricingConfig` and
+//! `EventInfo` gained fields since the first release, and entries written by
+//! contracts deployed before then do not decode with these definitions;
+//! such events are redeployed (see the crate docs).
+use soroban_sdk::{contracttype, Address, BytesN, String, Symbol, Vec};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DataKey {
     Admin,
     EventInfo,
-    TokenIdCounter,
     Tier(Symbol),
+    TierIndex,
     Ticket(u32),
+    TierTicketCount(Symbol),
+    TierTicket(Symbol, u32),
+    TierTicketIndex(u32),
     PricingConfig,
+    AuctionClearingPrice(Symbol),
+    IdentityRegistry,
+    Lottery(Symbol),
+    LotteryEntrant(Symbol, u32),
+    LotteryEntry(Symbol, Address),
+    LotteryDid(Symbol, String),
+    ExperimentCounter,
+    Experiment(Symbol),
+    ExperimentExposure(u32, Address),
+    VariantStats(u32, u32),
+    PromoCode(BytesN<32>),
+    LastGoodMultiplier,
+    PriceObservations,
+    TwapMultiplier,
+    CachedMultiplier,
+    PaymentAsset(Address, Symbol),
+    PaymentRate(Address, Symbol),
+    TierUri(Symbol),
+    Session(Symbol),
+    SessionIndex,
+    TierSessions(Symbol),
+    SessionCheckIn(Symbol, u32),
+    AddOn(Symbol),
+    AddOnIndex,
+    TicketAddOn(u32, Symbol),
+    TicketAddOns(u32),
+    Payees,
+    PlatformFee,
+    Revenue(Address),
+    RevenueTokens,
+    Withdrawn(Address, Address),
+    PlatformWithdrawn(Address),
+    Badge(u32),
+    HolderBadges(Address),
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum PricingStrategy {
-    Standard,  // Normal demand-curve
-    TimeDecay, // Decreases over time
-    AbTestA,   // High floor
-    AbTestB,   // Higher sensitivity
+    Standard,                     // Normal demand-curve
+    TimeDecay(TimeDecaySchedule), // Early-bird phases, optional surge
+    AbTestA,                      // Higher demand sensitivity; use as an experiment variant
+    AbTestB,                      // Higher floor; use as an experiment variant
+    BondingCurve(BondingCurve),   // Continuous supply curve
+    DutchAuction(DutchAuction),   // Descending price between two times
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DiscountPhase {
+    /// Phase applies to purchases strictly before this timestamp.
+    pub until: u64,
+    pub discount_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TimeDecaySchedule {
+    /// Early-bird phases sorted by ascending `until`; the first phase that
+    /// has not ended yet applies.
+    pub phases: Vec<DiscountPhase>,
+    /// Last-minute surge window before `EventInfo::start_time`; 0 disables it.
+    pub surge_window_seconds: u64,
+    pub surge_premium_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PricePoint {
+    /// Timestamp from which `price` applies.
+    pub from: u64,
+    pub price: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CurveShape {
+    Linear,      // Constant slope
+    Exponential, // Slow start, steep finish
+    Sigmoid,     // Flat at both ends, steep around half supply
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BondingCurve {
+    pub shape: CurveShape,
+    /// Price increase reached at sell-out, in bps of `base_price` (5000 = +50%).
+    pub max_increase_bps: u32,
+    /// Curvature for `Exponential` and `Sigmoid`, in thousandths (1000 = 1.0).
+    /// Ignored for `Linear`; 0 degrades the other shapes to linear.
+    pub steepness: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DutchAuction {
+    /// Price at `start_time` (the ceiling).
+    pub start_price: i128,
+    /// Price reached at `end_time` and never undercut.
+    pub floor_price: i128,
+    pub start_time: u64,
+    pub end_time: u64,
+    /// The price drops once per step; 0 decays every second.
+    pub step_seconds: u64,
+    /// When set, every buyer ends up paying the final (lowest) sale price and
+    /// early buyers can claim the difference once the auction settles.
+    pub uniform_clearing: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Lottery {
+    /// Token every entrant deposits `price` in.
+    pub payment_token: Address,
+    /// Flat ticket price, escrowed by the contract until the draw.
+    pub price: i128,
+    pub registration_start: u64,
+    pub registration_end: u64,
+    /// Entries accepted in total; `enter_lottery` fails once reached.
+    pub max_entrants: u32,
+    /// Last moment `draw_lottery` may pick winners.  After it, every entrant
+    /// not yet picked can reclaim their deposit if the draw never finished.
+    pub draw_deadline: u64,
+    pub entrants: u32,
+    /// Winners to pick, fixed by the first draw batch.
+    pub winners: u32,
+    /// Winners picked so far: the entrants at indices `0..picked`.
+    pub picked: u32,
+    /// Set once the last draw batch has picked every winner.
+    pub drawn: bool,
+    /// Losers before this index have been refunded by the draw.
+    pub refunded_until: u32,
+    /// Set once the draw has refunded every loser.
+    pub settled: bool,
+    /// Set by `cancel_lottery`; no further entries or draws.
+    pub cancelled: bool,
+}
+
+/// An entrant's position in a lottery.  Draw batches swap winners to the
+/// front, so `index` moves until the entrant is picked.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LotteryEntry {
+    pub index: u32,
+    /// Set once the draw or `claim_lottery_refund` returned the deposit.
+    pub refunded: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExperimentVariant {
+    pub strategy: PricingStrategy,
+    /// Share of buyers assigned to this variant, in bps.
+    pub weight_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Experiment {
+    /// Contract-wide experiment id; mixed into the assignment hash and used to
+    /// key stats so a rerun starts from clean counters.
+    pub id: u32,
+    pub variants: Vec<ExperimentVariant>,
+    pub active: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VariantStats {
+    /// Distinct buyers quoted a price under this variant.
+    pub exposures: u32,
+    /// Tickets sold under this variant.
+    pub conversions: u32,
+    pub revenue: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PromoDiscount {
+    Bps(u32),    // Percentage of the ticket price
+    Fixed(i128), // Flat amount off in the tier's price unit, never below zero
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PromoCode {
+    pub discount: PromoDiscount,
+    pub max_redemptions: u32,
+    pub redemptions: u32,
+    /// Tiers the code applies to; empty means every tier.
+    pub tiers: Vec<Symbol>,
+    /// Last timestamp at which the code can be redeemed.
+    pub expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OracleKind {
+    Dia,          // get_value(pair) -> (price, timestamp)
+    Dex,          // get_spot_price(pair) -> price
+    Sep40(Asset), // lastprice(asset) -> Option<PriceData>, e.g. Reflector
+}
+
+/// Asset identifier of the SEP-40 oracle interface.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Asset {
+    Stellar(Address), // Stellar asset contract
+    Other(Symbol),    // Off-chain ticker, e.g. "XLM"
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleSource {
+    pub kind: OracleKind,
+    pub address: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StalePricePolicy {
+    Neutral,       // Sell at unadjusted prices (1x multiplier)
+    LastKnownGood, // Reuse the last multiplier computed from a live price
+    HaltSales,     // Refuse to price tickets until an oracle recovers
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LastGoodMultiplier {
+    pub multiplier: i128,
+    /// Ledger timestamp at which the multiplier was computed.
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceObservation {
+    /// Raw price, normalised to 8 decimals.
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+/// How a payment token is priced in a fiat currency.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentAsset {
+    pub oracle_kind: OracleKind,
+    pub oracle_address: Address,
+    /// Pair quoting the token in the fiat currency, e.g. "XLM/USD".
+    pub oracle_pair: String,
+    /// Decimals of the payment token (7 for Stellar assets).
+    pub token_decimals: u32,
+    /// Largest move (bps) of the live rate away from the quoted rate
+    /// before the live rate replaces the quote.
+    pub max_slippage_bps: u32,
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct PricingConfig {
+    /// Interface spoken by `oracle_address`.
+    pub oracle_kind: OracleKind,
     pub oracle_address: Address,
     pub dex_pool_address: Address, // Fallback
     pub price_floor: i128,
     pub price_ceiling: i128,
-    pub update_frequency: u64, // Seconds
+    /// Seconds a cached oracle multiplier stays valid.
+    pub update_frequency: u64,
+    /// When the cached multiplier was last refreshed.
     pub last_update_time: u64,
     pub is_frozen: bool,
     /// Asset pair string to query the oracle, e.g. "XLM/USD".
@@ -37,6 +297,21 @@ pub struct PricingConfig {
     pub oracle_reference_price: i128,
     /// How old an oracle price can be (seconds) before we fall back to the DEX.
     pub max_oracle_age_seconds: u64,
+    /// Price sources aggregated by median.  When empty, the single
+    /// `oracle_address` / `dex_pool_address` fallback pair is used instead.
+    pub oracle_sources: Vec<OracleSource>,
+    /// Sources further than this (bps) from the median are discarded.
+    pub max_deviation_bps: u32,
+    /// Minimum number of agreeing sources needed to apply a multiplier.
+    pub min_sources: u32,
+    /// What to do when no oracle or DEX price is usable.
+    pub stale_policy: StalePricePolicy,
+    /// Number of price observations averaged into the TWAP.  0 disables
+    /// smoothing and prices tickets from the live oracle reading.
+    pub twap_window: u32,
+    /// Largest change (bps) of the smoothed multiplier per observation.
+    /// 0 leaves the change unbounded.
+    pub max_change_bps: u32,
 }
 
 #[contracttype]
@@ -44,6 +319,8 @@ pub struct PricingConfig {
 pub struct EventInfo {
     pub start_time: u64,
     pub refund_cutoff_time: u64,
+    /// When the event is over; 0 until the organizer sets it.
+    pub end_time: u64,
 }
 
 #[contracttype]
@@ -56,6 +333,13 @@ pub struct Tier {
     pub minted: u32,
     pub active: bool,
     pub strategy: PricingStrategy,
+    /// Fiat currency (e.g. "USD") the prices are quoted in, in hundredths.
+    /// `None` means prices are plain payment token amounts.
+    pub currency: Option<Symbol>,
+    /// Purchases open at this timestamp; 0 means as soon as the tier exists.
+    pub sale_start: u64,
+    /// Purchases close after this timestamp; 0 means no end.
+    pub sale_end: u64,
 }
 
 #[contracttype]
@@ -65,4 +349,115 @@ pub struct Ticket {
     pub purchase_time: u64,
     pub price_paid: i128,
     pub is_valid: bool,
+    /// Experiment the ticket was sold under, if any.  Ids are contract-wide,
+    /// so this stays meaningful after the tier's experiment is replaced.
+    pub experiment_id: Option<u32>,
+    /// Variant index within `experiment_id`; set together with it.
+    pub variant: Option<u32>,
+    /// When the holder was admitted at the door.
+    pub checked_in_at: Option<u64>,
+    /// Who paid for the ticket and receives refunds and rebates.
+    pub payer: Address,
+    /// Token the price was paid in; `None` for free tickets.
+    pub payment_token: Option<Address>,
+    /// Set once `assign_attendee` has moved the ticket.
+    pub attendee_assigned: bool,
+}
+
+/// A day or workshop within the event that tiers can grant access to.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Session {
+    pub start_time: u64,
+    pub end_time: u64,
+    /// Most tickets that may grant this session; 0 means unlimited.
+    pub capacity: u32,
+    /// Live tickets granting this session.
+    pub sold: u32,
+    pub checked_in: u32,
+}
+
+/// A product sold with tickets, e.g. merch, parking or a workshop seat.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AddOn {
+    pub name: String,
+    pub payment_token: Address,
+    /// Unit price in `payment_token`.
+    pub price: i128,
+    /// Units that may be sold; 0 means unlimited.
+    pub stock: u32,
+    /// Units sold and not refunded.
+    pub sold: u32,
+    /// Only tickets of this tier may buy the add-on.
+    pub required_tier: Option<Symbol>,
+    pub active: bool,
+}
+
+/// Units of one add-on attached to one ticket.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TicketAddOn {
+    /// Who paid, and receives the refund if the ticket is refunded.
+    pub buyer: Address,
+    pub quantity: u32,
+    pub redeemed: u32,
+    /// Total paid for the units not yet refunded.
+    pub amount_paid: i128,
+}
+
+/// A recipient of sale proceeds.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Payee {
+    pub recipient: Address,
+    /// Share of the proceeds after the platform fee, in bps.
+    pub share_bps: u32,
+}
+
+/// Fee kept by the platform that deployed the event, e.g. the factory.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlatformFee {
+    /// Only this address may change the fee.
+    pub authority: Address,
+    pub recipient: Address,
+    pub fee_bps: u32,
+}
+
+/// Proceeds received in one payment token.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Revenue {
+    /// Ticket, lottery and add-on sales.
+    pub gross: i128,
+    /// Refunds and rebates paid back out of `gross`.
+    pub refunded: i128,
+}
+
+/// Lifecycle of a ticket as shown to wallets.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TicketStatus {
+    Valid,
+    CheckedIn,
+    Refunded,
+    /// Checked in and the event has ended.
+    Attended,
+}
+
+/// Non-transferable proof of attendance, claimed from a checked-in ticket
+/// once the event has ended.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttendanceBadge {
+    pub holder: Address,
+    pub token_id: u32,
+    pub tier: Symbol,
+    /// Collection name of the event.
+    pub event_name: String,
+    pub event_start: u64,
+    pub event_end: u64,
+    pub checked_in_at: u64,
+    pub claimed_at: u64,
 }
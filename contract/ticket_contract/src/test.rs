@@ -2,7 +2,28 @@
 extern crate std;
 
 use super::*;
-use soroban_sdk::{contract, contractimpl, testutils::Address as _, Address, Env, String, Symbol};
+use events::{
+    AttendeeAssigned, BadgeClaimed, Frozen, OracleFallback, PricingChanged, PromoRedeemed,
+    PromoRegistered, Repriced, TicketCheckedIn, TicketPurchased, TicketRefunded, TierAdded,
+    TierUpdated,
+};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, symbol_short, testutils::Address as _, Address, Env,
+    Map, String, Symbol,
+};
+use soroban_sdk::{
+    testutils::{
+        storage::{Instance as _, Persistent as _},
+        AuthorizedFunction, AuthorizedInvocation, Events, Ledger, MockAuth, MockAuthInvoke,
+    },
+    token, Event, FromVal, IntoVal, InvokeError, Val,
+};
+use stellar_tokens::non_fungible::NFTStorageKey;
+use storage_types::{
+    Asset, BondingCurve, CurveShape, DiscountPhase, DutchAuction, ExperimentVariant, OracleKind,
+    OracleSource, PaymentAsset, PriceObservation, PricePoint, PromoDiscount, StalePricePolicy,
+    TimeDecaySchedule,
+};
 
 // ---------------------------------------------------------------------------
 // Mock Oracle Contract
@@ -39,6 +60,173 @@ impl MockDex {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Configurable Mock Price Feed
+//
+// Serves both the DIA and the DEX interface from a stored price, so tests can
+// line up several sources that agree or disagree.  The timestamp defaults to
+// the current ledger time (fresh) unless set explicitly.
+// ---------------------------------------------------------------------------
+#[contract]
+pub struct MockPriceFeed;
+
+#[contractimpl]
+impl MockPriceFeed {
+    pub fn set_price(env: Env, price: i128) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("price"), &price);
+    }
+
+    pub fn set_timestamp(env: Env, timestamp: u64) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("ts"), &timestamp);
+    }
+
+    pub fn get_value(env: Env, _pair: String) -> (i128, u64) {
+        let price: i128 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("price"))
+            .unwrap();
+        let timestamp: u64 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("ts"))
+            .unwrap_or(env.ledger().timestamp());
+        (price, timestamp)
+    }
+
+    pub fn get_spot_price(env: Env, _pair: String) -> i128 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("price"))
+            .unwrap()
+    }
+}
+
+/// `event` in the shape `e.events().all()` reports it for `contract`.
+fn published(
+    e: &Env,
+    contract: &Address,
+    event: &impl Event,
+) -> (Address, soroban_sdk::Vec<Val>, Val) {
+    (contract.clone(), event.topics(e), event.data(e))
+}
+
+fn price_feed(e: &Env, price: i128) -> Address {
+    let id = e.register(MockPriceFeed, ());
+    MockPriceFeedClient::new(e, &id).set_price(&price);
+    id
+}
+
+fn source_config(
+    e: &Env,
+    admin: &Address,
+    sources: soroban_sdk::Vec<OracleSource>,
+    max_deviation_bps: u32,
+    min_sources: u32,
+) -> PricingConfig {
+    PricingConfig {
+        oracle_kind: OracleKind::Dia,
+        oracle_address: admin.clone(),
+        dex_pool_address: admin.clone(),
+        price_floor: 0,
+        price_ceiling: i128::MAX,
+        update_frequency: 0,
+        last_update_time: e.ledger().timestamp(),
+        is_frozen: false,
+        oracle_pair: String::from_str(e, "XLM/USD"),
+        oracle_reference_price: oracle::DIA_ORACLE_DECIMALS,
+        max_oracle_age_seconds: oracle::DEFAULT_STALENESS_SECONDS,
+        oracle_sources: sources,
+        max_deviation_bps,
+        min_sources,
+        stale_policy: StalePricePolicy::Neutral,
+        twap_window: 0,
+        max_change_bps: 0,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Mock SEP-40 Oracle Contract
+//
+// Reflector-style feed: 14 decimals, only knows the assets it was given a
+// price for.
+// ---------------------------------------------------------------------------
+#[contract]
+pub struct MockSep40Oracle;
+
+#[contractimpl]
+impl MockSep40Oracle {
+    pub fn set_price(env: Env, asset: Asset, price: i128, timestamp: u64) {
+        env.storage()
+            .persistent()
+            .set(&asset, &oracle::PriceData { price, timestamp });
+    }
+
+    pub fn lastprice(env: Env, asset: Asset) -> Option<oracle::PriceData> {
+        env.storage().persistent().get(&asset)
+    }
+
+    pub fn decimals(_env: Env) -> u32 {
+        14
+    }
+
+    pub fn resolution(_env: Env) -> u32 {
+        300
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Mock Identity Registry Contract
+//
+// Maps addresses to DIDs set up by the test; two addresses can share a DID
+// to simulate one person entering with several wallets.  Attendance counts
+// once per DID, like the real registry.
+// ---------------------------------------------------------------------------
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MockRegistryError {
+    AttendanceAlreadyRecorded = 1,
+}
+
+#[contract]
+pub struct MockIdentityRegistry;
+
+#[contractimpl]
+impl MockIdentityRegistry {
+    pub fn set_did(env: Env, address: Address, did: String) {
+        env.storage().persistent().set(&address, &did);
+    }
+
+    pub fn get_did_by_address(env: Env, address: Address) -> Option<String> {
+        env.storage().persistent().get(&address)
+    }
+
+    pub fn record_attendance(
+        env: Env,
+        event: Address,
+        did: String,
+    ) -> Result<(), MockRegistryError> {
+        event.require_auth();
+        if env
+            .storage()
+            .persistent()
+            .has(&(event.clone(), did.clone()))
+        {
+            return Err(MockRegistryError::AttendanceAlreadyRecorded);
+        }
+        env.storage().persistent().set(&(event, did), &true);
+        Ok(())
+    }
+
+    pub fn has_attended(env: Env, event: Address, did: String) -> bool {
+        env.storage().persistent().has(&(event, did))
+    }
+}
+
 fn create_contract(e: &Env, admin: &Address) -> SoulboundTicketContractClient<'static> {
     let contract_id = e.register(SoulboundTicketContract, ());
     let client = SoulboundTicketContractClient::new(e, &contract_id);
@@ -54,6 +242,48 @@ fn create_contract(e: &Env, admin: &Address) -> SoulboundTicketContractClient<'s
     client
 }
 
+fn create_payment_token<'a>(e: &Env, admin: &Address) -> (Address, token::StellarAssetClient<'a>) {
+    let sac = e.register_stellar_asset_contract_v2(admin.clone());
+    let address = sac.address();
+    (address.clone(), token::StellarAssetClient::new(e, &address))
+}
+
+/// A fresh contract selling one tier for a fresh payment token.
+struct Sale<'a> {
+    client: SoulboundTicketContractClient<'a>,
+    admin: Address,
+    token_id: Address,
+    token_admin: token::StellarAssetClient<'a>,
+    tier: Symbol,
+}
+
+fn setup_sale<'a>(
+    e: &Env,
+    tier: &str,
+    base_price: i128,
+    max_supply: u32,
+    strategy: PricingStrategy,
+) -> Sale<'a> {
+    let admin = Address::generate(e);
+    let client = create_contract(e, &admin);
+    let (token_id, token_admin) = create_payment_token(e, &admin);
+    let tier = Symbol::new(e, tier);
+    client.add_tier(
+        &tier,
+        &String::from_str(e, "General"),
+        &base_price,
+        &max_supply,
+        &strategy,
+    );
+    Sale {
+        client,
+        admin,
+        token_id,
+        token_admin,
+        tier,
+    }
+}
+
 #[test]
 fn test_initialize_and_tier_creation() {
     let e = Env::default();
@@ -102,7 +332,7 @@ fn test_batch_mint() {
 }
 
 #[test]
-#[should_panic(expected = "Soulbound: Tickets cannot be transferred")]
+#[should_panic(expected = "Error(Contract, #96)")]
 fn test_soulbound_restriction() {
     let e = Env::default();
     e.mock_all_auths();
@@ -215,6 +445,7 @@ fn test_emergency_freeze_and_bounds() {
     );
 
     let config = PricingConfig {
+        oracle_kind: OracleKind::Dia,
         oracle_address: admin.clone(), // admin == neutral (no oracle configured), returns ORACLE_PRECISION
         dex_pool_address: admin.clone(),
         price_floor: 50,
@@ -225,6 +456,12 @@ fn test_emergency_freeze_and_bounds() {
         oracle_pair: String::from_str(&e, "XLM/USD"),
         oracle_reference_price: oracle::DIA_ORACLE_DECIMALS,
         max_oracle_age_seconds: oracle::DEFAULT_STALENESS_SECONDS,
+        oracle_sources: soroban_sdk::vec![&e],
+        max_deviation_bps: 500,
+        min_sources: 1,
+        stale_policy: StalePricePolicy::Neutral,
+        twap_window: 0,
+        max_change_bps: 0,
     };
     client.set_pricing_config(&config);
 
@@ -286,6 +523,7 @@ fn test_oracle_multiplier_integration() {
 
     // Point the PricingConfig at the mock oracle
     let config = PricingConfig {
+        oracle_kind: OracleKind::Dia,
         oracle_address: oracle_id.clone(),
         dex_pool_address: dex_id.clone(),
         price_floor: 0,
@@ -297,6 +535,12 @@ fn test_oracle_multiplier_integration() {
         // $1.00 baseline in 8-decimal format
         oracle_reference_price: oracle::DIA_ORACLE_DECIMALS,
         max_oracle_age_seconds: oracle::DEFAULT_STALENESS_SECONDS,
+        oracle_sources: soroban_sdk::vec![&e],
+        max_deviation_bps: 500,
+        min_sources: 1,
+        stale_policy: StalePricePolicy::Neutral,
+        twap_window: 0,
+        max_change_bps: 0,
     };
     client.set_pricing_config(&config);
 
@@ -341,3 +585,4064 @@ fn test_oracle_fallback_neutral_when_unconfigured() {
     // No oracle configured → price should equal base price
     assert_eq!(client.get_ticket_price(&tier_sym), 200);
 }
+
+fn curve(shape: CurveShape, max_increase_bps: u32, steepness: u32) -> BondingCurve {
+    BondingCurve {
+        shape,
+        max_increase_bps,
+        steepness,
+    }
+}
+
+/// Every curve shape must be non-decreasing in `minted`, start at the base
+/// price and end exactly at base price + `max_increase_bps`.
+#[test]
+fn test_bonding_curve_monotonic_and_bounded() {
+    let shapes = [
+        (CurveShape::Linear, 0),
+        (CurveShape::Linear, 5_000),
+        (CurveShape::Exponential, 1_000),
+        (CurveShape::Exponential, pricing::MAX_CURVE_STEEPNESS),
+        (CurveShape::Sigmoid, 8_000),
+        (CurveShape::Sigmoid, pricing::MAX_CURVE_STEEPNESS),
+    ];
+    let base = 1_000_000;
+    let max_supply = 200;
+
+    for (shape, steepness) in shapes.iter() {
+        let c = curve(shape.clone(), 5_000, *steepness);
+        let mut last = pricing::bonding_curve_price(base, 0, max_supply, &c);
+        assert_eq!(last, base);
+
+        for minted in 1..=max_supply {
+            let price = pricing::bonding_curve_price(base, minted, max_supply, &c);
+            assert!(price >= last, "curve {:?} decreased at {}", shape, minted);
+            assert!(price <= base * 3 / 2);
+            last = price;
+        }
+        assert_eq!(last, base * 3 / 2);
+    }
+}
+
+#[test]
+fn test_bonding_curve_shapes_differ_at_midpoint() {
+    let base = 1_000_000;
+    let linear = curve(CurveShape::Linear, 10_000, 0);
+    let exp = curve(CurveShape::Exponential, 10_000, 3_000);
+    let sig = curve(CurveShape::Sigmoid, 10_000, 10_000);
+
+    let lin_mid = pricing::bonding_curve_price(base, 50, 100, &linear);
+    assert_eq!(lin_mid, 1_500_000);
+    // Exponential lags behind linear early on
+    assert!(pricing::bonding_curve_price(base, 50, 100, &exp) < lin_mid);
+    // Sigmoid is symmetric around half supply
+    assert_eq!(pricing::bonding_curve_price(base, 50, 100, &sig), lin_mid);
+    assert!(pricing::bonding_curve_price(base, 10, 100, &sig) < 1_100_000);
+    assert!(pricing::bonding_curve_price(base, 90, 100, &sig) > 1_900_000);
+}
+
+#[test]
+fn test_bonding_curve_extreme_inputs_do_not_overflow() {
+    let c = curve(
+        CurveShape::Exponential,
+        u32::MAX,
+        pricing::MAX_CURVE_STEEPNESS,
+    );
+    assert_eq!(
+        pricing::bonding_curve_price(i128::MAX, u32::MAX, u32::MAX, &c),
+        i128::MAX
+    );
+    assert_eq!(
+        pricing::bonding_curve_price(i128::MAX / 2, 1, u32::MAX, &c),
+        i128::MAX / 2
+    );
+    assert_eq!(pricing::mul_div(i128::MAX, 3, 4), i128::MAX / 4 * 3 + 2);
+}
+
+#[test]
+fn test_bonding_curve_tier_pricing() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    let tier_sym = Symbol::new(&e, "CURVE");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Curve"),
+        &1000,
+        &10,
+        &PricingStrategy::BondingCurve(curve(CurveShape::Linear, 10_000, 0)),
+    );
+
+    assert_eq!(client.get_ticket_price(&tier_sym), 1000);
+
+    // No cliffs: every single sale moves the price by 10% of base
+    client.batch_mint(&user, &tier_sym, &1);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1100);
+    client.batch_mint(&user, &tier_sym, &4);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1500);
+}
+
+#[test]
+fn test_saturated_bonding_curve_reports_price_overflow() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    // Small enough to survive the neutral oracle multiplier on its own
+    let base = i128::MAX / 10_000;
+    let tier_sym = Symbol::new(&e, "CURVE");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Curve"),
+        &base,
+        &2,
+        &PricingStrategy::BondingCurve(curve(CurveShape::Linear, u32::MAX, 0)),
+    );
+    assert_eq!(client.get_ticket_price(&tier_sym), base);
+
+    // The curve saturates at i128::MAX; the multiplier must not wrap it
+    client.batch_mint(&user, &tier_sym, &1);
+    assert_eq!(
+        client.try_get_ticket_price(&tier_sym),
+        Err(Ok(Error::PriceOverflow))
+    );
+    assert_eq!(
+        client.try_purchase(&user, &Address::generate(&e), &tier_sym, &None, &None),
+        Err(Ok(Error::PriceOverflow))
+    );
+}
+
+#[test]
+fn test_bonding_curve_rejects_excessive_steepness() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    let tier_sym = Symbol::new(&e, "CURVE");
+    let res = client.try_add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Curve"),
+        &1000,
+        &10,
+        &PricingStrategy::BondingCurve(curve(
+            CurveShape::Sigmoid,
+            10_000,
+            pricing::MAX_CURVE_STEEPNESS + 1,
+        )),
+    );
+    assert_eq!(res, Err(Ok(Error::CurveTooSteep)));
+}
+
+fn auction(uniform_clearing: bool) -> DutchAuction {
+    DutchAuction {
+        start_price: 1000,
+        floor_price: 400,
+        start_time: 1_000,
+        end_time: 2_000,
+        step_seconds: 100,
+        uniform_clearing,
+    }
+}
+
+#[test]
+fn test_dutch_auction_price_decay() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    let tier_sym = Symbol::new(&e, "PREMIUM");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Premium"),
+        &0,
+        &10,
+        &PricingStrategy::DutchAuction(auction(false)),
+    );
+
+    e.ledger().set_timestamp(500);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1000);
+    // 150s in: only one 100s step has elapsed → 1000 - 600 * 100 / 1000
+    e.ledger().set_timestamp(1_150);
+    assert_eq!(client.get_ticket_price(&tier_sym), 940);
+    e.ledger().set_timestamp(1_500);
+    assert_eq!(client.get_ticket_price(&tier_sym), 700);
+    e.ledger().set_timestamp(2_000);
+    assert_eq!(client.get_ticket_price(&tier_sym), 400);
+    e.ledger().set_timestamp(5_000);
+    assert_eq!(client.get_ticket_price(&tier_sym), 400);
+}
+
+#[test]
+fn test_dutch_auction_uniform_clearing_rebate() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let early = Address::generate(&e);
+    let late = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    let token_client = token::Client::new(&e, &token_id);
+    token_admin.mint(&early, &10_000);
+    token_admin.mint(&late, &10_000);
+
+    let tier_sym = Symbol::new(&e, "PREMIUM");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Premium"),
+        &0,
+        &2,
+        &PricingStrategy::DutchAuction(auction(true)),
+    );
+
+    e.ledger().set_timestamp(1_000);
+    client.purchase(&early, &token_id, &tier_sym, &None, &None);
+    assert_eq!(client.get_clearing_price(&tier_sym), None);
+
+    e.ledger().set_timestamp(1_500);
+    let late_ticket = client.purchase(&late, &token_id, &tier_sym, &None, &None);
+
+    // Sold out: auction settles at the last (lowest) sale price
+    assert_eq!(client.get_clearing_price(&tier_sym), Some(700));
+    assert_eq!(token_client.balance(&early), 9_000);
+
+    client.claim_auction_rebate(&early, &token_id, &1);
+    assert_eq!(token_client.balance(&early), 9_300);
+    assert_eq!(client.get_ticket(&1).price_paid, 700);
+
+    // Rebates are paid once; the late buyer already paid the clearing price
+    assert_eq!(
+        client.try_claim_auction_rebate(&early, &token_id, &1),
+        Err(Ok(Error::NoRebateDue))
+    );
+    assert_eq!(
+        client.try_claim_auction_rebate(&late, &token_id, &late_ticket),
+        Err(Ok(Error::NoRebateDue))
+    );
+}
+
+#[test]
+fn test_dutch_auction_rebate_requires_settlement() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+
+    let tier_sym = Symbol::new(&e, "PREMIUM");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Premium"),
+        &0,
+        &10,
+        &PricingStrategy::DutchAuction(auction(true)),
+    );
+
+    e.ledger().set_timestamp(1_200);
+    let ticket_id = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    assert_eq!(
+        client.try_claim_auction_rebate(&buyer, &token_id, &ticket_id),
+        Err(Ok(Error::AuctionNotSettled))
+    );
+}
+
+#[test]
+fn test_dutch_auction_rebates_close_before_withdrawals() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let early = Address::generate(&e);
+    let late = Address::generate(&e);
+    let payee = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    let token_client = token::Client::new(&e, &token_id);
+    token_admin.mint(&early, &10_000);
+    token_admin.mint(&late, &10_000);
+    client.set_payees(&soroban_sdk::vec![
+        &e,
+        Payee {
+            recipient: payee.clone(),
+            share_bps: 10_000,
+        },
+    ]);
+
+    // An auction still running after the refund cutoff could owe rebates
+    // once proceeds are gone
+    let mut late_auction = auction(true);
+    late_auction.end_time = client.get_event_info().refund_cutoff_time + 1;
+    let tier_sym = Symbol::new(&e, "PREMIUM");
+    assert_eq!(
+        client.try_add_tier(
+            &tier_sym,
+            &String::from_str(&e, "Premium"),
+            &0,
+            &10,
+            &PricingStrategy::DutchAuction(late_auction),
+        ),
+        Err(Ok(Error::AuctionEndsAfterRefundCutoff))
+    );
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Premium"),
+        &0,
+        &10,
+        &PricingStrategy::DutchAuction(auction(true)),
+    );
+
+    e.ledger().set_timestamp(1_000);
+    let early_ticket = client.purchase(&early, &token_id, &tier_sym, &None, &None);
+    e.ledger().set_timestamp(1_500);
+    client.purchase(&late, &token_id, &tier_sym, &None, &None);
+
+    // The early buyer never claims their 300 rebate before the cutoff
+    e.ledger()
+        .set_timestamp(client.get_event_info().refund_cutoff_time + 1);
+    assert_eq!(client.get_clearing_price(&tier_sym), Some(700));
+    assert_eq!(client.withdraw(&payee, &token_id), 1_700);
+    assert_eq!(token_client.balance(&client.address), 0);
+    assert_eq!(
+        client.try_claim_auction_rebate(&early, &token_id, &early_ticket),
+        Err(Ok(Error::RefundWindowClosed))
+    );
+    assert_eq!(token_client.balance(&early), 9_000);
+}
+
+#[test]
+fn test_dutch_auction_rejects_sales_after_end() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+
+    let tier_sym = Symbol::new(&e, "PREMIUM");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Premium"),
+        &0,
+        &10,
+        &PricingStrategy::DutchAuction(auction(false)),
+    );
+
+    e.ledger().set_timestamp(999);
+    assert_eq!(
+        client.try_purchase(&buyer, &token_id, &tier_sym, &None, &None),
+        Err(Ok(Error::AuctionNotStarted))
+    );
+    e.ledger().set_timestamp(2_001);
+    assert_eq!(
+        client.try_purchase(&buyer, &token_id, &tier_sym, &None, &None),
+        Err(Ok(Error::AuctionEnded))
+    );
+}
+
+fn setup_lottery<'a>(
+    e: &Env,
+    max_supply: u32,
+) -> (
+    SoulboundTicketContractClient<'a>,
+    Address,
+    Address,
+    token::StellarAssetClient<'a>,
+    Symbol,
+) {
+    let sale = setup_sale(e, "HOT", 100, max_supply, PricingStrategy::Standard);
+    sale.client.configure_lottery(
+        &sale.tier,
+        &sale.token_id,
+        &100,
+        &1_000,
+        &2_000,
+        &10,
+        &3_000,
+    );
+    (
+        sale.client,
+        sale.admin,
+        sale.token_id,
+        sale.token_admin,
+        sale.tier,
+    )
+}
+
+#[test]
+fn test_lottery_configuration_errors() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, _admin, token_id, _token_admin, tier_sym) = setup_lottery(&e, 10);
+
+    assert_eq!(
+        client.try_configure_lottery(&tier_sym, &token_id, &100, &1_000, &2_000, &10, &3_000),
+        Err(Ok(LotteryError::LotteryExists))
+    );
+
+    let other_sym = Symbol::new(&e, "WARM");
+    assert_eq!(
+        client.try_get_lottery(&other_sym),
+        Err(Ok(LotteryError::LotteryNotFound))
+    );
+    assert_eq!(
+        client.try_enter_lottery(&Address::generate(&e), &other_sym),
+        Err(Ok(LotteryError::LotteryNotFound))
+    );
+    assert_eq!(
+        client.try_draw_lottery(&other_sym, &10),
+        Err(Ok(LotteryError::LotteryNotFound))
+    );
+
+    client.add_tier(
+        &other_sym,
+        &String::from_str(&e, "Warm Tier"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    for (price, start, end, max_entrants, deadline) in [
+        (-1, 1_000, 2_000, 10, 3_000),
+        (100, 2_000, 2_000, 10, 3_000),
+        (100, 1_000, 2_000, 10, 2_000),
+        (100, 1_000, 2_000, 0, 3_000),
+    ] {
+        assert_eq!(
+            client.try_configure_lottery(
+                &other_sym,
+                &token_id,
+                &price,
+                &start,
+                &end,
+                &max_entrants,
+                &deadline
+            ),
+            Err(Ok(LotteryError::InvalidLottery))
+        );
+    }
+}
+
+#[test]
+fn test_lottery_draw_mints_winners_and_refunds_losers() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, admin, token_id, token_admin, tier_sym) = setup_lottery(&e, 2);
+    let token_client = token::Client::new(&e, &token_id);
+
+    e.ledger().set_timestamp(1_500);
+    let mut entrants = std::vec::Vec::new();
+    for _ in 0..5 {
+        let entrant = Address::generate(&e);
+        token_admin.mint(&entrant, &100);
+        client.enter_lottery(&entrant, &tier_sym);
+        assert_eq!(token_client.balance(&entrant), 0);
+        entrants.push(entrant);
+    }
+    assert_eq!(token_client.balance(&client.address), 500);
+    assert_eq!(client.get_lottery_entrants(&tier_sym, &0, &10).len(), 5);
+    assert_eq!(
+        client.try_claim_lottery_refund(&entrants[0], &tier_sym),
+        Err(Ok(LotteryError::RefundUnavailable))
+    );
+
+    e.ledger().set_timestamp(2_001);
+    client.draw_lottery(&tier_sym, &10);
+    assert_eq!(
+        e.auths()[0],
+        (
+            admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    client.address.clone(),
+                    Symbol::new(&e, "draw_lottery"),
+                    (tier_sym.clone(), 10u32).into_val(&e),
+                )),
+                sub_invocations: std::vec![],
+            }
+        )
+    );
+    let lottery = client.get_lottery(&tier_sym);
+    assert!(lottery.drawn && lottery.settled);
+    assert_eq!(
+        client.try_draw_lottery(&tier_sym, &10),
+        Err(Ok(LotteryError::AlreadyDrawn))
+    );
+
+    // Winners were swapped to the front; losers got their deposit back
+    let page = client.get_lottery_entrants(&tier_sym, &0, &10);
+    assert_eq!(client.balance(&page.get(0).unwrap()), 1);
+    assert_eq!(client.balance(&page.get(1).unwrap()), 1);
+    for entrant in entrants.iter() {
+        let won = client.balance(entrant) == 1;
+        assert_eq!(token_client.balance(entrant), if won { 0 } else { 100 });
+        assert_eq!(
+            client.try_claim_lottery_refund(entrant, &tier_sym),
+            Err(Ok(if won {
+                LotteryError::RefundUnavailable
+            } else {
+                LotteryError::AlreadyRefunded
+            }))
+        );
+    }
+    // Winning deposits stay in escrow as sale proceeds
+    assert_eq!(token_client.balance(&admin), 0);
+    assert_eq!(token_client.balance(&client.address), 200);
+    assert_eq!(client.get_revenue(&token_id).gross, 200);
+    assert_eq!(client.get_ticket(&1).price_paid, 100);
+    assert_eq!(
+        client.try_claim_lottery_refund(&Address::generate(&e), &tier_sym),
+        Err(Ok(LotteryError::NotEntered))
+    );
+}
+
+#[test]
+fn test_lottery_draw_is_admin_only() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, _admin, _token_id, token_admin, tier_sym) = setup_lottery(&e, 1);
+
+    e.ledger().set_timestamp(1_500);
+    let entrant = Address::generate(&e);
+    token_admin.mint(&entrant, &100);
+    client.enter_lottery(&entrant, &tier_sym);
+
+    // An entrant signing for themselves cannot run the draw
+    e.ledger().set_timestamp(2_001);
+    e.mock_auths(&[MockAuth {
+        address: &entrant,
+        invoke: &MockAuthInvoke {
+            contract: &client.address,
+            fn_name: "draw_lottery",
+            args: (tier_sym.clone(), 1u32).into_val(&e),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(client.try_draw_lottery(&tier_sym, &1).is_err());
+    assert_eq!(client.get_lottery(&tier_sym).picked, 0);
+}
+
+#[test]
+fn test_lottery_draws_in_batches() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, _admin, token_id, token_admin, tier_sym) = setup_lottery(&e, 3);
+    let token_client = token::Client::new(&e, &token_id);
+
+    e.ledger().set_timestamp(1_500);
+    for _ in 0..6 {
+        let entrant = Address::generate(&e);
+        token_admin.mint(&entrant, &100);
+        client.enter_lottery(&entrant, &tier_sym);
+    }
+
+    e.ledger().set_timestamp(2_001);
+    assert_eq!(
+        client.try_draw_lottery(&tier_sym, &0),
+        Err(Ok(LotteryError::InvalidDrawBatch))
+    );
+    client.draw_lottery(&tier_sym, &2);
+    let lottery = client.get_lottery(&tier_sym);
+    assert_eq!((lottery.winners, lottery.picked), (3, 2));
+    assert!(!lottery.drawn);
+
+    // The last winner, then the first loser's refund
+    client.draw_lottery(&tier_sym, &2);
+    let lottery = client.get_lottery(&tier_sym);
+    assert_eq!((lottery.picked, lottery.refunded_until), (3, 4));
+    assert!(lottery.drawn && !lottery.settled);
+    assert_eq!(client.get_tier(&tier_sym).minted, 3);
+
+    // Refunds may finish after the draw deadline
+    e.ledger().set_timestamp(3_001);
+    client.draw_lottery(&tier_sym, &2);
+    assert!(client.get_lottery(&tier_sym).settled);
+
+    let page = client.get_lottery_entrants(&tier_sym, &0, &10);
+    for (index, entrant) in page.iter().enumerate() {
+        let won = index < 3;
+        assert_eq!(client.balance(&entrant), if won { 1 } else { 0 });
+        assert_eq!(token_client.balance(&entrant), if won { 0 } else { 100 });
+        let entry = client.get_lottery_entry(&tier_sym, &entrant).unwrap();
+        assert_eq!(entry.index, index as u32);
+        assert_eq!(entry.refunded, !won);
+    }
+}
+
+#[test]
+fn test_lottery_winners_limited_by_session_seats() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, _admin, token_id, token_admin, tier_sym) = setup_lottery(&e, 3);
+    let token_client = token::Client::new(&e, &token_id);
+
+    // The lottery tier and an open tier share a two-seat workshop
+    let workshop = Symbol::new(&e, "WORKSHOP");
+    client.add_session(&workshop, &5_000, &6_000, &2);
+    let open_sym = Symbol::new(&e, "OPEN");
+    client.add_tier(
+        &open_sym,
+        &String::from_str(&e, "Open"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    client.set_tier_sessions(&tier_sym, &soroban_sdk::vec![&e, workshop.clone()]);
+    client.set_tier_sessions(&open_sym, &soroban_sdk::vec![&e, workshop.clone()]);
+
+    e.ledger().set_timestamp(1_500);
+    let mut entrants = std::vec::Vec::new();
+    for _ in 0..3 {
+        let entrant = Address::generate(&e);
+        token_admin.mint(&entrant, &100);
+        client.enter_lottery(&entrant, &tier_sym);
+        entrants.push(entrant);
+    }
+
+    // A seat goes to the open tier after registration closes
+    e.ledger().set_timestamp(2_001);
+    let buyer = Address::generate(&e);
+    token_admin.mint(&buyer, &100);
+    client.purchase(&buyer, &token_id, &open_sym, &None, &None);
+
+    // Only one seat is left: one winner, and the draw still completes
+    client.draw_lottery(&tier_sym, &10);
+    let lottery = client.get_lottery(&tier_sym);
+    assert_eq!(lottery.winners, 1);
+    assert!(lottery.drawn && lottery.settled);
+    assert_eq!(client.get_session(&workshop).sold, 2);
+    let refunded = entrants
+        .iter()
+        .filter(|entrant| token_client.balance(entrant) == 100)
+        .count();
+    assert_eq!(refunded, 2);
+}
+
+#[test]
+fn test_lottery_entrants_capped() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, _admin, _token_id, token_admin, tier_sym) = setup_lottery(&e, 10);
+
+    e.ledger().set_timestamp(1_500);
+    let max_entrants = client.get_lottery(&tier_sym).max_entrants;
+    for _ in 0..max_entrants {
+        let entrant = Address::generate(&e);
+        token_admin.mint(&entrant, &100);
+        client.enter_lottery(&entrant, &tier_sym);
+    }
+    assert_eq!(client.get_lottery(&tier_sym).entrants, max_entrants);
+
+    let late = Address::generate(&e);
+    token_admin.mint(&late, &100);
+    assert_eq!(
+        client.try_enter_lottery(&late, &tier_sym),
+        Err(Ok(LotteryError::LotteryFull))
+    );
+
+    // The configured cap itself is bounded
+    let other_sym = Symbol::new(&e, "WARM");
+    client.add_tier(
+        &other_sym,
+        &String::from_str(&e, "Warm Tier"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    let token_id = client.get_lottery(&tier_sym).payment_token;
+    assert_eq!(
+        client.try_configure_lottery(&other_sym, &token_id, &100, &2_000, &2_500, &10_001, &3_000),
+        Err(Ok(LotteryError::InvalidLottery))
+    );
+}
+
+#[test]
+fn test_lottery_cancel_and_deadline_release_deposits() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, _admin, token_id, token_admin, tier_sym) = setup_lottery(&e, 10);
+    let token_client = token::Client::new(&e, &token_id);
+
+    // Cancelled during registration
+    e.ledger().set_timestamp(1_500);
+    let entrant = Address::generate(&e);
+    token_admin.mint(&entrant, &200);
+    client.enter_lottery(&entrant, &tier_sym);
+    assert_eq!(
+        client.try_purchase(&entrant, &token_id, &tier_sym, &None, &None),
+        Err(Ok(Error::TierAllocatedByLottery))
+    );
+    client.cancel_lottery(&tier_sym);
+    assert_eq!(
+        client.try_enter_lottery(&Address::generate(&e), &tier_sym),
+        Err(Ok(LotteryError::LotteryCancelled))
+    );
+    assert_eq!(client.claim_lottery_refund(&entrant, &tier_sym), 100);
+    assert_eq!(token_client.balance(&entrant), 200);
+    e.ledger().set_timestamp(2_001);
+    assert_eq!(
+        client.try_draw_lottery(&tier_sym, &10),
+        Err(Ok(LotteryError::LotteryCancelled))
+    );
+
+    // The tier is back on regular sale
+    let ticket = client.purchase(&entrant, &token_id, &tier_sym, &None, &None);
+    assert_eq!(client.owner_of(&ticket), entrant);
+    assert_eq!(token_client.balance(&entrant), 100);
+
+    // Never drawn: the deadline releases the deposit
+    let other_sym = Symbol::new(&e, "WARM");
+    client.add_tier(
+        &other_sym,
+        &String::from_str(&e, "Warm Tier"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    client.configure_lottery(&other_sym, &token_id, &100, &2_000, &2_500, &10, &3_000);
+    e.ledger().set_timestamp(2_200);
+    client.enter_lottery(&entrant, &other_sym);
+    assert_eq!(token_client.balance(&entrant), 0);
+    e.ledger().set_timestamp(3_000);
+    assert_eq!(
+        client.try_claim_lottery_refund(&entrant, &other_sym),
+        Err(Ok(LotteryError::RefundUnavailable))
+    );
+    e.ledger().set_timestamp(3_001);
+    assert_eq!(
+        client.try_draw_lottery(&other_sym, &10),
+        Err(Ok(LotteryError::DrawDeadlinePassed))
+    );
+    assert_eq!(client.claim_lottery_refund(&entrant, &other_sym), 100);
+    assert_eq!(token_client.balance(&entrant), 100);
+}
+
+#[test]
+fn test_lottery_undersubscribed_everyone_wins() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, _admin, _token_id, token_admin, tier_sym) = setup_lottery(&e, 10);
+
+    e.ledger().set_timestamp(1_000);
+    let entrant = Address::generate(&e);
+    token_admin.mint(&entrant, &100);
+    client.enter_lottery(&entrant, &tier_sym);
+
+    e.ledger().set_timestamp(2_001);
+    client.draw_lottery(&tier_sym, &10);
+    assert_eq!(client.balance(&entrant), 1);
+}
+
+#[test]
+fn test_lottery_window_and_purchase_guard() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, _admin, token_id, token_admin, tier_sym) = setup_lottery(&e, 10);
+    let entrant = Address::generate(&e);
+    token_admin.mint(&entrant, &1_000);
+
+    e.ledger().set_timestamp(999);
+    assert_eq!(
+        client.try_enter_lottery(&entrant, &tier_sym),
+        Err(Ok(LotteryError::RegistrationClosed))
+    );
+    e.ledger().set_timestamp(1_500);
+    assert_eq!(
+        client.try_draw_lottery(&tier_sym, &10),
+        Err(Ok(LotteryError::RegistrationOpen))
+    );
+    assert_eq!(
+        client.try_purchase(&entrant, &token_id, &tier_sym, &None, &None),
+        Err(Ok(Error::TierAllocatedByLottery))
+    );
+
+    client.enter_lottery(&entrant, &tier_sym);
+    assert_eq!(
+        client.try_enter_lottery(&entrant, &tier_sym),
+        Err(Ok(LotteryError::AlreadyEntered))
+    );
+
+    e.ledger().set_timestamp(2_001);
+    assert_eq!(
+        client.try_enter_lottery(&Address::generate(&e), &tier_sym),
+        Err(Ok(LotteryError::RegistrationClosed))
+    );
+}
+
+#[test]
+fn test_lottery_one_entry_per_did() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, _admin, _token_id, token_admin, tier_sym) = setup_lottery(&e, 10);
+
+    let registry_id = e.register(MockIdentityRegistry, ());
+    let registry = MockIdentityRegistryClient::new(&e, &registry_id);
+    client.set_identity_registry(&registry_id);
+
+    let wallet_a = Address::generate(&e);
+    let wallet_b = Address::generate(&e);
+    let no_did = Address::generate(&e);
+    let did = String::from_str(&e, "did:stellar:alice");
+    registry.set_did(&wallet_a, &did);
+    registry.set_did(&wallet_b, &did);
+    for wallet in [&wallet_a, &wallet_b, &no_did] {
+        token_admin.mint(wallet, &100);
+    }
+
+    e.ledger().set_timestamp(1_500);
+    client.enter_lottery(&wallet_a, &tier_sym);
+    // Same person, second wallet
+    assert_eq!(
+        client.try_enter_lottery(&wallet_b, &tier_sym),
+        Err(Ok(LotteryError::AlreadyEntered))
+    );
+    // No DID registered at all
+    assert_eq!(
+        client.try_enter_lottery(&no_did, &tier_sym),
+        Err(Err(InvokeError::Contract(Error::DidRequired as u32)))
+    );
+    assert_eq!(client.get_lottery_entrants(&tier_sym, &0, &10).len(), 1);
+}
+
+fn ab_variants(e: &Env) -> soroban_sdk::Vec<ExperimentVariant> {
+    soroban_sdk::vec![
+        e,
+        ExperimentVariant {
+            strategy: PricingStrategy::Standard,
+            weight_bps: 5000,
+        },
+        ExperimentVariant {
+            strategy: PricingStrategy::AbTestB,
+            weight_bps: 5000,
+        },
+    ]
+}
+
+#[test]
+fn test_experiment_splits_buyers_deterministically() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    client.start_experiment(&tier_sym, &ab_variants(&e));
+
+    let mut seen = [0u32; 2];
+    for _ in 0..40 {
+        let buyer = Address::generate(&e);
+        let variant = client.get_variant(&tier_sym, &buyer).unwrap();
+        assert_eq!(client.get_variant(&tier_sym, &buyer), Some(variant));
+        let expected = if variant == 0 { 100 } else { 120 };
+        assert_eq!(client.get_ticket_price_for(&tier_sym, &buyer), expected);
+        seen[variant as usize] += 1;
+    }
+    assert!(seen[0] > 0 && seen[1] > 0);
+
+    // The tier-level price stays the control strategy
+    assert_eq!(client.get_ticket_price(&tier_sym), 100);
+
+    client.end_experiment(&tier_sym);
+    let buyer = Address::generate(&e);
+    assert_eq!(client.get_variant(&tier_sym, &buyer), None);
+    assert_eq!(client.get_ticket_price_for(&tier_sym, &buyer), 100);
+}
+
+#[test]
+fn test_experiment_records_variant_and_stats() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    client.start_experiment(&tier_sym, &ab_variants(&e));
+
+    // Quote six buyers, only the first three buy
+    let mut expected_stats = [(0u32, 0u32, 0i128); 2];
+    for i in 0..6u32 {
+        let buyer = Address::generate(&e);
+        token_admin.mint(&buyer, &1_000);
+        let variant = client.get_variant(&tier_sym, &buyer).unwrap() as usize;
+        let price = client.quote_price(&buyer, &tier_sym);
+        // Quoting twice counts a single exposure
+        client.quote_price(&buyer, &tier_sym);
+        expected_stats[variant].0 += 1;
+
+        if i < 3 {
+            let ticket_id = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+            let ticket = client.get_ticket(&ticket_id);
+            let experiment_id = client.get_experiment(&tier_sym).unwrap().id;
+            assert_eq!(ticket.experiment_id, Some(experiment_id));
+            assert_eq!(ticket.variant, Some(variant as u32));
+            assert_eq!(ticket.price_paid, price);
+            expected_stats[variant].1 += 1;
+            expected_stats[variant].2 += price;
+        }
+    }
+
+    for (variant, (exposures, conversions, revenue)) in expected_stats.iter().enumerate() {
+        let stats = client.get_variant_stats(&tier_sym, &(variant as u32));
+        assert_eq!(stats.exposures, *exposures);
+        assert_eq!(stats.conversions, *conversions);
+        assert_eq!(stats.revenue, *revenue);
+    }
+}
+
+#[test]
+fn test_experiment_validation() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = Symbol::new(&e, "GEN");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &100,
+        &PricingStrategy::Standard,
+    );
+
+    let mut uneven = ab_variants(&e);
+    uneven.set(
+        1,
+        ExperimentVariant {
+            strategy: PricingStrategy::AbTestA,
+            weight_bps: 4000,
+        },
+    );
+    assert_eq!(
+        client.try_start_experiment(&tier_sym, &uneven),
+        Err(Ok(Error::InvalidVariants))
+    );
+
+    // Weights that would wrap around to 10000 are rejected, not summed
+    let mut wrapping = ab_variants(&e);
+    for weight_bps in [u32::MAX, 5001] {
+        wrapping.push_back(ExperimentVariant {
+            strategy: PricingStrategy::AbTestA,
+            weight_bps,
+        });
+    }
+    wrapping.remove(1);
+    assert_eq!(
+        client.try_start_experiment(&tier_sym, &wrapping),
+        Err(Ok(Error::InvalidVariants))
+    );
+
+    let single = soroban_sdk::vec![&e, ab_variants(&e).get(0).unwrap()];
+    assert_eq!(
+        client.try_start_experiment(&tier_sym, &single),
+        Err(Ok(Error::InvalidVariants))
+    );
+
+    assert_eq!(
+        client.try_end_experiment(&tier_sym),
+        Err(Ok(Error::ExperimentNotFound))
+    );
+    assert_eq!(
+        client.try_get_variant_stats(&tier_sym, &0),
+        Err(Ok(Error::ExperimentNotFound))
+    );
+
+    // Auctions promise one clearing price, so they cannot be split
+    let mut with_auction = ab_variants(&e);
+    with_auction.set(
+        1,
+        ExperimentVariant {
+            strategy: PricingStrategy::DutchAuction(auction(true)),
+            weight_bps: 5000,
+        },
+    );
+    assert_eq!(
+        client.try_start_experiment(&tier_sym, &with_auction),
+        Err(Ok(Error::ExperimentOnAuction))
+    );
+    let auction_sym = Symbol::new(&e, "PREMIUM");
+    client.add_tier(
+        &auction_sym,
+        &String::from_str(&e, "Premium"),
+        &0,
+        &10,
+        &PricingStrategy::DutchAuction(auction(true)),
+    );
+    assert_eq!(
+        client.try_start_experiment(&auction_sym, &ab_variants(&e)),
+        Err(Ok(Error::ExperimentOnAuction))
+    );
+
+    client.start_experiment(&tier_sym, &ab_variants(&e));
+    assert_eq!(
+        client.try_start_experiment(&tier_sym, &ab_variants(&e)),
+        Err(Ok(Error::ExperimentRunning))
+    );
+    assert_eq!(
+        client.try_update_tier(
+            &tier_sym,
+            &String::from_str(&e, "General"),
+            &0,
+            &PricingStrategy::DutchAuction(auction(true)),
+        ),
+        Err(Ok(Error::ExperimentOnAuction))
+    );
+}
+
+fn early_bird_schedule(e: &Env) -> TimeDecaySchedule {
+    TimeDecaySchedule {
+        phases: soroban_sdk::vec![
+            e,
+            DiscountPhase {
+                until: 1_000,
+                discount_bps: 3000,
+            },
+            DiscountPhase {
+                until: 5_000,
+                discount_bps: 1000,
+            },
+        ],
+        surge_window_seconds: 2_000,
+        surge_premium_bps: 2500,
+    }
+}
+
+fn create_contract_starting_at(
+    e: &Env,
+    admin: &Address,
+    start_time: u64,
+) -> SoulboundTicketContractClient<'static> {
+    let contract_id = e.register(SoulboundTicketContract, ());
+    let client = SoulboundTicketContractClient::new(e, &contract_id);
+    client.initialize(
+        admin,
+        &String::from_str(e, "EventTicket"),
+        &String::from_str(e, "TKT"),
+        &String::from_str(e, "https://example.com"),
+        &start_time,
+        &start_time,
+    );
+    client
+}
+
+#[test]
+fn test_time_decay_schedule_phases_and_surge() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract_starting_at(&e, &admin, 10_000);
+    let tier_sym = Symbol::new(&e, "EARLY");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Early"),
+        &1000,
+        &100,
+        &PricingStrategy::TimeDecay(early_bird_schedule(&e)),
+    );
+
+    e.ledger().set_timestamp(999);
+    assert_eq!(client.get_ticket_price(&tier_sym), 700);
+    e.ledger().set_timestamp(1_000);
+    assert_eq!(client.get_ticket_price(&tier_sym), 900);
+    e.ledger().set_timestamp(5_000);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1000);
+    e.ledger().set_timestamp(8_000);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1250);
+}
+
+#[test]
+fn test_time_decay_price_timeline() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract_starting_at(&e, &admin, 10_000);
+    let tier_sym = Symbol::new(&e, "EARLY");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Early"),
+        &1000,
+        &100,
+        &PricingStrategy::TimeDecay(early_bird_schedule(&e)),
+    );
+
+    let expected = soroban_sdk::vec![
+        &e,
+        PricePoint {
+            from: 0,
+            price: 700
+        },
+        PricePoint {
+            from: 1_000,
+            price: 900,
+        },
+        PricePoint {
+            from: 5_000,
+            price: 1000,
+        },
+        PricePoint {
+            from: 8_000,
+            price: 1250,
+        },
+    ];
+    assert_eq!(client.get_price_timeline(&tier_sym), expected);
+
+    // Without a schedule the timeline is just the current price
+    let flat = Symbol::new(&e, "FLAT");
+    client.add_tier(
+        &flat,
+        &String::from_str(&e, "Flat"),
+        &500,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    assert_eq!(
+        client.get_price_timeline(&flat),
+        soroban_sdk::vec![
+            &e,
+            PricePoint {
+                from: 0,
+                price: 500
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_time_decay_schedule_validation() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    let mut unordered = early_bird_schedule(&e);
+    let first = unordered.phases.get(0).unwrap();
+    unordered.phases.push_back(first);
+    let res = client.try_add_tier(
+        &Symbol::new(&e, "BAD1"),
+        &String::from_str(&e, "Bad"),
+        &1000,
+        &100,
+        &PricingStrategy::TimeDecay(unordered),
+    );
+    assert_eq!(res, Err(Ok(Error::UnorderedDiscountPhases)));
+
+    let mut too_deep = early_bird_schedule(&e);
+    too_deep.phases.set(
+        0,
+        DiscountPhase {
+            until: 1_000,
+            discount_bps: 10_001,
+        },
+    );
+    let res = client.try_add_tier(
+        &Symbol::new(&e, "BAD2"),
+        &String::from_str(&e, "Bad"),
+        &1000,
+        &100,
+        &PricingStrategy::TimeDecay(too_deep),
+    );
+    assert_eq!(res, Err(Ok(Error::DiscountTooLarge)));
+}
+
+fn promo_hash(e: &Env, code: &str) -> BytesN<32> {
+    e.crypto()
+        .sha256(&String::from_str(e, code).to_bytes())
+        .into()
+}
+
+fn setup_promo_sale<'a>(
+    e: &Env,
+) -> (
+    SoulboundTicketContractClient<'a>,
+    Address,
+    token::Client<'a>,
+    Address,
+    Symbol,
+) {
+    let sale = setup_sale(e, "GEN", 1000, 100, PricingStrategy::Standard);
+    let buyer = Address::generate(e);
+    sale.token_admin.mint(&buyer, &10_000);
+    (
+        sale.client,
+        sale.token_id.clone(),
+        token::Client::new(e, &sale.token_id),
+        buyer,
+        sale.tier,
+    )
+}
+
+#[test]
+fn test_promo_code_discounts_and_redemption_count() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, token_id, token_client, buyer, tier_sym) = setup_promo_sale(&e);
+
+    client.register_promo_code(
+        &promo_hash(&e, "EARLY20"),
+        &PromoDiscount::Bps(2000),
+        &2,
+        &soroban_sdk::vec![&e],
+        &1_000,
+    );
+    client.register_promo_code(
+        &promo_hash(&e, "FLAT150"),
+        &PromoDiscount::Fixed(150),
+        &1,
+        &soroban_sdk::vec![&e, tier_sym.clone()],
+        &1_000,
+    );
+
+    let code = Some(String::from_str(&e, "EARLY20"));
+    let ticket_id = client.purchase(&buyer, &token_id, &tier_sym, &code, &None);
+    assert_eq!(client.get_ticket(&ticket_id).price_paid, 800);
+    assert_eq!(token_client.balance(&buyer), 9_200);
+    assert_eq!(client.get_promo_redemptions(&promo_hash(&e, "EARLY20")), 1);
+
+    let ticket_id = client.purchase(
+        &buyer,
+        &token_id,
+        &tier_sym,
+        &Some(String::from_str(&e, "FLAT150")),
+        &None,
+    );
+    assert_eq!(client.get_ticket(&ticket_id).price_paid, 850);
+
+    // The tier keeps recording the undiscounted price
+    assert_eq!(client.get_ticket_price(&tier_sym), 1000);
+
+    client.purchase(&buyer, &token_id, &tier_sym, &code, &None);
+    assert_eq!(client.get_promo_redemptions(&promo_hash(&e, "EARLY20")), 2);
+    assert_eq!(
+        client.try_purchase(&buyer, &token_id, &tier_sym, &code, &None),
+        Err(Ok(Error::PromoCodeExhausted))
+    );
+}
+
+#[test]
+fn test_promo_code_rejections() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, token_id, _token_client, buyer, tier_sym) = setup_promo_sale(&e);
+
+    let vip = Symbol::new(&e, "VIP");
+    client.register_promo_code(
+        &promo_hash(&e, "VIPONLY"),
+        &PromoDiscount::Bps(5000),
+        &10,
+        &soroban_sdk::vec![&e, vip],
+        &1_000,
+    );
+    let res = client.try_purchase(
+        &buyer,
+        &token_id,
+        &tier_sym,
+        &Some(String::from_str(&e, "VIPONLY")),
+        &None,
+    );
+    assert_eq!(res, Err(Ok(Error::PromoCodeNotValidForTier)));
+
+    let res = client.try_purchase(
+        &buyer,
+        &token_id,
+        &tier_sym,
+        &Some(String::from_str(&e, "UNKNOWN")),
+        &None,
+    );
+    assert_eq!(res, Err(Ok(Error::PromoCodeNotFound)));
+
+    client.register_promo_code(
+        &promo_hash(&e, "LATE"),
+        &PromoDiscount::Bps(5000),
+        &10,
+        &soroban_sdk::vec![&e],
+        &1_000,
+    );
+    e.ledger().set_timestamp(1_001);
+    let res = client.try_purchase(
+        &buyer,
+        &token_id,
+        &tier_sym,
+        &Some(String::from_str(&e, "LATE")),
+        &None,
+    );
+    assert_eq!(res, Err(Ok(Error::PromoCodeExpired)));
+    assert_eq!(client.get_promo_redemptions(&promo_hash(&e, "LATE")), 0);
+
+    // Invalid registrations
+    let res = client.try_register_promo_code(
+        &promo_hash(&e, "LATE"),
+        &PromoDiscount::Bps(100),
+        &1,
+        &soroban_sdk::vec![&e],
+        &1_000,
+    );
+    assert_eq!(res, Err(Ok(Error::PromoCodeExists)));
+    let res = client.try_register_promo_code(
+        &promo_hash(&e, "TOOMUCH"),
+        &PromoDiscount::Bps(10_001),
+        &1,
+        &soroban_sdk::vec![&e],
+        &1_000,
+    );
+    assert_eq!(res, Err(Ok(Error::InvalidPromoCode)));
+}
+
+#[test]
+fn test_promo_code_emits_events() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (client, token_id, _token_client, buyer, tier_sym) = setup_promo_sale(&e);
+
+    let hash = promo_hash(&e, "EARLY20");
+    client.register_promo_code(
+        &hash,
+        &PromoDiscount::Bps(2000),
+        &5,
+        &soroban_sdk::vec![&e],
+        &1_000,
+    );
+    assert_eq!(
+        e.events().all(),
+        soroban_sdk::vec![
+            &e,
+            published(
+                &e,
+                &client.address,
+                &PromoRegistered {
+                    code_hash: hash.clone(),
+                    max_redemptions: 5,
+                    expires_at: 1_000,
+                }
+            )
+        ]
+    );
+
+    client.purchase(
+        &buyer,
+        &token_id,
+        &tier_sym,
+        &Some(String::from_str(&e, "EARLY20")),
+        &None,
+    );
+    assert!(e.events().all().contains(published(
+        &e,
+        &client.address,
+        &PromoRedeemed {
+            code_hash: hash,
+            buyer,
+            tier: tier_sym,
+            discount: 200,
+        }
+    )));
+}
+
+fn add_flat_tier(e: &Env, client: &SoulboundTicketContractClient, base_price: i128) -> Symbol {
+    let tier_sym = Symbol::new(e, "ORK");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(e, "Oracle Tier"),
+        &base_price,
+        &100,
+        &PricingStrategy::Standard,
+    );
+    tier_sym
+}
+
+#[test]
+fn test_multi_oracle_median_price() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+
+    let sources = soroban_sdk::vec![
+        &e,
+        OracleSource {
+            kind: OracleKind::Dia,
+            address: price_feed(&e, 110_000_000),
+        },
+        OracleSource {
+            kind: OracleKind::Dia,
+            address: price_feed(&e, 112_000_000),
+        },
+        OracleSource {
+            kind: OracleKind::Dex,
+            address: price_feed(&e, 114_000_000),
+        },
+    ];
+    client.set_pricing_config(&source_config(&e, &admin, sources, 500, 3));
+
+    // Median $1.12 → 12% markup
+    assert_eq!(client.get_ticket_price(&tier_sym), 1120);
+}
+
+#[test]
+fn test_multi_oracle_rejects_outlier() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+
+    let sources = soroban_sdk::vec![
+        &e,
+        OracleSource {
+            kind: OracleKind::Dia,
+            address: price_feed(&e, 110_000_000),
+        },
+        OracleSource {
+            kind: OracleKind::Dia,
+            address: price_feed(&e, 111_000_000),
+        },
+        OracleSource {
+            kind: OracleKind::Dex,
+            address: price_feed(&e, 112_000_000),
+        },
+        // Manipulated pool, far outside the 5% band
+        OracleSource {
+            kind: OracleKind::Dex,
+            address: price_feed(&e, 300_000_000),
+        },
+    ];
+    client.set_pricing_config(&source_config(&e, &admin, sources, 500, 3));
+
+    // Raw median would be $1.115; after dropping the outlier it is $1.11
+    assert_eq!(client.get_ticket_price(&tier_sym), 1110);
+}
+
+#[test]
+fn test_multi_oracle_requires_min_agreeing_sources() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().set_timestamp(1_000_000);
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+
+    let stale = price_feed(&e, 150_000_000);
+    MockPriceFeedClient::new(&e, &stale).set_timestamp(&1);
+    let sources = soroban_sdk::vec![
+        &e,
+        OracleSource {
+            kind: OracleKind::Dia,
+            address: price_feed(&e, 120_000_000),
+        },
+        OracleSource {
+            kind: OracleKind::Dia,
+            address: stale,
+        },
+        // Unreachable source (not a contract)
+        OracleSource {
+            kind: OracleKind::Dex,
+            address: admin.clone(),
+        },
+    ];
+
+    // Only one fresh source: below the minimum of two → neutral multiplier
+    client.set_pricing_config(&source_config(&e, &admin, sources.clone(), 500, 2));
+    assert_eq!(client.get_ticket_price(&tier_sym), 1000);
+
+    client.set_pricing_config(&source_config(&e, &admin, sources, 500, 1));
+    assert_eq!(client.get_ticket_price(&tier_sym), 1200);
+}
+
+#[test]
+fn test_median_helper() {
+    let e = Env::default();
+    assert_eq!(oracle::median(&soroban_sdk::vec![&e]), None);
+    assert_eq!(oracle::median(&soroban_sdk::vec![&e, 7]), Some(7));
+    assert_eq!(oracle::median(&soroban_sdk::vec![&e, 9, 1, 5]), Some(5));
+    assert_eq!(oracle::median(&soroban_sdk::vec![&e, 10, 2, 8, 4]), Some(6));
+}
+
+fn xlm(e: &Env) -> Asset {
+    Asset::Other(Symbol::new(e, "XLM"))
+}
+
+fn sep40_oracle(e: &Env, price: i128, timestamp: u64) -> Address {
+    let id = e.register(MockSep40Oracle, ());
+    MockSep40OracleClient::new(e, &id).set_price(&xlm(e), &price, &timestamp);
+    id
+}
+
+#[test]
+fn test_sep40_primary_oracle() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().set_timestamp(10_000);
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+
+    // $1.10 with 14 decimals
+    let mut config = source_config(&e, &admin, soroban_sdk::vec![&e], 500, 1);
+    config.oracle_kind = OracleKind::Sep40(xlm(&e));
+    config.oracle_address = sep40_oracle(&e, 110_000_000_000_000, 9_900);
+    config.dex_pool_address = e.register(MockDex, ());
+    client.set_pricing_config(&config);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1100);
+
+    // Asset unknown to the oracle → DEX fallback ($1.05)
+    config.oracle_kind = OracleKind::Sep40(Asset::Other(Symbol::new(&e, "BTC")));
+    client.set_pricing_config(&config);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1050);
+
+    // Stale SEP-40 price → DEX fallback as well
+    config.oracle_kind = OracleKind::Sep40(xlm(&e));
+    config.max_oracle_age_seconds = 50;
+    client.set_pricing_config(&config);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1050);
+}
+
+#[test]
+fn test_dia_stale_price_falls_back_to_dex() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().set_timestamp(10_000);
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+
+    let dia = price_feed(&e, 120_000_000);
+    let mut config = source_config(&e, &admin, soroban_sdk::vec![&e], 500, 1);
+    config.oracle_address = dia.clone();
+    config.dex_pool_address = e.register(MockDex, ());
+    config.max_oracle_age_seconds = 100;
+    client.set_pricing_config(&config);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1200);
+
+    MockPriceFeedClient::new(&e, &dia).set_timestamp(&9_000);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1050);
+}
+
+#[test]
+fn test_non_positive_oracle_prices_are_ignored() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().set_timestamp(10_000);
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+
+    let dia = price_feed(&e, 0);
+    let dex = price_feed(&e, 105_000_000);
+    let mut config = source_config(&e, &admin, soroban_sdk::vec![&e], 500, 1);
+    config.oracle_address = dia.clone();
+    config.dex_pool_address = dex.clone();
+    client.set_pricing_config(&config);
+
+    // A zero or negative oracle price is a failed source → DEX fallback
+    assert_eq!(client.get_ticket_price(&tier_sym), 1050);
+    MockPriceFeedClient::new(&e, &dia).set_price(&-110_000_000);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1050);
+    // ... and so is a non-positive DEX price → neutral multiplier
+    MockPriceFeedClient::new(&e, &dex).set_price(&-1);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1000);
+
+    // Extreme prices convert without overflowing
+    assert_eq!(
+        oracle::oracle_price_to_multiplier(i128::MAX, oracle::DIA_ORACLE_DECIMALS, 10_000),
+        i128::MAX / 10_000
+    );
+}
+
+#[test]
+fn test_sep40_and_dia_sources_aggregate() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().set_timestamp(10_000);
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+
+    let sources = soroban_sdk::vec![
+        &e,
+        OracleSource {
+            kind: OracleKind::Sep40(xlm(&e)),
+            address: sep40_oracle(&e, 108_000_000_000_000, 10_000),
+        },
+        OracleSource {
+            kind: OracleKind::Dia,
+            address: price_feed(&e, 110_000_000),
+        },
+    ];
+    client.set_pricing_config(&source_config(&e, &admin, sources, 500, 2));
+
+    // Mean of the two middle values: ($1.08 + $1.10) / 2
+    assert_eq!(client.get_ticket_price(&tier_sym), 1090);
+}
+
+#[test]
+fn test_normalize_decimals() {
+    assert_eq!(
+        oracle::normalize_decimals(110_000_000_000_000, 14),
+        Some(110_000_000)
+    );
+    assert_eq!(
+        oracle::normalize_decimals(110_000_000, 8),
+        Some(110_000_000)
+    );
+    assert_eq!(oracle::normalize_decimals(11_000, 4), Some(110_000_000));
+    assert_eq!(oracle::normalize_decimals(i128::MAX, 0), None);
+    assert_eq!(oracle::normalize_decimals(1, 60), None);
+}
+
+#[test]
+fn test_stale_policy_last_known_good() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().set_timestamp(10_000);
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+    assert_eq!(client.get_last_good_multiplier(), None);
+
+    let dia = price_feed(&e, 120_000_000);
+    let mut config = source_config(&e, &admin, soroban_sdk::vec![&e], 500, 1);
+    config.oracle_address = dia.clone();
+    config.max_oracle_age_seconds = 100;
+    config.stale_policy = StalePricePolicy::LastKnownGood;
+    client.set_pricing_config(&config);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1200);
+    // Views never record the live multiplier; refreshes do
+    assert_eq!(client.get_last_good_multiplier(), None);
+    assert!(client.poke_price());
+
+    let last_good = client.get_last_good_multiplier().unwrap();
+    assert_eq!(last_good.multiplier, 12_000);
+    assert_eq!(last_good.timestamp, 10_000);
+
+    // Oracle goes stale and there is no DEX: keep the last live multiplier
+    e.ledger().set_timestamp(20_000);
+    MockPriceFeedClient::new(&e, &dia).set_timestamp(&10_000);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1200);
+    assert!(client.poke_price());
+    assert_eq!(client.get_cached_multiplier(), Some((12_000, 20_000)));
+    assert_eq!(client.get_last_good_multiplier().unwrap().timestamp, 10_000);
+}
+
+#[test]
+fn test_stale_policy_last_known_good_without_history_is_neutral() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+
+    let mut config = source_config(&e, &admin, soroban_sdk::vec![&e], 500, 1);
+    config.stale_policy = StalePricePolicy::LastKnownGood;
+    client.set_pricing_config(&config);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1000);
+}
+
+#[test]
+fn test_stale_policy_halt_sales() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+
+    let mut config = source_config(&e, &admin, soroban_sdk::vec![&e], 500, 1);
+    config.stale_policy = StalePricePolicy::HaltSales;
+    client.set_pricing_config(&config);
+
+    let buyer = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+    assert_eq!(
+        client.try_purchase(&buyer, &token_id, &tier_sym, &None, &None),
+        Err(Ok(Error::OraclePriceUnavailable))
+    );
+    // Views and keepers get the same typed error
+    assert_eq!(
+        client.try_get_ticket_price(&tier_sym),
+        Err(Ok(Error::OraclePriceUnavailable))
+    );
+    assert_eq!(
+        client.try_get_payment_quote(&tier_sym, &buyer, &token_id),
+        Err(Ok(Error::OraclePriceUnavailable))
+    );
+    assert_eq!(
+        client.try_get_price_timeline(&tier_sym),
+        Err(Ok(Error::OraclePriceUnavailable))
+    );
+    assert_eq!(
+        client.try_poke_price(),
+        Err(Ok(Error::OraclePriceUnavailable))
+    );
+}
+
+#[test]
+fn test_stale_policy_fallback_events() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+
+    // Neutral fallback
+    client.set_pricing_config(&source_config(&e, &admin, soroban_sdk::vec![&e], 500, 1));
+    assert_eq!(client.get_ticket_price(&tier_sym), 1000);
+    // Views publish nothing; the refresh reports the fallback
+    assert_eq!(e.events().all(), soroban_sdk::vec![&e]);
+    client.poke_price();
+    assert!(e.events().all().contains(published(
+        &e,
+        &client.address,
+        &OracleFallback {
+            path: symbol_short!("neutral"),
+            value: ORACLE_PRECISION,
+        }
+    )));
+
+    // DEX fallback when the primary oracle is unreachable
+    let mut config = source_config(&e, &admin, soroban_sdk::vec![&e], 500, 1);
+    config.dex_pool_address = e.register(MockDex, ());
+    client.set_pricing_config(&config);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1050);
+    assert_eq!(e.events().all(), soroban_sdk::vec![&e]);
+    client.poke_price();
+    assert!(e.events().all().contains(published(
+        &e,
+        &client.address,
+        &OracleFallback {
+            path: symbol_short!("dex"),
+            value: 105_000_000,
+        }
+    )));
+}
+
+fn twap_config(e: &Env, admin: &Address, dex: &Address) -> PricingConfig {
+    let mut config = source_config(e, admin, soroban_sdk::vec![e], 500, 1);
+    config.dex_pool_address = dex.clone();
+    config.update_frequency = 60;
+    config.max_oracle_age_seconds = 600;
+    config.twap_window = 4;
+    config.max_change_bps = 1000;
+    config
+}
+
+#[test]
+fn test_twap_resists_spot_manipulation() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().set_timestamp(1_000);
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+
+    // Primary oracle unreachable, so every reading is a DEX spot price
+    let dex = price_feed(&e, 100_000_000);
+    let feed = MockPriceFeedClient::new(&e, &dex);
+    client.set_pricing_config(&twap_config(&e, &admin, &dex));
+
+    assert!(client.record_price_observation());
+    // Too early for the next observation
+    assert!(!client.record_price_observation());
+
+    e.ledger().set_timestamp(1_060);
+    assert!(client.record_price_observation());
+
+    // Pool pushed to $3.00: a same-ledger spike carries no weight yet
+    e.ledger().set_timestamp(1_120);
+    feed.set_price(&300_000_000);
+    assert!(client.record_price_observation());
+    assert_eq!(client.get_ticket_price(&tier_sym), 1000);
+
+    // Once the spike has weight, the change is clamped to 10% per update
+    e.ledger().set_timestamp(1_180);
+    feed.set_price(&100_000_000);
+    assert!(client.record_price_observation());
+    assert_eq!(client.get_ticket_price(&tier_sym), 1100);
+
+    // Ring buffer keeps the newest `twap_window` observations
+    e.ledger().set_timestamp(1_240);
+    assert!(client.record_price_observation());
+    let observations = client.get_price_observations();
+    assert_eq!(observations.len(), 4);
+    assert_eq!(observations.get(0).unwrap().timestamp, 1_060);
+    assert_eq!(observations.get(3).unwrap().timestamp, 1_240);
+}
+
+#[test]
+fn test_twap_updated_on_purchase_and_goes_stale() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().set_timestamp(1_000);
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+
+    let dex = price_feed(&e, 120_000_000);
+    client.set_pricing_config(&twap_config(&e, &admin, &dex));
+
+    // No observations yet: stale policy (neutral) applies
+    assert_eq!(client.get_ticket_price(&tier_sym), 1000);
+
+    let buyer = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+    client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+
+    assert_eq!(client.get_price_observations().len(), 1);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1200);
+
+    // Nobody records a fresh observation within `max_oracle_age_seconds`
+    e.ledger().set_timestamp(2_000);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1000);
+}
+
+#[test]
+fn test_twap_helpers() {
+    let e = Env::default();
+    let observations = soroban_sdk::vec![
+        &e,
+        PriceObservation {
+            price: 100,
+            timestamp: 0,
+        },
+        PriceObservation {
+            price: 400,
+            timestamp: 30,
+        },
+    ];
+    // 100 for 30s, 400 for 10s
+    assert_eq!(oracle::time_weighted_average(&observations, 40), Some(175));
+    // Newest observation has no weight yet
+    assert_eq!(oracle::time_weighted_average(&observations, 30), Some(100));
+    assert_eq!(
+        oracle::time_weighted_average(&soroban_sdk::vec![&e], 30),
+        None
+    );
+
+    assert_eq!(oracle::clamp_change(10_000, 15_000, 1000), 11_000);
+    assert_eq!(oracle::clamp_change(10_000, 5_000, 1000), 9_000);
+    assert_eq!(oracle::clamp_change(10_000, 15_000, 0), 15_000);
+}
+
+#[test]
+fn test_cached_multiplier_refreshes_after_update_frequency() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().set_timestamp(1_000);
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+
+    let dia = price_feed(&e, 120_000_000);
+    let feed = MockPriceFeedClient::new(&e, &dia);
+    let mut config = source_config(&e, &admin, soroban_sdk::vec![&e], 500, 1);
+    config.oracle_address = dia.clone();
+    config.update_frequency = 300;
+    client.set_pricing_config(&config);
+    assert_eq!(client.get_cached_multiplier(), None);
+
+    assert!(client.poke_price());
+    assert_eq!(
+        e.events().all(),
+        soroban_sdk::vec![
+            &e,
+            published(
+                &e,
+                &client.address,
+                &Repriced {
+                    previous: ORACLE_PRECISION,
+                    multiplier: 12_000,
+                    timestamp: 1_000,
+                }
+            ),
+        ]
+    );
+    assert_eq!(client.get_cached_multiplier(), Some((12_000, 1_000)));
+
+    // Within the window the cached multiplier is served
+    e.ledger().set_timestamp(1_200);
+    feed.set_price(&150_000_000);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1200);
+    assert!(!client.poke_price());
+
+    // Once expired, views follow the oracle until the next refresh
+    e.ledger().set_timestamp(1_300);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1500);
+    assert_eq!(client.get_cached_multiplier(), Some((12_000, 1_000)));
+    assert!(client.poke_price());
+    assert_eq!(client.get_cached_multiplier(), Some((15_000, 1_300)));
+}
+
+#[test]
+fn test_purchase_refreshes_cached_multiplier() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().set_timestamp(1_000);
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+
+    let dia = price_feed(&e, 110_000_000);
+    let mut config = source_config(&e, &admin, soroban_sdk::vec![&e], 500, 1);
+    config.oracle_address = dia.clone();
+    config.update_frequency = 300;
+    client.set_pricing_config(&config);
+
+    let buyer = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+    client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    assert_eq!(client.get_cached_multiplier(), Some((11_000, 1_000)));
+
+    MockPriceFeedClient::new(&e, &dia).set_price(&200_000_000);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1100);
+
+    // A new config invalidates the cache
+    client.set_pricing_config(&config);
+    assert_eq!(client.get_cached_multiplier(), None);
+    assert_eq!(client.get_ticket_price(&tier_sym), 2000);
+}
+
+/// A $50.00 tier paid in a token priced by a DIA feed at `rate`; also
+/// returns the feed.
+fn setup_fiat_sale<'a>(e: &Env, rate: i128) -> (Sale<'a>, Address) {
+    let sale = setup_sale(e, "ORK", 5000, 100, PricingStrategy::Standard);
+    let client = &sale.client;
+    let mut config = source_config(e, &sale.admin, soroban_sdk::vec![e], 500, 1);
+    config.update_frequency = 300;
+    client.set_pricing_config(&config);
+
+    let usd = Symbol::new(e, "USD");
+    client.set_tier_currency(&sale.tier, &Some(usd.clone()));
+
+    let feed = price_feed(e, rate);
+    client.set_payment_asset(
+        &sale.token_id,
+        &usd,
+        &PaymentAsset {
+            oracle_kind: OracleKind::Dia,
+            oracle_address: feed.clone(),
+            oracle_pair: String::from_str(e, "XLM/USD"),
+            token_decimals: 7,
+            max_slippage_bps: 200,
+        },
+    );
+    (sale, feed)
+}
+
+#[test]
+fn test_fiat_tier_converted_at_purchase() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    // 1 XLM = $0.12
+    let (
+        Sale {
+            client,
+            token_id,
+            token_admin,
+            tier: tier_sym,
+            ..
+        },
+        _,
+    ) = setup_fiat_sale(&e, 12_000_000);
+
+    let buyer = Address::generate(&e);
+    token_admin.mint(&buyer, &100_000_000_000);
+
+    // $50 / $0.12 = 416.66666666.. XLM, rounded up in the organizer's favour
+    assert_eq!(client.get_ticket_price(&tier_sym), 5000);
+    let quote = client.get_payment_quote(&tier_sym, &buyer, &token_id);
+    assert_eq!(quote, 4_166_666_667);
+
+    let ticket_id = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    let token = token::Client::new(&e, &token_id);
+    assert_eq!(token.balance(&client.address), quote);
+    assert_eq!(client.get_revenue(&token_id).gross, quote);
+    assert_eq!(client.get_ticket(&ticket_id).price_paid, quote);
+}
+
+#[test]
+fn test_fiat_tier_slippage_band() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().set_timestamp(1_000);
+
+    let (
+        Sale {
+            client,
+            token_id,
+            token_admin,
+            tier: tier_sym,
+            ..
+        },
+        feed,
+    ) = setup_fiat_sale(&e, 12_500_000);
+    let feed = MockPriceFeedClient::new(&e, &feed);
+
+    let buyer = Address::generate(&e);
+    token_admin.mint(&buyer, &100_000_000_000);
+    client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+
+    // Within the 2% band the quoted rate is still charged
+    e.ledger().set_timestamp(1_100);
+    feed.set_price(&12_600_000);
+    let id = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    assert_eq!(client.get_ticket(&id).price_paid, 4_000_000_000);
+
+    // A larger move re-quotes at the live rate: $50 / $0.13 = 384.61538461.. XLM
+    feed.set_price(&13_000_000);
+    let id = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    assert_eq!(client.get_ticket(&id).price_paid, 3_846_153_847);
+    assert_eq!(
+        client.get_payment_quote(&tier_sym, &buyer, &token_id),
+        3_846_153_847
+    );
+
+    // The new quote holds within the band
+    feed.set_price(&12_900_000);
+    let id = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    assert_eq!(client.get_ticket(&id).price_paid, 3_846_153_847);
+}
+
+#[test]
+fn test_fiat_tier_max_amount() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().set_timestamp(1_000);
+
+    let (
+        Sale {
+            client,
+            token_id,
+            token_admin,
+            tier: tier_sym,
+            ..
+        },
+        feed,
+    ) = setup_fiat_sale(&e, 12_500_000);
+    let feed = MockPriceFeedClient::new(&e, &feed);
+    let token = token::Client::new(&e, &token_id);
+
+    let buyer = Address::generate(&e);
+    token_admin.mint(&buyer, &100_000_000_000);
+    let shown = client.get_payment_quote(&tier_sym, &buyer, &token_id);
+    assert_eq!(shown, 4_000_000_000);
+    client.purchase(&buyer, &token_id, &tier_sym, &None, &Some(shown));
+
+    // The rate falls out of the 2% band: $50 / $0.12 = 416.66666667 XLM
+    e.ledger().set_timestamp(1_100);
+    feed.set_price(&12_000_000);
+    assert_eq!(
+        client.try_purchase(&buyer, &token_id, &tier_sym, &None, &Some(shown)),
+        Err(Ok(Error::MaxAmountExceeded))
+    );
+    assert_eq!(token.balance(&buyer), 96_000_000_000);
+    assert_eq!(client.get_tier(&tier_sym).minted, 1);
+
+    // Accepting the new amount goes through
+    let id = client.purchase(&buyer, &token_id, &tier_sym, &None, &Some(4_166_666_667));
+    assert_eq!(client.get_ticket(&id).price_paid, 4_166_666_667);
+}
+
+#[test]
+fn test_fiat_tier_bounded_in_token_units() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    // 1 XLM = $0.125, so $50 = 400 XLM
+    let (
+        Sale {
+            client,
+            token_id,
+            token_admin,
+            tier: tier_sym,
+            ..
+        },
+        _,
+    ) = setup_fiat_sale(&e, 12_500_000);
+    let buyer = Address::generate(&e);
+    token_admin.mint(&buyer, &100_000_000_000);
+
+    // A ceiling in token units does not cap the fiat list price in cents
+    client.set_price_bounds(&0, &3_000_000_000);
+    assert_eq!(client.get_ticket_price(&tier_sym), 5000);
+    assert_eq!(
+        client.get_payment_quote(&tier_sym, &buyer, &token_id),
+        3_000_000_000
+    );
+    let id = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    assert_eq!(client.get_ticket(&id).price_paid, 3_000_000_000);
+
+    // A floor in token units does not inflate it either
+    client.set_price_bounds(&4_500_000_000, &i128::MAX);
+    assert_eq!(client.get_ticket_price(&tier_sym), 5000);
+    let id = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    assert_eq!(client.get_ticket(&id).price_paid, 4_500_000_000);
+}
+
+#[test]
+fn test_fiat_payment_errors() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().set_timestamp(10_000);
+
+    let (
+        Sale {
+            client,
+            admin,
+            token_id,
+            token_admin,
+            tier: tier_sym,
+        },
+        feed,
+    ) = setup_fiat_sale(&e, 12_500_000);
+    let buyer = Address::generate(&e);
+    token_admin.mint(&buyer, &100_000_000_000);
+    let usd = Symbol::new(&e, "USD");
+
+    let mut asset = client.get_payment_asset(&token_id, &usd).unwrap();
+    asset.max_slippage_bps = 10_001;
+    assert_eq!(
+        client.try_set_payment_asset(&token_id, &usd, &asset),
+        Err(Ok(Error::InvalidSlippage))
+    );
+
+    // No rate configured for this token
+    let (other_token, _) = create_payment_token(&e, &admin);
+    assert_eq!(
+        client.try_get_payment_quote(&tier_sym, &buyer, &other_token),
+        Err(Ok(Error::PaymentAssetNotAccepted))
+    );
+    assert_eq!(
+        client.try_purchase(&buyer, &other_token, &tier_sym, &None, &None),
+        Err(Ok(Error::PaymentAssetNotAccepted))
+    );
+
+    // Stale rate and no quote to fall back on
+    MockPriceFeedClient::new(&e, &feed).set_timestamp(&10_000);
+    e.ledger()
+        .set_timestamp(10_001 + oracle::DEFAULT_STALENESS_SECONDS);
+    assert_eq!(
+        client.try_get_payment_quote(&tier_sym, &buyer, &token_id),
+        Err(Ok(Error::PaymentRateUnavailable))
+    );
+    assert_eq!(
+        client.try_purchase(&buyer, &token_id, &tier_sym, &None, &None),
+        Err(Ok(Error::PaymentRateUnavailable))
+    );
+}
+
+#[test]
+fn test_fiat_auction_rejected() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = Symbol::new(&e, "DUTCH");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "Dutch"),
+        &1000,
+        &10,
+        &PricingStrategy::DutchAuction(auction(false)),
+    );
+    assert_eq!(
+        client.try_set_tier_currency(&tier_sym, &Some(Symbol::new(&e, "USD"))),
+        Err(Ok(Error::FiatAuctionUnsupported))
+    );
+}
+
+#[test]
+fn test_fiat_to_token_amount_rounding() {
+    // $1.00 at $0.50 → exactly 2 tokens (7 decimals)
+    assert_eq!(
+        pricing::fiat_to_token_amount(100, 50_000_000, 7),
+        Some(20_000_000)
+    );
+    // $1.00 at $3.00 → 0.3333334 tokens, rounded up
+    assert_eq!(
+        pricing::fiat_to_token_amount(100, 300_000_000, 7),
+        Some(3_333_334)
+    );
+    assert_eq!(pricing::fiat_to_token_amount(100, 0, 7), None);
+    assert_eq!(pricing::fiat_to_token_amount(i128::MAX, 1, 7), None);
+}
+
+#[test]
+fn test_add_tier_validation() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let name = String::from_str(&e, "Tier");
+    let tier_sym = Symbol::new(&e, "T1");
+
+    assert_eq!(
+        client.try_add_tier(&tier_sym, &name, &-1, &10, &PricingStrategy::Standard),
+        Err(Ok(Error::NegativeBasePrice))
+    );
+    assert_eq!(
+        client.try_add_tier(&tier_sym, &name, &100, &0, &PricingStrategy::Standard),
+        Err(Ok(Error::ZeroMaxSupply))
+    );
+
+    let mut inverted = auction(false);
+    inverted.floor_price = 2000;
+    assert_eq!(
+        client.try_add_tier(
+            &tier_sym,
+            &name,
+            &100,
+            &10,
+            &PricingStrategy::DutchAuction(inverted)
+        ),
+        Err(Ok(Error::InvalidAuctionPriceRange))
+    );
+    let mut backwards = auction(false);
+    backwards.end_time = backwards.start_time;
+    assert_eq!(
+        client.try_add_tier(
+            &tier_sym,
+            &name,
+            &100,
+            &10,
+            &PricingStrategy::DutchAuction(backwards)
+        ),
+        Err(Ok(Error::InvalidAuctionSchedule))
+    );
+
+    // Free tickets are fine
+    client.add_tier(&tier_sym, &name, &0, &10, &PricingStrategy::Standard);
+    assert_eq!(
+        client.try_add_tier(&tier_sym, &name, &100, &10, &PricingStrategy::Standard),
+        Err(Ok(Error::TierAlreadyExists))
+    );
+}
+
+#[test]
+fn test_pricing_config_validation() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let valid = source_config(&e, &admin, soroban_sdk::vec![&e], 500, 1);
+
+    let mut config = valid.clone();
+    config.price_floor = 200;
+    config.price_ceiling = 100;
+    assert_eq!(
+        client.try_set_pricing_config(&config),
+        Err(Ok(PricingError::InvalidPriceBounds))
+    );
+    config.price_floor = -1;
+    assert_eq!(
+        client.try_set_pricing_config(&config),
+        Err(Ok(PricingError::InvalidPriceBounds))
+    );
+
+    let mut config = valid.clone();
+    config.oracle_reference_price = 0;
+    assert_eq!(
+        client.try_set_pricing_config(&config),
+        Err(Ok(PricingError::InvalidReferencePrice))
+    );
+
+    let mut config = valid.clone();
+    config.max_oracle_age_seconds = 0;
+    assert_eq!(
+        client.try_set_pricing_config(&config),
+        Err(Ok(PricingError::ZeroOracleAge))
+    );
+
+    let mut config = valid.clone();
+    config.max_deviation_bps = 10_001;
+    assert_eq!(
+        client.try_set_pricing_config(&config),
+        Err(Ok(PricingError::InvalidDeviationBand))
+    );
+
+    let mut config = valid.clone();
+    config.min_sources = 0;
+    assert_eq!(
+        client.try_set_pricing_config(&config),
+        Err(Ok(PricingError::InvalidMinSources))
+    );
+    let sources = soroban_sdk::vec![
+        &e,
+        OracleSource {
+            kind: OracleKind::Dia,
+            address: price_feed(&e, 100_000_000),
+        },
+    ];
+    assert_eq!(
+        client.try_set_pricing_config(&source_config(&e, &admin, sources, 500, 2)),
+        Err(Ok(PricingError::InvalidMinSources))
+    );
+
+    let mut config = valid.clone();
+    config.max_change_bps = 10_001;
+    assert_eq!(
+        client.try_set_pricing_config(&config),
+        Err(Ok(PricingError::InvalidChangeClamp))
+    );
+
+    client.set_pricing_config(&valid);
+}
+
+#[test]
+fn test_pricing_config_rejects_foreign_last_update_time() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().set_timestamp(1_000);
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+
+    let config = source_config(&e, &admin, soroban_sdk::vec![&e], 500, 1);
+    client.set_pricing_config(&config);
+    e.ledger().set_timestamp(2_000);
+    client.poke_price();
+
+    // The refresh moved the stored time on; the old copy is out of date
+    assert_eq!(
+        client.try_set_pricing_config(&config),
+        Err(Ok(PricingError::LastUpdateTimeMismatch))
+    );
+    let mut config = client.get_pricing_config();
+    assert_eq!(config.last_update_time, 2_000);
+    config.last_update_time = 99_999;
+    assert_eq!(
+        client.try_set_pricing_config(&config),
+        Err(Ok(PricingError::LastUpdateTimeMismatch))
+    );
+
+    config.last_update_time = 2_000;
+    config.max_deviation_bps = 300;
+    client.set_pricing_config(&config);
+    assert_eq!(client.get_cached_multiplier(), None);
+    assert_eq!(client.get_pricing_config(), config);
+}
+
+#[test]
+fn test_partial_pricing_updates() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 1000);
+
+    client.set_price_bounds(&0, &900);
+    assert_eq!(client.get_ticket_price(&tier_sym), 900);
+    assert_eq!(
+        client.try_set_price_bounds(&1000, &900),
+        Err(Ok(PricingError::InvalidPriceBounds))
+    );
+    // A rejected update leaves the config untouched
+    assert_eq!(client.get_ticket_price(&tier_sym), 900);
+
+    client.set_oracle(
+        &OracleKind::Dia,
+        &price_feed(&e, 80_000_000),
+        &admin,
+        &String::from_str(&e, "XLM/USD"),
+    );
+    client.set_price_bounds(&0, &i128::MAX);
+    assert_eq!(client.get_ticket_price(&tier_sym), 800);
+
+    client.update_oracle_reference(&80_000_000);
+    assert_eq!(client.get_ticket_price(&tier_sym), 1000);
+    assert_eq!(
+        client.try_update_oracle_reference(&0),
+        Err(Ok(PricingError::InvalidReferencePrice))
+    );
+
+    assert_eq!(
+        client.try_set_max_oracle_age(&0),
+        Err(Ok(PricingError::ZeroOracleAge))
+    );
+    assert_eq!(
+        client.try_set_oracle_sources(&soroban_sdk::vec![&e], &10_001, &1),
+        Err(Ok(PricingError::InvalidDeviationBand))
+    );
+    assert_eq!(
+        client.try_set_twap(&4, &10_001),
+        Err(Ok(PricingError::InvalidChangeClamp))
+    );
+
+    client.set_update_frequency(&300);
+    client.set_twap(&4, &1000);
+    client.set_stale_policy(&StalePricePolicy::HaltSales);
+    assert!(client
+        .try_set_oracle(
+            &OracleKind::Dia,
+            &admin,
+            &admin,
+            &String::from_str(&e, "XLM/USD")
+        )
+        .is_ok());
+}
+
+#[test]
+fn test_sale_and_refund_error_codes() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    assert_eq!(
+        client.try_initialize(
+            &admin,
+            &String::from_str(&e, "EventTicket"),
+            &String::from_str(&e, "TKT"),
+            &String::from_str(&e, "https://example.com"),
+            &0,
+            &0,
+        ),
+        Err(Ok(Error::AlreadyInitialized))
+    );
+
+    let tier_sym = Symbol::new(&e, "GA");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "General"),
+        &100,
+        &3,
+        &PricingStrategy::Standard,
+    );
+    let buyer = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+    token_admin.mint(&admin, &10_000);
+
+    assert_eq!(
+        client.try_purchase(&buyer, &token_id, &Symbol::new(&e, "NOPE"), &None, &None),
+        Err(Ok(Error::TierNotFound))
+    );
+
+    let first = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    let second = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    assert_eq!(
+        client.try_batch_mint(&buyer, &tier_sym, &2),
+        Err(Ok(Error::ExceedsMaxSupply))
+    );
+    client.batch_mint(&buyer, &tier_sym, &1);
+    assert_eq!(
+        client.try_purchase(&buyer, &token_id, &tier_sym, &None, &None),
+        Err(Ok(Error::TierSoldOut))
+    );
+
+    assert_eq!(
+        client.try_refund(&Address::generate(&e), &token_id, &first),
+        Err(Ok(Error::NotTicketOwner))
+    );
+    client.refund(&buyer, &token_id, &first);
+    assert_eq!(
+        client.try_refund(&buyer, &token_id, &first),
+        Err(Ok(Error::TicketInvalidated))
+    );
+    assert_eq!(client.try_get_ticket(&999), Err(Ok(Error::TicketNotFound)));
+
+    e.ledger().set_timestamp(e.ledger().timestamp() + 100_001);
+    assert_eq!(
+        client.try_refund(&buyer, &token_id, &second),
+        Err(Ok(Error::RefundWindowClosed))
+    );
+}
+
+#[test]
+fn test_tier_management() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let buyer = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+
+    let ga = Symbol::new(&e, "GA");
+    let vip = Symbol::new(&e, "VIP");
+    client.add_tier(
+        &ga,
+        &String::from_str(&e, "General"),
+        &100,
+        &2,
+        &PricingStrategy::Standard,
+    );
+    client.add_tier(
+        &vip,
+        &String::from_str(&e, "VIP"),
+        &500,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    assert_eq!(
+        client.list_tiers(),
+        soroban_sdk::vec![&e, ga.clone(), vip.clone()]
+    );
+
+    client.update_tier(
+        &ga,
+        &String::from_str(&e, "General Admission"),
+        &150,
+        &PricingStrategy::Standard,
+    );
+    let tier = client.get_tier(&ga);
+    assert_eq!(tier.name, String::from_str(&e, "General Admission"));
+    assert_eq!(tier.base_price, 150);
+    assert_eq!(tier.current_price, 150);
+    assert_eq!(
+        client.try_update_tier(
+            &ga,
+            &String::from_str(&e, "General"),
+            &-1,
+            &PricingStrategy::Standard
+        ),
+        Err(Ok(Error::NegativeBasePrice))
+    );
+
+    client.set_tier_active(&ga, &false);
+    assert_eq!(
+        client.try_purchase(&buyer, &token_id, &ga, &None, &None),
+        Err(Ok(Error::TierInactive))
+    );
+    client.set_tier_active(&ga, &true);
+
+    client.purchase(&buyer, &token_id, &ga, &None, &None);
+    client.purchase(&buyer, &token_id, &ga, &None, &None);
+    assert_eq!(
+        client.try_purchase(&buyer, &token_id, &ga, &None, &None),
+        Err(Ok(Error::TierSoldOut))
+    );
+    assert_eq!(
+        client.try_set_max_supply(&ga, &1),
+        Err(Ok(Error::SupplyBelowMinted))
+    );
+    assert_eq!(
+        client.try_set_max_supply(&vip, &0),
+        Err(Ok(Error::ZeroMaxSupply))
+    );
+    client.set_max_supply(&ga, &3);
+    client.purchase(&buyer, &token_id, &ga, &None, &None);
+    assert_eq!(client.get_tier(&ga).minted, 3);
+
+    assert_eq!(
+        client.try_set_tier_active(&Symbol::new(&e, "NOPE"), &false),
+        Err(Ok(Error::TierNotFound))
+    );
+}
+
+#[test]
+fn test_auction_tier_locked_after_sales() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let buyer = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+    let name = String::from_str(&e, "Premium");
+    let premium = Symbol::new(&e, "PREMIUM");
+    client.add_tier(
+        &premium,
+        &name,
+        &0,
+        &10,
+        &PricingStrategy::DutchAuction(auction(true)),
+    );
+    let ga = add_flat_tier(&e, &client, 100);
+
+    // Before any sale the auction can still be reworked
+    let mut longer = auction(true);
+    longer.floor_price = 300;
+    client.update_tier(
+        &premium,
+        &name,
+        &0,
+        &PricingStrategy::DutchAuction(longer.clone()),
+    );
+    client.set_max_supply(&premium, &5);
+
+    e.ledger().set_timestamp(1_000);
+    client.purchase(&buyer, &token_id, &premium, &None, &None);
+    client.purchase(&buyer, &token_id, &ga, &None, &None);
+
+    // Leaving the auction would drop the clearing price buyers are owed
+    assert_eq!(
+        client.try_update_tier(&premium, &name, &0, &PricingStrategy::Standard),
+        Err(Ok(Error::AuctionTierSold))
+    );
+    assert_eq!(
+        client.try_update_tier(
+            &premium,
+            &name,
+            &0,
+            &PricingStrategy::DutchAuction(auction(true))
+        ),
+        Err(Ok(Error::AuctionTierSold))
+    );
+    assert_eq!(
+        client.try_set_max_supply(&premium, &6),
+        Err(Ok(Error::AuctionTierSold))
+    );
+    // Renaming keeps the auction as it is
+    client.update_tier(
+        &premium,
+        &String::from_str(&e, "Premium Plus"),
+        &0,
+        &PricingStrategy::DutchAuction(longer),
+    );
+
+    // Full-price buyers of a sold tier cannot be turned into auction buyers
+    assert_eq!(
+        client.try_update_tier(
+            &ga,
+            &String::from_str(&e, "General"),
+            &100,
+            &PricingStrategy::DutchAuction(auction(true))
+        ),
+        Err(Ok(Error::AuctionTierSold))
+    );
+    client.set_max_supply(&ga, &20);
+}
+
+#[test]
+fn test_tier_sale_window() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().set_timestamp(1_000);
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let buyer = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+
+    let ga = Symbol::new(&e, "GA");
+    client.add_tier(
+        &ga,
+        &String::from_str(&e, "General"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    assert_eq!(
+        client.try_set_sale_window(&ga, &2_000, &2_000),
+        Err(Ok(Error::InvalidSaleWindow))
+    );
+    client.set_sale_window(&ga, &2_000, &3_000);
+
+    assert_eq!(
+        client.try_purchase(&buyer, &token_id, &ga, &None, &None),
+        Err(Ok(Error::SaleNotStarted))
+    );
+
+    e.ledger().set_timestamp(2_000);
+    client.purchase(&buyer, &token_id, &ga, &None, &None);
+    e.ledger().set_timestamp(3_000);
+    client.purchase(&buyer, &token_id, &ga, &None, &None);
+
+    e.ledger().set_timestamp(3_001);
+    assert_eq!(
+        client.try_purchase(&buyer, &token_id, &ga, &None, &None),
+        Err(Ok(Error::SaleEnded))
+    );
+
+    // Admin mints are not bound by the sale window
+    client.batch_mint(&buyer, &ga, &1);
+
+    // An open end keeps the tier on sale indefinitely
+    client.set_sale_window(&ga, &2_000, &0);
+    client.purchase(&buyer, &token_id, &ga, &None, &None);
+    assert_eq!(client.get_tier(&ga).minted, 4);
+}
+
+#[test]
+fn test_ticket_lifecycle_events() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let buyer = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+    token_admin.mint(&admin, &10_000);
+
+    let ga = Symbol::new(&e, "GA");
+    client.add_tier(
+        &ga,
+        &String::from_str(&e, "General"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    assert_eq!(
+        e.events().all(),
+        soroban_sdk::vec![
+            &e,
+            published(
+                &e,
+                &client.address,
+                &TierAdded {
+                    tier_symbol: ga.clone(),
+                    tier: client.get_tier(&ga),
+                }
+            )
+        ]
+    );
+
+    client.set_max_supply(&ga, &20);
+    assert_eq!(
+        e.events().all(),
+        soroban_sdk::vec![
+            &e,
+            published(
+                &e,
+                &client.address,
+                &TierUpdated {
+                    tier_symbol: ga.clone(),
+                    tier: client.get_tier(&ga),
+                }
+            )
+        ]
+    );
+
+    let mut config = source_config(&e, &admin, soroban_sdk::vec![&e], 500, 1);
+    config.price_floor = 50;
+    client.set_pricing_config(&config);
+    assert_eq!(
+        e.events().all(),
+        soroban_sdk::vec![
+            &e,
+            published(&e, &client.address, &PricingChanged { config })
+        ]
+    );
+
+    let ticket = client.purchase(&buyer, &token_id, &ga, &None, &None);
+    let events = e.events().all();
+    let price = client.get_ticket(&ticket).price_paid;
+    let purchased = published(
+        &e,
+        &client.address,
+        &TicketPurchased {
+            buyer: buyer.clone(),
+            tier: ga.clone(),
+            recipient: buyer.clone(),
+            token_id: ticket,
+            price,
+            payment_token: token_id.clone(),
+        },
+    );
+    assert!(events.contains(&purchased));
+
+    client.batch_mint(&buyer, &ga, &2);
+    let (_, topics, data) = e
+        .events()
+        .all()
+        .iter()
+        .find(|(_, topics, _)| {
+            topics.get(0).map(|t| Symbol::from_val(&e, &t))
+                == Some(Symbol::new(&e, "tickets_minted"))
+        })
+        .unwrap();
+    assert_eq!(
+        topics,
+        (
+            Symbol::new(&e, "tickets_minted"),
+            symbol_short!("v1"),
+            buyer.clone(),
+            ga.clone()
+        )
+            .into_val(&e)
+    );
+    let data: soroban_sdk::Map<Symbol, Val> = data.into_val(&e);
+    let token_ids: soroban_sdk::Vec<u32> =
+        data.get(Symbol::new(&e, "token_ids")).unwrap().into_val(&e);
+    assert_eq!(token_ids.len(), 2);
+    assert_eq!(client.owner_of(&token_ids.get(1).unwrap()), buyer);
+
+    client.refund(&buyer, &token_id, &ticket);
+    assert!(e.events().all().contains(published(
+        &e,
+        &client.address,
+        &TicketRefunded {
+            owner: buyer.clone(),
+            tier: ga.clone(),
+            token_id: ticket,
+            amount: price,
+            payment_token: token_id.clone(),
+        }
+    )));
+
+    client.emergency_freeze(&true);
+    assert_eq!(
+        e.events().all(),
+        soroban_sdk::vec![&e, published(&e, &client.address, &Frozen { frozen: true })]
+    );
+
+    // Schema v1: name, version, then the `#[topic]` fields
+    let (_, topics, _) = purchased;
+    assert_eq!(
+        topics,
+        (
+            Symbol::new(&e, "ticket_purchased"),
+            symbol_short!("v1"),
+            buyer,
+            ga
+        )
+            .into_val(&e)
+    );
+}
+
+#[test]
+fn test_token_ids_follow_sequential_mint() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let buyer = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+
+    let tier_sym = add_flat_tier(&e, &client, 100);
+    let other = Address::generate(&e);
+    client.batch_mint(&other, &tier_sym, &2);
+    let bought = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+
+    // Ids are consecutive from 1 and each ticket record sits on its own NFT
+    assert_eq!(bought, 3);
+    assert_eq!(client.owner_of(&1), other);
+    assert_eq!(client.owner_of(&2), other);
+    assert_eq!(client.owner_of(&3), buyer);
+    assert_eq!(client.get_ticket(&2).price_paid, 0);
+    assert_eq!(client.get_ticket(&3).price_paid, 100);
+}
+
+#[test]
+fn test_mint_never_overwrites_a_ticket() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let buyer = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+    let tier_sym = add_flat_tier(&e, &client, 100);
+
+    // A ticket record already sits on the next id
+    let ticket = Ticket {
+        tier_symbol: tier_sym.clone(),
+        purchase_time: 0,
+        price_paid: 100,
+        is_valid: true,
+        experiment_id: None,
+        variant: None,
+        checked_in_at: None,
+        payer: buyer.clone(),
+        payment_token: None,
+        attendee_assigned: false,
+    };
+    e.as_contract(&client.address, || {
+        e.storage().persistent().set(&DataKey::Ticket(1), &ticket);
+    });
+
+    assert_eq!(
+        client.try_purchase(&buyer, &token_id, &tier_sym, &None, &None),
+        Err(Ok(Error::TicketIdInUse))
+    );
+}
+
+#[test]
+fn test_ticket_enumeration_by_owner_and_tier() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let alice = Address::generate(&e);
+    let bob = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&alice, &10_000);
+    token_admin.mint(&admin, &10_000);
+
+    let ga = Symbol::new(&e, "GA");
+    let vip = Symbol::new(&e, "VIP");
+    for sym in [&ga, &vip] {
+        client.add_tier(
+            sym,
+            &String::from_str(&e, "Tier"),
+            &100,
+            &10,
+            &PricingStrategy::Standard,
+        );
+    }
+
+    let a1 = client.purchase(&alice, &token_id, &ga, &None, &None);
+    let a2 = client.purchase(&alice, &token_id, &vip, &None, &None);
+    let a3 = client.purchase(&alice, &token_id, &ga, &None, &None);
+    client.batch_mint(&bob, &ga, &2);
+
+    assert_eq!(client.total_supply(), 5);
+    assert_eq!(
+        client.get_tickets_by_owner(&alice, &0, &10),
+        soroban_sdk::vec![&e, a1, a2, a3]
+    );
+    assert_eq!(
+        client.get_tickets_by_owner(&alice, &1, &1),
+        soroban_sdk::vec![&e, a2]
+    );
+    assert_eq!(client.get_tickets_by_owner(&alice, &5, &10).len(), 0);
+    assert_eq!(
+        client.get_tickets_by_owner(&bob, &0, &10),
+        soroban_sdk::vec![&e, 4, 5]
+    );
+
+    assert_eq!(client.get_tier_ticket_count(&ga), 4);
+    assert_eq!(
+        client.get_tickets_by_tier(&ga, &0, &10),
+        soroban_sdk::vec![&e, a1, a3, 4, 5]
+    );
+    assert_eq!(
+        client.get_tickets_by_tier(&ga, &2, &2),
+        soroban_sdk::vec![&e, 4, 5]
+    );
+    assert_eq!(
+        client.get_tickets_by_tier(&vip, &0, &10),
+        soroban_sdk::vec![&e, a2]
+    );
+
+    // A refund drops the ticket from every index; the last ticket fills the gap
+    client.refund(&alice, &token_id, &a1);
+    assert_eq!(client.total_supply(), 4);
+    assert_eq!(
+        client.get_tickets_by_owner(&alice, &0, &10),
+        soroban_sdk::vec![&e, a3, a2]
+    );
+    assert_eq!(
+        client.get_tickets_by_tier(&ga, &0, &10),
+        soroban_sdk::vec![&e, 5, a3, 4]
+    );
+    assert_eq!(client.get_tier_ticket_count(&ga), 3);
+}
+
+#[test]
+fn test_ticket_listing_page_size_is_capped() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let holder = Address::generate(&e);
+    let ga = Symbol::new(&e, "GA");
+    client.add_tier(
+        &ga,
+        &String::from_str(&e, "General"),
+        &0,
+        &150,
+        &PricingStrategy::Standard,
+    );
+    for _ in 0..6 {
+        client.batch_mint(&holder, &ga, &20);
+    }
+
+    assert_eq!(
+        client.get_tickets_by_owner(&holder, &0, &u32::MAX).len(),
+        100
+    );
+    assert_eq!(client.get_tickets_by_tier(&ga, &100, &u32::MAX).len(), 20);
+}
+
+#[test]
+fn test_check_in_and_status_aware_token_uri() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 100);
+    let buyer = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+    token_admin.mint(&admin, &10_000);
+
+    let kept = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    let refunded = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+
+    // Without a template the collection URI is used
+    assert_eq!(
+        client.token_uri(&kept),
+        String::from_str(&e, "https://example.com1")
+    );
+
+    client.set_tier_uri(
+        &tier_sym,
+        &Some(String::from_str(&e, "ipfs://art/{status}/{id}.json")),
+    );
+    assert_eq!(
+        client.token_uri(&kept),
+        String::from_str(&e, "ipfs://art/valid/1.json")
+    );
+
+    client.refund(&buyer, &token_id, &refunded);
+    assert_eq!(
+        client.token_uri(&refunded),
+        String::from_str(&e, "ipfs://art/refunded/2.json")
+    );
+
+    client.set_event_end_time(&1_000);
+    client.check_in(&kept);
+    assert_eq!(
+        e.events().all(),
+        soroban_sdk::vec![
+            &e,
+            published(
+                &e,
+                &client.address,
+                &TicketCheckedIn {
+                    owner: buyer.clone(),
+                    tier: tier_sym.clone(),
+                    token_id: kept,
+                    timestamp: 0,
+                }
+            )
+        ]
+    );
+    assert_eq!(client.get_ticket_status(&kept), TicketStatus::CheckedIn);
+    assert_eq!(
+        client.token_uri(&kept),
+        String::from_str(&e, "ipfs://art/checked_in/1.json")
+    );
+
+    e.ledger().with_mut(|li| li.timestamp = 1_001);
+    assert_eq!(client.get_ticket_status(&kept), TicketStatus::Attended);
+    assert_eq!(
+        client.token_uri(&kept),
+        String::from_str(&e, "ipfs://art/attended/1.json")
+    );
+
+    // Clearing the template restores the collection URI
+    client.set_tier_uri(&tier_sym, &None);
+    assert_eq!(
+        client.token_uri(&kept),
+        String::from_str(&e, "https://example.com1")
+    );
+}
+
+#[test]
+fn test_check_in_error_codes() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 500);
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 100);
+    let buyer = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+    token_admin.mint(&admin, &10_000);
+    let first = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    let second = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    let third = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+
+    assert_eq!(
+        client.try_set_event_end_time(&499),
+        Err(Ok(TicketError::InvalidEventEndTime))
+    );
+    client.set_event_end_time(&1_000);
+    assert_eq!(client.get_event_info().end_time, 1_000);
+
+    assert_eq!(
+        client.try_check_in(&99),
+        Err(Err(InvokeError::Contract(Error::TicketNotFound as u32)))
+    );
+    // The same code as entry points that return `Error` directly
+    assert_eq!(
+        client.try_get_ticket_status(&99),
+        Err(Ok(Error::TicketNotFound))
+    );
+    client.check_in(&first);
+    assert_eq!(
+        client.try_check_in(&first),
+        Err(Ok(TicketError::AlreadyCheckedIn))
+    );
+    client.refund(&buyer, &token_id, &second);
+    assert_eq!(
+        client.try_check_in(&second),
+        Err(Err(InvokeError::Contract(Error::TicketInvalidated as u32)))
+    );
+    e.ledger().with_mut(|li| li.timestamp = 1_001);
+    assert_eq!(
+        client.try_check_in(&third),
+        Err(Ok(TicketError::CheckInClosed))
+    );
+
+    assert_eq!(
+        client.try_set_tier_uri(&Symbol::new(&e, "NOPE"), &None),
+        Err(Err(InvokeError::Contract(Error::TierNotFound as u32)))
+    );
+    let long = std::string::String::from("x").repeat(MAX_URI_TEMPLATE_LEN as usize + 1);
+    assert_eq!(
+        client.try_set_tier_uri(&tier_sym, &Some(String::from_str(&e, &long))),
+        Err(Ok(TicketError::UriTemplateTooLong))
+    );
+    assert_eq!(
+        client.try_ticket_metadata(&99),
+        Err(Ok(Error::TicketNotFound))
+    );
+}
+
+#[test]
+fn test_ticket_metadata_json() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 10);
+
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = Symbol::new(&e, "VIP");
+    client.add_tier(
+        &tier_sym,
+        &String::from_str(&e, "VIP \"Gold\""),
+        &250,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    client.set_tier_uri(&tier_sym, &Some(String::from_str(&e, "ipfs://vip/{id}")));
+    let buyer = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+    let ticket_id = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    client.set_event_end_time(&20);
+
+    assert_eq!(
+        client.ticket_metadata(&ticket_id),
+        String::from_str(
+            &e,
+            "{\"name\":\"EventTicket\",\"token_id\":1,\"tier\":\"VIP \\\"Gold\\\"\",\
+             \"status\":\"valid\",\"price_paid\":250,\"purchase_time\":10,\
+             \"event_start\":10,\"event_end\":20,\"image\":\"ipfs://vip/1\"}"
+        )
+    );
+}
+
+#[test]
+fn test_ttl_extended_on_write_and_bump() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 100);
+    let buyer = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+    let ticket_id = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+
+    e.as_contract(&client.address, || {
+        let persistent = e.storage().persistent();
+        assert_eq!(
+            persistent.get_ttl(&DataKey::Ticket(ticket_id)),
+            storage::TTL_PERSISTENT
+        );
+        assert_eq!(
+            persistent.get_ttl(&NFTStorageKey::Owner(ticket_id)),
+            storage::TTL_PERSISTENT
+        );
+        assert_eq!(
+            persistent.get_ttl(&DataKey::Tier(tier_sym.clone())),
+            storage::TTL_PERSISTENT
+        );
+        assert_eq!(e.storage().instance().get_ttl(), storage::TTL_INSTANCE);
+    });
+
+    // Admin setters keep the instance alive too
+    e.ledger().with_mut(|li| li.sequence_number += 17280);
+    client.set_tier_active(&tier_sym, &false);
+    e.as_contract(&client.address, || {
+        assert_eq!(e.storage().instance().get_ttl(), storage::TTL_INSTANCE);
+    });
+
+    // Unknown keys are skipped
+    let keys = soroban_sdk::vec![
+        &e,
+        DataKey::Ticket(ticket_id),
+        DataKey::Tier(tier_sym.clone()),
+        DataKey::Ticket(99),
+    ];
+    assert_eq!(client.bump(&keys), 2);
+}
+
+#[test]
+fn test_tickets_remain_valid_past_ttl_thresholds() {
+    const DAY: u32 = 17280;
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 100);
+    let buyer = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+    let ticket_id = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    let stale_id = client.purchase(&buyer, &token_id, &tier_sym, &None, &None);
+    let keys = soroban_sdk::vec![
+        &e,
+        DataKey::Ticket(ticket_id),
+        DataKey::Tier(tier_sym.clone())
+    ];
+
+    // A keeper bumps the sale every 25 days.  The test host restores
+    // archived entries on access, so liveness is checked through the TTLs.
+    for day in [25, 50, 75, 100] {
+        e.ledger().with_mut(|li| li.sequence_number += 25 * DAY);
+        e.as_contract(&client.address, || {
+            let persistent = e.storage().persistent();
+            let remaining = storage::TTL_PERSISTENT - 25 * DAY;
+            assert_eq!(persistent.get_ttl(&DataKey::Ticket(ticket_id)), remaining);
+            assert_eq!(
+                persistent.get_ttl(&NFTStorageKey::Owner(ticket_id)),
+                remaining
+            );
+            assert_eq!(
+                e.storage().instance().get_ttl(),
+                storage::TTL_INSTANCE - 25 * DAY
+            );
+            if day == 75 {
+                // Left alone, the other ticket lapses before day 100
+                assert_eq!(
+                    persistent.get_ttl(&DataKey::Ticket(stale_id)),
+                    storage::TTL_PERSISTENT - 75 * DAY
+                );
+            }
+        });
+        client.bump(&keys);
+    }
+
+    // 100 days in, past the 90-day TTL the ticket was written with
+    assert!(client.validate_ticket(&ticket_id));
+    assert_eq!(client.owner_of(&ticket_id), buyer);
+    assert_eq!(client.get_tier(&tier_sym).minted, 2);
+    client.check_in(&ticket_id);
+    assert_eq!(
+        client.get_ticket_status(&ticket_id),
+        TicketStatus::CheckedIn
+    );
+}
+
+#[test]
+fn test_purchase_for_and_assign_attendee() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    e.ledger().with_mut(|li| li.timestamp = 10);
+
+    let tier_sym = add_flat_tier(&e, &client, 100);
+    let company = Address::generate(&e);
+    let employee = Address::generate(&e);
+    let attendee = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&company, &10_000);
+    token_admin.mint(&admin, &10_000);
+    let token = token::Client::new(&e, &token_id);
+
+    let ticket_id = client.purchase_for(&company, &employee, &token_id, &tier_sym, &None, &None);
+    assert!(e.events().all().contains(published(
+        &e,
+        &client.address,
+        &TicketPurchased {
+            buyer: company.clone(),
+            tier: tier_sym.clone(),
+            recipient: employee.clone(),
+            token_id: ticket_id,
+            price: 100,
+            payment_token: token_id.clone(),
+        }
+    )));
+    assert_eq!(client.owner_of(&ticket_id), employee);
+    assert_eq!(token.balance(&company), 9_900);
+    assert_eq!(client.get_ticket(&ticket_id).payer, company);
+
+    client.assign_attendee(&ticket_id, &attendee);
+    assert_eq!(
+        e.events().all().slice(1..),
+        soroban_sdk::vec![
+            &e,
+            published(
+                &e,
+                &client.address,
+                &AttendeeAssigned {
+                    attendee: attendee.clone(),
+                    tier: tier_sym.clone(),
+                    token_id: ticket_id,
+                    previous: employee.clone(),
+                }
+            )
+        ]
+    );
+    assert_eq!(client.owner_of(&ticket_id), attendee);
+    assert_eq!(client.balance(&employee), 0);
+    assert_eq!(
+        client.get_tickets_by_owner(&attendee, &0, &10),
+        soroban_sdk::vec![&e, ticket_id]
+    );
+    assert_eq!(client.get_tickets_by_owner(&employee, &0, &10).len(), 0);
+    assert_eq!(
+        client.get_tickets_by_tier(&tier_sym, &0, &10),
+        soroban_sdk::vec![&e, ticket_id]
+    );
+    assert_eq!(
+        client.try_assign_attendee(&ticket_id, &employee),
+        Err(Ok(TicketError::AttendeeAlreadyAssigned))
+    );
+
+    // The attendee asks for the refund; the company gets the money back
+    client.refund(&attendee, &token_id, &ticket_id);
+    assert_eq!(token.balance(&company), 10_000);
+    assert_eq!(token.balance(&attendee), 0);
+    assert_eq!(client.balance(&attendee), 0);
+}
+
+#[test]
+fn test_assign_attendee_error_codes() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    e.ledger().with_mut(|li| li.timestamp = 10);
+
+    let tier_sym = add_flat_tier(&e, &client, 100);
+    let company = Address::generate(&e);
+    let employee = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&company, &10_000);
+    token_admin.mint(&admin, &10_000);
+    let refunded = client.purchase_for(&company, &employee, &token_id, &tier_sym, &None, &None);
+    let checked_in = client.purchase_for(&company, &employee, &token_id, &tier_sym, &None, &None);
+    let late = client.purchase_for(&company, &employee, &token_id, &tier_sym, &None, &None);
+
+    assert_eq!(
+        client.try_refund(&Address::generate(&e), &token_id, &refunded),
+        Err(Ok(Error::NotTicketOwner))
+    );
+    client.refund(&company, &token_id, &refunded);
+    assert_eq!(
+        client.try_assign_attendee(&refunded, &company),
+        Err(Err(InvokeError::Contract(Error::TicketInvalidated as u32)))
+    );
+    client.check_in(&checked_in);
+    assert_eq!(
+        client.try_assign_attendee(&checked_in, &company),
+        Err(Ok(TicketError::AlreadyCheckedIn))
+    );
+    assert_eq!(
+        client.try_assign_attendee(&99, &company),
+        Err(Err(InvokeError::Contract(Error::TicketNotFound as u32)))
+    );
+
+    // With an identity registry the attendee needs a DID
+    let registry_id = e.register(MockIdentityRegistry, ());
+    client.set_identity_registry(&registry_id);
+    let attendee = Address::generate(&e);
+    assert_eq!(
+        client.try_assign_attendee(&late, &attendee),
+        Err(Err(InvokeError::Contract(Error::DidRequired as u32)))
+    );
+    MockIdentityRegistryClient::new(&e, &registry_id)
+        .set_did(&attendee, &String::from_str(&e, "did:stellar:attendee"));
+
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    assert_eq!(
+        client.try_assign_attendee(&late, &attendee),
+        Err(Ok(TicketError::EventStarted))
+    );
+    e.ledger().with_mut(|li| li.timestamp = 999);
+    client.assign_attendee(&late, &attendee);
+    assert_eq!(client.owner_of(&late), attendee);
+}
+
+fn add_sessions(client: &SoulboundTicketContractClient) -> (Symbol, Symbol, Symbol) {
+    let day1 = symbol_short!("DAY1");
+    let day2 = symbol_short!("DAY2");
+    let workshop = symbol_short!("WS1");
+    client.add_session(&day1, &1_000, &2_000, &2);
+    client.add_session(&day2, &2_000, &3_000, &0);
+    client.add_session(&workshop, &1_200, &1_500, &1);
+    (day1, day2, workshop)
+}
+
+#[test]
+fn test_session_capacity_and_check_in() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let (day1, day2, workshop) = add_sessions(&client);
+
+    let full = symbol_short!("FULL");
+    let day_pass = symbol_short!("DAYPASS");
+    for tier in [&full, &day_pass] {
+        client.add_tier(
+            tier,
+            &String::from_str(&e, "Pass"),
+            &100,
+            &10,
+            &PricingStrategy::Standard,
+        );
+    }
+    let all = soroban_sdk::vec![&e, day1.clone(), day2.clone(), workshop.clone()];
+    client.set_tier_sessions(&full, &all);
+    client.set_tier_sessions(&day_pass, &soroban_sdk::vec![&e, day1.clone()]);
+    assert_eq!(client.get_tier_sessions(&full), all);
+    assert_eq!(client.list_sessions(), all);
+
+    let buyer = Address::generate(&e);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    token_admin.mint(&buyer, &10_000);
+    token_admin.mint(&admin, &10_000);
+
+    let full_ticket = client.purchase(&buyer, &token_id, &full, &None, &None);
+    assert_eq!(client.get_session(&workshop).sold, 1);
+    assert_eq!(client.get_session(&day2).sold, 1);
+
+    // The workshop is full, so no second full pass; day one still has room
+    assert_eq!(
+        client.try_purchase(&buyer, &token_id, &full, &None, &None),
+        Err(Ok(Error::SessionFull))
+    );
+    assert_eq!(
+        client.try_batch_mint(&buyer, &full, &1),
+        Err(Ok(Error::SessionFull))
+    );
+    let day_ticket = client.purchase(&buyer, &token_id, &day_pass, &None, &None);
+    assert_eq!(client.get_session(&day1).sold, 2);
+    assert_eq!(
+        client.try_purchase(&buyer, &token_id, &day_pass, &None, &None),
+        Err(Ok(Error::SessionFull))
+    );
+
+    e.ledger().with_mut(|li| li.timestamp = 1_300);
+    client.check_in_session(&full_ticket, &workshop);
+    assert_eq!(
+        client.get_session_check_in(&full_ticket, &workshop),
+        Some(1_300)
+    );
+    assert_eq!(client.get_session(&workshop).checked_in, 1);
+    assert_eq!(
+        client.try_check_in_session(&full_ticket, &workshop),
+        Err(Ok(SessionError::AlreadyAdmitted))
+    );
+    assert_eq!(
+        client.try_check_in_session(&day_ticket, &workshop),
+        Err(Ok(SessionError::SessionNotGranted))
+    );
+    client.check_in_session(&day_ticket, &day1);
+    assert_eq!(client.get_session_check_in(&day_ticket, &day2), None);
+
+    e.ledger().with_mut(|li| li.timestamp = 3_001);
+    assert_eq!(
+        client.try_check_in_session(&full_ticket, &day2),
+        Err(Ok(SessionError::SessionEnded))
+    );
+
+    // Refunding the full pass frees its seats
+    e.ledger().with_mut(|li| li.timestamp = 1_100);
+    client.refund(&buyer, &token_id, &full_ticket);
+    assert_eq!(client.get_session(&workshop).sold, 0);
+    assert_eq!(client.get_session(&day1).sold, 1);
+    assert_eq!(
+        client.try_check_in_session(&full_ticket, &day1),
+        Err(Err(InvokeError::Contract(Error::TicketInvalidated as u32)))
+    );
+    client.purchase(&buyer, &token_id, &full, &None, &None);
+}
+
+#[test]
+fn test_session_configuration_error_codes() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let (day1, _day2, workshop) = add_sessions(&client);
+
+    assert_eq!(
+        client.try_add_session(&day1, &1_000, &2_000, &2),
+        Err(Ok(SessionError::SessionExists))
+    );
+    assert_eq!(
+        client.try_add_session(&symbol_short!("BAD"), &2_000, &2_000, &2),
+        Err(Ok(SessionError::InvalidSessionWindow))
+    );
+    assert_eq!(
+        client.try_set_tier_sessions(&symbol_short!("NOPE"), &soroban_sdk::vec![&e, day1.clone()]),
+        Err(Err(InvokeError::Contract(Error::TierNotFound as u32)))
+    );
+
+    let tier_sym = add_flat_tier(&e, &client, 0);
+    assert_eq!(
+        client.try_set_tier_sessions(&tier_sym, &soroban_sdk::vec![&e, symbol_short!("NOPE")]),
+        Err(Ok(SessionError::SessionNotFound))
+    );
+    client.set_tier_sessions(&tier_sym, &soroban_sdk::vec![&e, workshop.clone()]);
+    client.batch_mint(&admin, &tier_sym, &1);
+    assert_eq!(
+        client.try_set_tier_sessions(&tier_sym, &soroban_sdk::vec![&e, day1.clone()]),
+        Err(Ok(SessionError::TierHasTickets))
+    );
+    assert_eq!(
+        client.try_set_session_capacity(&symbol_short!("NOPE"), &5),
+        Err(Ok(SessionError::SessionNotFound))
+    );
+
+    client.set_session_capacity(&workshop, &0);
+    client.batch_mint(&admin, &tier_sym, &1);
+    assert_eq!(
+        client.try_set_session_capacity(&workshop, &1),
+        Err(Ok(SessionError::CapacityBelowSold))
+    );
+    client.set_session_capacity(&workshop, &2);
+    assert_eq!(client.get_session(&workshop).capacity, 2);
+    assert_eq!(
+        client.try_check_in_session(&99, &workshop),
+        Err(Err(InvokeError::Contract(Error::TicketNotFound as u32)))
+    );
+}
+
+/// A sale whose tier is `VIP`, plus a `GA` tier, a `MERCH` add-on open
+/// to every tier and a `PARK` add-on limited to two and to VIP holders.
+fn setup_addon_sale<'a>(e: &Env) -> (Sale<'a>, Symbol, Symbol, Symbol) {
+    let sale = setup_sale(e, "VIP", 100, 10, PricingStrategy::Standard);
+    let (client, token_id) = (&sale.client, &sale.token_id);
+    let vip = sale.tier.clone();
+    let general = symbol_short!("GA");
+    client.add_tier(
+        &general,
+        &String::from_str(e, "General"),
+        &100,
+        &10,
+        &PricingStrategy::Standard,
+    );
+    let merch = symbol_short!("MERCH");
+    let parking = symbol_short!("PARK");
+    client.add_addon(
+        &merch,
+        &String::from_str(e, "T-shirt"),
+        token_id,
+        &50,
+        &0,
+        &None,
+    );
+    client.add_addon(
+        &parking,
+        &String::from_str(e, "VIP parking"),
+        token_id,
+        &30,
+        &2,
+        &Some(vip),
+    );
+    (sale, general, merch, parking)
+}
+
+#[test]
+fn test_addons_purchase_redeem_and_refund() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (
+        Sale {
+            client,
+            admin,
+            token_id,
+            token_admin,
+            tier: vip,
+        },
+        _general,
+        merch,
+        parking,
+    ) = setup_addon_sale(&e);
+    let buyer = Address::generate(&e);
+    token_admin.mint(&buyer, &10_000);
+    token_admin.mint(&admin, &10_000);
+    let token = token::Client::new(&e, &token_id);
+
+    let mut addons = Map::new(&e);
+    addons.set(merch.clone(), 2);
+    addons.set(parking.clone(), 1);
+    let ticket_id = client.purchase_with_addons(&buyer, &token_id, &vip, &None, &None, &addons);
+    assert_eq!(token.balance(&buyer), 10_000 - 100 - 2 * 50 - 30);
+    assert_eq!(
+        client.get_ticket_addons(&ticket_id),
+        soroban_sdk::vec![&e, merch.clone(), parking.clone()]
+    );
+
+    client.purchase_addon(&buyer, &ticket_id, &merch, &1);
+    assert_eq!(
+        client.get_ticket_addon(&ticket_id, &merch),
+        Some(TicketAddOn {
+            buyer: buyer.clone(),
+            quantity: 3,
+            redeemed: 0,
+            amount_paid: 150,
+        })
+    );
+    assert_eq!(client.get_addon(&merch).sold, 3);
+    assert_eq!(client.get_addon(&parking).sold, 1);
+
+    assert_eq!(
+        client.try_redeem_addon(&ticket_id, &merch, &1),
+        Err(Err(InvokeError::Contract(TicketError::NotCheckedIn as u32)))
+    );
+    client.check_in(&ticket_id);
+    client.redeem_addon(&ticket_id, &merch, &1);
+    assert_eq!(
+        client.try_redeem_addon(&ticket_id, &parking, &2),
+        Err(Ok(AddOnError::NothingToRedeem))
+    );
+
+    // The ticket and every unredeemed unit are refunded together
+    client.refund(&buyer, &token_id, &ticket_id);
+    assert_eq!(token.balance(&buyer), 10_000 - 50);
+    assert_eq!(client.get_addon(&merch).sold, 1);
+    assert_eq!(client.get_addon(&parking).sold, 0);
+    let line = client.get_ticket_addon(&ticket_id, &merch).unwrap();
+    assert_eq!((line.quantity, line.redeemed, line.amount_paid), (1, 1, 50));
+}
+
+#[test]
+fn test_addon_error_codes() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let (
+        Sale {
+            client,
+            token_id,
+            token_admin,
+            tier: vip,
+            ..
+        },
+        general,
+        merch,
+        parking,
+    ) = setup_addon_sale(&e);
+    let company = Address::generate(&e);
+    let employee = Address::generate(&e);
+    token_admin.mint(&company, &10_000);
+    token_admin.mint(&employee, &10_000);
+
+    assert_eq!(
+        client.try_add_addon(&merch, &String::from_str(&e, "x"), &token_id, &1, &0, &None),
+        Err(Ok(AddOnError::AddOnExists))
+    );
+    assert_eq!(
+        client.try_add_addon(
+            &symbol_short!("NEG"),
+            &String::from_str(&e, "x"),
+            &token_id,
+            &-1,
+            &0,
+            &None
+        ),
+        Err(Ok(AddOnError::NegativePrice))
+    );
+    assert_eq!(
+        client.try_add_addon(
+            &symbol_short!("NOTIER"),
+            &String::from_str(&e, "x"),
+            &token_id,
+            &1,
+            &0,
+            &Some(symbol_short!("NOPE"))
+        ),
+        Err(Err(InvokeError::Contract(Error::TierNotFound as u32)))
+    );
+
+    let vip_ticket = client.purchase_for(&company, &employee, &token_id, &vip, &None, &None);
+    let ga_ticket = client.purchase(&employee, &token_id, &general, &None, &None);
+
+    assert_eq!(
+        client.try_purchase_addon(&employee, &ga_ticket, &parking, &1),
+        Err(Ok(AddOnError::TierNotEligible))
+    );
+    assert_eq!(
+        client.try_purchase_addon(&employee, &vip_ticket, &parking, &3),
+        Err(Ok(AddOnError::OutOfStock))
+    );
+    assert_eq!(
+        client.try_purchase_addon(&employee, &vip_ticket, &merch, &0),
+        Err(Ok(AddOnError::InvalidQuantity))
+    );
+    assert_eq!(
+        client.try_purchase_addon(&Address::generate(&e), &vip_ticket, &merch, &1),
+        Err(Ok(AddOnError::NotTicketHolder))
+    );
+    assert_eq!(
+        client.try_purchase_addon(&employee, &99, &merch, &1),
+        Err(Err(InvokeError::Contract(Error::TicketNotFound as u32)))
+    );
+
+    // Holder and payer may both buy, but each line has one buyer
+    client.purchase_addon(&company, &vip_ticket, &parking, &2);
+    client.purchase_addon(&employee, &vip_ticket, &merch, &1);
+    assert_eq!(
+        client.try_purchase_addon(&company, &vip_ticket, &merch, &1),
+        Err(Ok(AddOnError::NotAddOnBuyer))
+    );
+    assert_eq!(
+        client.try_set_addon_stock(&parking, &1),
+        Err(Ok(AddOnError::StockBelowSold))
+    );
+    client.set_addon_active(&merch, &false);
+    assert_eq!(
+        client.try_purchase_addon(&employee, &vip_ticket, &merch, &1),
+        Err(Ok(AddOnError::AddOnInactive))
+    );
+
+    // Add-on failures abort the combined purchase, ticket included
+    let mut addons = Map::new(&e);
+    addons.set(parking.clone(), 1);
+    assert_eq!(
+        client.try_purchase_with_addons(&employee, &token_id, &vip, &None, &None, &addons),
+        Err(Err(InvokeError::Contract(AddOnError::OutOfStock as u32)))
+    );
+    assert_eq!(client.get_tier(&vip).minted, 1);
+}
+
+#[test]
+fn test_payout_split_fee_and_withdrawals() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let Sale {
+        client,
+        token_id,
+        token_admin,
+        tier,
+        ..
+    } = setup_sale(&e, "GA", 1000, 100, PricingStrategy::Standard);
+    let token = token::Client::new(&e, &token_id);
+    let factory = Address::generate(&e);
+    let platform = Address::generate(&e);
+    let (a, b, c) = (
+        Address::generate(&e),
+        Address::generate(&e),
+        Address::generate(&e),
+    );
+
+    client.set_platform_fee(&factory, &platform, &250);
+    client.set_payees(&soroban_sdk::vec![
+        &e,
+        Payee {
+            recipient: a.clone(),
+            share_bps: 3334,
+        },
+        Payee {
+            recipient: b.clone(),
+            share_bps: 3333,
+        },
+        Payee {
+            recipient: c.clone(),
+            share_bps: 3333,
+        },
+    ]);
+
+    let mut tickets = soroban_sdk::Vec::<u32>::new(&e);
+    let buyer = Address::generate(&e);
+    token_admin.mint(&buyer, &3000);
+    for _ in 0..3 {
+        tickets.push_back(client.purchase(&buyer, &token_id, &tier, &None, &None));
+    }
+    // Proceeds stay in the contract until withdrawn
+    assert_eq!(token.balance(&client.address), 3000);
+    client.refund(&buyer, &token_id, &tickets.get(0).unwrap());
+    assert_eq!(
+        client.get_revenue(&token_id),
+        Revenue {
+            gross: 3000,
+            refunded: 1000,
+        }
+    );
+
+    assert_eq!(
+        client.try_withdraw(&a, &token_id),
+        Err(Ok(PayoutError::WithdrawLocked))
+    );
+    assert_eq!(
+        client.try_set_payees(&soroban_sdk::vec![
+            &e,
+            Payee {
+                recipient: a.clone(),
+                share_bps: 10_000,
+            }
+        ]),
+        Err(Ok(PayoutError::RevenueRecorded))
+    );
+    assert_eq!(
+        client.try_set_platform_fee(&factory, &platform, &0),
+        Err(Ok(PayoutError::RevenueRecorded))
+    );
+
+    e.ledger().with_mut(|l| l.timestamp += 100_001);
+
+    // Net 2000: 2.5% platform fee, then 1950 split with the rounding dust
+    // going to the first payee
+    assert_eq!(client.get_withdrawable(&b, &token_id), 649);
+    assert_eq!(client.withdraw(&a, &token_id), 652);
+    assert_eq!(client.withdraw(&b, &token_id), 649);
+    assert_eq!(client.withdraw(&c, &token_id), 649);
+    assert_eq!(client.withdraw_platform_fee(&token_id), 50);
+    assert!(e.events().all().contains(published(
+        &e,
+        &client.address,
+        &Withdrawn {
+            recipient: platform.clone(),
+            payment_token: token_id.clone(),
+            amount: 50,
+            platform: true,
+        }
+    )));
+    assert_eq!(token.balance(&a), 652);
+    assert_eq!(token.balance(&platform), 50);
+    assert_eq!(token.balance(&client.address), 0);
+
+    assert_eq!(
+        client.try_withdraw(&a, &token_id),
+        Err(Ok(PayoutError::NothingToWithdraw))
+    );
+    assert_eq!(
+        client.try_withdraw_platform_fee(&token_id),
+        Err(Ok(PayoutError::NothingToWithdraw))
+    );
+    assert_eq!(
+        client.try_withdraw(&buyer, &token_id),
+        Err(Ok(PayoutError::NotPayee))
+    );
+}
+
+#[test]
+fn test_payout_error_codes() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let Sale {
+        client,
+        token_id,
+        token_admin,
+        tier,
+        ..
+    } = setup_sale(&e, "GA", 1000, 100, PricingStrategy::Standard);
+    let (other_token, other_admin) = create_payment_token(&e, &Address::generate(&e));
+    let (a, b) = (Address::generate(&e), Address::generate(&e));
+    let payee = |recipient: &Address, share_bps: u32| Payee {
+        recipient: recipient.clone(),
+        share_bps,
+    };
+
+    assert_eq!(
+        client.try_set_payees(&soroban_sdk::vec![&e, payee(&a, 5000), payee(&b, 4999)]),
+        Err(Ok(PayoutError::InvalidShares))
+    );
+    assert_eq!(
+        client.try_set_payees(&soroban_sdk::vec![&e, payee(&a, 5000), payee(&a, 5000)]),
+        Err(Ok(PayoutError::DuplicatePayee))
+    );
+    assert_eq!(client.get_payees().len(), 1);
+
+    let factory = Address::generate(&e);
+    assert_eq!(
+        client.try_set_platform_fee(&factory, &a, &10_001),
+        Err(Ok(PayoutError::InvalidFee))
+    );
+    client.set_platform_fee(&factory, &a, &100);
+    assert_eq!(
+        client.try_set_platform_fee(&b, &b, &0),
+        Err(Ok(PayoutError::NotPlatform))
+    );
+
+    // A ticket is only refunded in the token it was paid with
+    let buyer = Address::generate(&e);
+    token_admin.mint(&buyer, &1000);
+    other_admin.mint(&client.address, &1000);
+    let ticket_id = client.purchase(&buyer, &token_id, &tier, &None, &None);
+    assert_eq!(
+        client.try_refund(&buyer, &other_token, &ticket_id),
+        Err(Ok(Error::WrongPaymentToken))
+    );
+    assert_eq!(client.get_withdrawable(&a, &other_token), 0);
+}
+
+#[test]
+fn test_attendance_badges_and_reputation() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 100);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    let holder = Address::generate(&e);
+    token_admin.mint(&holder, &10_000);
+    let first = client.purchase(&holder, &token_id, &tier_sym, &None, &None);
+    let second = client.purchase(&holder, &token_id, &tier_sym, &None, &None);
+
+    let registry_id = e.register(MockIdentityRegistry, ());
+    let registry = MockIdentityRegistryClient::new(&e, &registry_id);
+    let did = String::from_str(&e, "did:stellar:holder");
+    registry.set_did(&holder, &did);
+    client.set_identity_registry(&registry_id);
+
+    client.set_event_end_time(&1_000);
+    client.check_in(&first);
+    client.check_in(&second);
+    e.ledger().with_mut(|li| li.timestamp = 1_001);
+
+    let badge = client.claim_badge(&first);
+    assert_eq!(
+        badge,
+        AttendanceBadge {
+            holder: holder.clone(),
+            token_id: first,
+            tier: tier_sym.clone(),
+            event_name: String::from_str(&e, "EventTicket"),
+            event_start: 0,
+            event_end: 1_000,
+            checked_in_at: 0,
+            claimed_at: 1_001,
+        }
+    );
+    assert!(e.events().all().contains(published(
+        &e,
+        &client.address,
+        &BadgeClaimed {
+            holder: holder.clone(),
+            tier: tier_sym.clone(),
+            token_id: first,
+            reputation_recorded: true,
+        }
+    )));
+    assert!(registry.has_attended(&client.address, &did));
+
+    // The registry counts the DID once per event; the badge is still issued
+    client.claim_badge(&second);
+    assert!(e.events().all().contains(published(
+        &e,
+        &client.address,
+        &BadgeClaimed {
+            holder: holder.clone(),
+            tier: tier_sym.clone(),
+            token_id: second,
+            reputation_recorded: false,
+        }
+    )));
+    assert_eq!(client.get_badge(&first), Some(badge));
+    assert_eq!(
+        client.get_badges(&holder),
+        soroban_sdk::vec![&e, first, second]
+    );
+}
+
+#[test]
+fn test_badge_error_codes() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let client = create_contract(&e, &admin);
+    let tier_sym = add_flat_tier(&e, &client, 100);
+    let (token_id, token_admin) = create_payment_token(&e, &admin);
+    let holder = Address::generate(&e);
+    token_admin.mint(&holder, &10_000);
+    let attended = client.purchase(&holder, &token_id, &tier_sym, &None, &None);
+    let absent = client.purchase(&holder, &token_id, &tier_sym, &None, &None);
+    let refunded = client.purchase(&holder, &token_id, &tier_sym, &None, &None);
+    client.refund(&holder, &token_id, &refunded);
+
+    client.set_event_end_time(&1_000);
+    client.check_in(&attended);
+    assert_eq!(
+        client.try_claim_badge(&attended),
+        Err(Ok(BadgeError::EventNotEnded))
+    );
+
+    e.ledger().with_mut(|li| li.timestamp = 1_001);
+    assert_eq!(
+        client.try_claim_badge(&99),
+        Err(Err(InvokeError::Contract(Error::TicketNotFound as u32)))
+    );
+    assert_eq!(
+        client.try_claim_badge(&refunded),
+        Err(Err(InvokeError::Contract(Error::TicketInvalidated as u32)))
+    );
+    assert_eq!(
+        client.try_claim_badge(&absent),
+        Err(Err(InvokeError::Contract(TicketError::NotCheckedIn as u32)))
+    );
+
+    // Without an identity registry nothing is reported
+    client.claim_badge(&attended);
+    assert!(e.events().all().contains(published(
+        &e,
+        &client.address,
+        &BadgeClaimed {
+            holder: holder.clone(),
+            tier: tier_sym.clone(),
+            token_id: attended,
+            reputation_recorded: false,
+        }
+    )));
+    assert_eq!(
+        client.try_claim_badge(&attended),
+        Err(Ok(BadgeError::AlreadyClaimed))
+    );
+    assert_eq!(client.get_badge(&absent), None);
+}